@@ -24,29 +24,44 @@ pub enum NativeError {
     UniformLocation(Option<String>),
     UniformIndex(Option<String>),
     MipsPowerOf2,
-    NoExtension,
-    MissingShaderProgram,
+    NoExtension(Option<String>),
+    MultiDrawLength,
+    MissingShaderProgram(Option<String>),
+    MissingShader(Option<String>),
+    ShaderInUse(Option<String>),
     NoCreateBuffer,
     NoCreateFrameBuffer,
     NoCreateRenderBuffer,
     NoExistingBuffer,
     NoCreateTexture,
-    MissingTexture,
+    MissingTexture(Option<String>),
     MissingTextureSampler(Option<String>),
-    MissingBuffer,
-    MissingRenderBuffer,
-    MissingFrameBuffer,
+    MissingBuffer(Option<String>),
+    MissingRenderBuffer(Option<String>),
+    MissingFrameBuffer(Option<String>),
     UniformSize,
     UniformMatrixMustBeFloat,
-    UniformType,
+    UniformType(Option<String>),
     UniformBufferName,
     UniformBufferParameter,
     UniformBufferMissing(Option<String>),
     UniformBufferOffsetMissing(Option<(String, String)>),
     UniformBufferBlockIndexMissing(Option<String>),
     UniformBufferTarget,
-    VertexArrayMissing,
+    UniformBufferLayout(Option<String>),
+    VertexArrayMissing(Option<String>),
     VertexArrayCreate,
+    VertexLayout(Option<String>),
+    SamplerMissing(Option<String>),
+    SamplerCreate,
+    QueryCreate,
+    GpuProfilerDisabled,
+    GpuScopeOpen,
+    GpuScopeNotOpen,
+    ShaderPreprocess(Option<String>),
+    #[cfg(feature = "webgl")]
+    ShaderCompile(crate::webgl::ShaderCompileError),
+    StateStackEmpty,
     JsValueExpectedBool,
     JsValueExpectedNumber,
     WebGl1TextureArray2d,
@@ -56,7 +71,12 @@ pub enum NativeError {
     WebGlVersion2,
     TextureCubeFaceNotCube,
     TextureMissingCubeFace,
-    NoTextureTarget,
+    TextureSubImageBounds(Option<String>),
+    TextureImmutable(Option<String>),
+    TextureFormatMismatch(Option<String>),
+    TextureStorage(Option<String>),
+    CompressedTexture(Option<String>),
+    NoTextureTarget(Option<String>),
     Internal,
     FrameBuffer(Option<String>),
 }
@@ -70,6 +90,14 @@ impl Error {
         }
     }
 
+    //see NativeError::with_label
+    pub fn with_label(self: Self, label: Option<&str>) -> Self {
+        match self {
+            Error::Native(err) => Error::Native(err.with_label(label)),
+            _ => self,
+        }
+    }
+
     pub fn is_abort(&self) -> bool {
         match self {
             Error::Native(err) => std::mem::discriminant(err) == std::mem::discriminant(&NativeError::Abort),
@@ -135,22 +163,36 @@ impl NativeError {
             NativeError::UniformLocation(_optional_name) => "Couldn't get uniform location",
             NativeError::UniformIndex(_optional_name) => "Couldn't get uniform index",
             NativeError::MipsPowerOf2 => "mipmapping requires that textures be power of 2",
-            NativeError::NoExtension => "extension not found",
+            NativeError::NoExtension(_) => "extension not found",
+            NativeError::MultiDrawLength => "multi draw slices must all be the same length",
             NativeError::NoCreateBuffer => "couldn't create buffer",
             NativeError::NoCreateFrameBuffer => "couldn't create frame buffer",
             NativeError::NoCreateRenderBuffer => "couldn't create render buffer",
             NativeError::NoExistingBuffer => "no existing buffer",
-            NativeError::MissingShaderProgram => "No shader program activated",
-            NativeError::MissingRenderBuffer => "No render buffer",
-            NativeError::MissingFrameBuffer => "No frame buffer",
+            NativeError::MissingShaderProgram(_optional_label) => "No shader program activated",
+            NativeError::MissingShader(_optional_label) => "no such shader",
+            NativeError::ShaderInUse(_optional_label) => "shader is still used by a program",
+            NativeError::MissingRenderBuffer(_optional_label) => "No render buffer",
+            NativeError::MissingFrameBuffer(_optional_label) => "No frame buffer",
             NativeError::NoCreateTexture => "unable to create texture",
-            NativeError::MissingTexture => "couldn't get texture",
+            NativeError::MissingTexture(_optional_label) => "couldn't get texture",
             NativeError::MissingTextureSampler(_optional_name) => "couldn't get texture sampler",
-            NativeError::MissingBuffer => "couldn't get buffer",
-            NativeError::VertexArrayMissing => "no such vertex array",
+            NativeError::MissingBuffer(_optional_label) => "couldn't get buffer",
+            NativeError::VertexArrayMissing(_optional_label) => "no such vertex array",
             NativeError::VertexArrayCreate => "unable to create vertex array",
+            NativeError::VertexLayout(_optional_desc) => "invalid vertex layout",
+            NativeError::SamplerMissing(_optional_label) => "no such sampler",
+            NativeError::SamplerCreate => "unable to create sampler",
+            NativeError::QueryCreate => "unable to create query",
+            NativeError::GpuProfilerDisabled => "gpu profiler isn't enabled",
+            NativeError::GpuScopeOpen => "a gpu scope is still open (they can't nest)",
+            NativeError::GpuScopeNotOpen => "no gpu scope is open",
+            NativeError::ShaderPreprocess(_optional_desc) => "couldn't preprocess shader",
+            #[cfg(feature = "webgl")]
+            NativeError::ShaderCompile(_compile_error) => "shader compile error",
+            NativeError::StateStackEmpty => "no render state to pop",
             NativeError::UniformMatrixMustBeFloat => "uniform matrix must be floats",
-            NativeError::UniformType => "wrong uniform type",
+            NativeError::UniformType(_optional_desc) => "wrong uniform type",
             NativeError::UniformSize => "uniform data is not large enough",
             NativeError::UniformBufferName => "couldn't get uniform block name",
             NativeError::UniformBufferParameter => "couldn't get uniform block parameter",
@@ -159,6 +201,7 @@ impl NativeError {
             NativeError::UniformBufferOffsetMissing(_optional_name) => {
                 "uniform buffer offset is missing"
             }
+            NativeError::UniformBufferLayout(_optional_desc) => "uniform buffer layout mismatch",
             NativeError::UniformBufferTarget => {
                 "buffer target must be UniformBuffer for uniform buffers"
             }
@@ -172,11 +215,16 @@ impl NativeError {
             NativeError::WebGl1Texture3d => "webgl 1 doesn't support 3d textures",
             NativeError::JsValueExpectedBool => "expected jsvalue to be a bool",
             NativeError::JsValueExpectedNumber => "expected jsvalue to be a number",
-            NativeError::NoTextureTarget => {
+            NativeError::NoTextureTarget(_optional_label) => {
                 "texture target must be known (call assign before activate)"
             }
             NativeError::TextureCubeFaceNotCube => "texture cube face is set but not cube target",
             NativeError::TextureMissingCubeFace => "texture cube face missing for cube target",
+            NativeError::TextureSubImageBounds(_optional_label) => "texture sub image doesn't fit inside the texture",
+            NativeError::TextureImmutable(_optional_label) => "texture has immutable storage (use update_texture_sub)",
+            NativeError::TextureFormatMismatch(_optional_label) => "data format doesn't match the texture's internal format",
+            NativeError::TextureStorage(_optional_desc) => "invalid texture storage",
+            NativeError::CompressedTexture(_optional_desc) => "invalid compressed texture",
             NativeError::Internal => "internal error",
             NativeError::FrameBuffer(_optional_desc) => "framebuffer error",
         }
@@ -211,17 +259,79 @@ impl NativeError {
                     block_name.as_str()
                 ),
             },
+            NativeError::UniformBufferLayout(optional_desc) => match optional_desc {
+                None => self.default_str().to_string(),
+                Some(desc) => format!("uniform buffer layout mismatch: {}", desc.as_str()),
+            },
+            NativeError::VertexLayout(optional_desc) => match optional_desc {
+                None => self.default_str().to_string(),
+                Some(desc) => format!("invalid vertex layout: {}", desc.as_str()),
+            },
             NativeError::MissingTextureSampler(optional_name) => match optional_name {
                 None => self.default_str().to_string(),
                 Some(name) => format!("couldn't get texture sampler named {}", name.as_str()),
             },
+            NativeError::TextureStorage(optional_desc) => match optional_desc {
+                None => self.default_str().to_string(),
+                Some(desc) => format!("invalid texture storage: {}", desc.as_str()),
+            },
+            NativeError::CompressedTexture(optional_desc) => match optional_desc {
+                None => self.default_str().to_string(),
+                Some(desc) => format!("invalid compressed texture: {}", desc.as_str()),
+            },
+            NativeError::UniformType(optional_desc) => match optional_desc {
+                None => self.default_str().to_string(),
+                Some(desc) => format!("wrong uniform type: {}", desc.as_str()),
+            },
+            NativeError::ShaderPreprocess(optional_desc) => match optional_desc {
+                None => self.default_str().to_string(),
+                Some(desc) => format!("couldn't preprocess shader: {}", desc.as_str()),
+            },
+            #[cfg(feature = "webgl")]
+            NativeError::ShaderCompile(compile_error) => compile_error.to_string(),
+            NativeError::NoExtension(Some(name)) => format!("extension not found: {}", name),
             NativeError::FrameBuffer(optional_desc) => match optional_desc{
                 None => self.default_str().to_string(),
                 Some(desc) => format!("framebuffer error: {}", desc.as_str()),
             },
+            //resources with a debug label
+            NativeError::MissingShaderProgram(Some(label))
+            | NativeError::MissingShader(Some(label))
+            | NativeError::ShaderInUse(Some(label))
+            | NativeError::MissingTexture(Some(label))
+            | NativeError::MissingBuffer(Some(label))
+            | NativeError::MissingRenderBuffer(Some(label))
+            | NativeError::MissingFrameBuffer(Some(label))
+            | NativeError::VertexArrayMissing(Some(label))
+            | NativeError::SamplerMissing(Some(label))
+            | NativeError::NoTextureTarget(Some(label))
+            | NativeError::TextureImmutable(Some(label))
+            | NativeError::TextureSubImageBounds(Some(label))
+            | NativeError::TextureFormatMismatch(Some(label)) => format!("{} [{}]", self.default_str(), label),
             _ => self.default_str().to_string(),
         }
     }
+
+    //sets the debug label on errors that are about a single resource (no-op for the rest)
+    pub fn with_label(self: Self, label: Option<&str>) -> Self {
+        let label = label.map(String::from);
+        match self {
+            NativeError::MissingShaderProgram(_) => NativeError::MissingShaderProgram(label),
+            NativeError::MissingShader(_) => NativeError::MissingShader(label),
+            NativeError::ShaderInUse(_) => NativeError::ShaderInUse(label),
+            NativeError::MissingTexture(_) => NativeError::MissingTexture(label),
+            NativeError::MissingBuffer(_) => NativeError::MissingBuffer(label),
+            NativeError::MissingRenderBuffer(_) => NativeError::MissingRenderBuffer(label),
+            NativeError::MissingFrameBuffer(_) => NativeError::MissingFrameBuffer(label),
+            NativeError::VertexArrayMissing(_) => NativeError::VertexArrayMissing(label),
+            NativeError::SamplerMissing(_) => NativeError::SamplerMissing(label),
+            NativeError::NoTextureTarget(_) => NativeError::NoTextureTarget(label),
+            NativeError::TextureImmutable(_) => NativeError::TextureImmutable(label),
+            NativeError::TextureSubImageBounds(_) => NativeError::TextureSubImageBounds(label),
+            NativeError::TextureFormatMismatch(_) => NativeError::TextureFormatMismatch(label),
+            _ => self,
+        }
+    }
 }
 
 impl From<Error> for JsValue {
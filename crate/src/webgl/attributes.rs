@@ -1,4 +1,4 @@
-use super::{BufferDataImpl, BufferTarget, DataType, Id, WebGlCommon, WebGlRenderer};
+use super::{BufferDataImpl, BufferTarget, DataType, BufferId, ProgramId, WebGlCommon, WebGlRenderer};
 use crate::errors::{Error, NativeError};
 use web_sys::WebGlProgram;
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
@@ -98,11 +98,10 @@ impl_context! {
 //However, they can also be used by direct u32, before the shader
 impl<T: WebGlCommon> WebGlRenderer<T> {
 
-    pub fn cache_attribute_name(&mut self, program_id: Id, name:&str) -> Result<(u32, bool), Error> {
+    pub fn cache_attribute_name(&mut self, program_id: ProgramId, name:&str) -> Result<(u32, bool), Error> {
         let program_info = self
             .program_lookup
-            .get_mut(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get_mut(program_id, NativeError::MissingShaderProgram)?;
 
 
         let entry = program_info.attribute_lookup.entry(name.to_string());
@@ -134,7 +133,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
 
         let program_id = self
             .current_program_id
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .ok_or(Error::from(NativeError::MissingShaderProgram(None)))?;
 
         self.cache_attribute_name(program_id, name)
             .map(|(loc, _cached)| loc)
@@ -156,7 +155,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
 
     pub fn activate_buffer_for_attribute_name(
         &mut self,
-        buffer_id: Id,
+        buffer_id: BufferId,
         buffer_target: BufferTarget,
         attribute_name: &str,
         opts: &AttributeOptions,
@@ -167,7 +166,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     }
     pub fn activate_buffer_for_attribute_loc(
         &self,
-        buffer_id: Id,
+        buffer_id: BufferId,
         buffer_target: BufferTarget,
         attribute_loc: AttributeLocation,
         opts: &AttributeOptions,
@@ -179,7 +178,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
 
     pub fn upload_buffer_to_attribute_name<B: BufferDataImpl>(
         &mut self,
-        id: Id,
+        id: BufferId,
         data: B,
         attribute_name: &str,
         opts: &AttributeOptions,
@@ -190,7 +189,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     }
     pub fn upload_buffer_to_attribute_loc<B: BufferDataImpl>(
         &self,
-        id: Id,
+        id: BufferId,
         data: B,
         attribute_loc: AttributeLocation,
         opts: &AttributeOptions,
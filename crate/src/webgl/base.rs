@@ -1,14 +1,24 @@
 use super::funcs::FuncSettings;
 use super::misc::MiscSettings;
+use super::stencil::StencilSettings;
 use super::toggles::ToggleFlags;
-use super::{ BufferTarget, FrameBufferTarget, GlParameter, Id, ProgramInfo, TextureInfo, WebGlCommon, WebGlVersion, BufferLocation, AttributeLocation};
+use super::shader::{ShaderInfo, ShaderPreprocessor, ProgramVariantKey};
+use super::samplers::SamplerInfo;
+use super::buffers::BufferInfo;
+use super::renderbuffers::RenderbufferInfo;
+use super::profiler::GpuProfiler;
+use super::state::RenderState;
+use super::{ BufferTarget, FrameBufferTarget, GlParameter, ProgramInfo, TextureInfo, WebGlCommon, WebGlVersion, BufferLocation, AttributeLocation};
 use super::viewport::ResizeStrategy;
+use super::owned::DeletionQueue;
+use super::context_loss::{ContextLossListener, ContextRestoreHook, ContextState};
 use crate::errors::{Error, NativeError};
-use beach_map::{BeachMap, DefaultVersion};
+use super::id::{IdMap, BufferId, FramebufferId, ProgramId, RenderbufferId, SamplerId, ShaderId, TextureId, VaoId};
 use rustc_hash::FxHashMap;
 use std::cell::Cell;
+use std::rc::Rc;
 use std::any::Any;
-use web_sys::{HtmlCanvasElement, WebGlBuffer, WebGlShader, WebGlVertexArrayObject, WebGlRenderbuffer, WebGlFramebuffer};
+use web_sys::{HtmlCanvasElement, WebGlVertexArrayObject, WebGlFramebuffer};
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 
 pub type WebGl1Renderer = WebGlRenderer<WebGlRenderingContext>;
@@ -36,39 +46,63 @@ pub struct WebGlRenderer<T: WebGlCommon> {
     //only in webgl2
     pub hardcoded_ubo_locations: FxHashMap<String, BufferLocation>,
 
+    //chunks for #include in compile_program_variant()
+    pub shader_preprocessor: ShaderPreprocessor,
+
+    //check uploads by name against the declared uniform types (costs a lookup per upload)
+    pub validate_uniforms: bool,
+
     //really just local to the module
     pub(super) last_resize_strategy: Option<ResizeStrategy>, 
     pub(super) viewport: Option<(u32, u32, u32, u32)>,
 
-    pub(super) shader_lookup: BeachMap<DefaultVersion, WebGlShader>,
+    pub(super) shader_lookup: IdMap<ShaderId, ShaderInfo>,
     
-    pub(super) current_program_id: Option<Id>,
-    pub(super) program_lookup: BeachMap<DefaultVersion, ProgramInfo>,
+    pub(super) current_program_id: Option<ProgramId>,
+    pub(super) program_lookup: IdMap<ProgramId, ProgramInfo>,
+    pub(super) program_variant_lookup: FxHashMap<ProgramVariantKey, ProgramId>,
 
-    pub(super) current_framebuffer_id: Cell<Option<Id>>,
+    pub(super) current_framebuffer_id: Cell<Option<FramebufferId>>,
     pub(super) current_framebuffer_target: Cell<Option<FrameBufferTarget>>,
-    pub(super) framebuffer_lookup: BeachMap<DefaultVersion, WebGlFramebuffer>,
+    pub(super) framebuffer_lookup: IdMap<FramebufferId, WebGlFramebuffer>,
 
-    pub(super) current_renderbuffer_id: Cell<Option<Id>>,
-    pub(super) renderbuffer_lookup: BeachMap<DefaultVersion, WebGlRenderbuffer>,
+    pub(super) current_renderbuffer_id: Cell<Option<RenderbufferId>>,
+    pub(super) renderbuffer_lookup: IdMap<RenderbufferId, RenderbufferInfo>,
 
-    pub(super) current_buffer_id: Cell<Option<Id>>,
+    pub(super) current_buffer_id: Cell<Option<BufferId>>,
     pub(super) current_buffer_target: Cell<Option<BufferTarget>>,
     pub(super) current_buffer_index: Cell<Option<u32>>, //only used for webgl_2
-    pub(super) buffer_lookup: BeachMap<DefaultVersion, WebGlBuffer>,
+    pub(super) buffer_lookup: IdMap<BufferId, BufferInfo>,
 
-    pub(super) texture_lookup: BeachMap<DefaultVersion, TextureInfo>,
+    pub(super) texture_lookup: IdMap<TextureId, TextureInfo>,
+
+    //only in webgl2, cached per texture unit
+    pub(super) current_sampler_ids: Vec<Cell<Option<SamplerId>>>,
+    pub(super) sampler_lookup: IdMap<SamplerId, SamplerInfo>,
 
     pub(super) extension_lookup: FxHashMap<String, js_sys::Object>,
 
-    pub(super) current_vao_id: Cell<Option<Id>>,
-    pub(super) vao_lookup: BeachMap<DefaultVersion, WebGlVertexArrayObject>,
+    pub(super) current_vao_id: Cell<Option<VaoId>>,
+    pub(super) vao_lookup: IdMap<VaoId, WebGlVertexArrayObject>,
 
 
     pub(super) toggle_flags: ToggleFlags,
 
     pub(super) func_settings: FuncSettings,
     pub(super) misc_settings: MiscSettings,
+    pub(super) stencil_settings: StencilSettings,
+
+    pub(super) state_stack: Vec<RenderState>,
+
+    //filled by dropped Owned handles, see owned.rs
+    pub(super) deletion_queue: DeletionQueue,
+
+    //only in webgl2
+    pub(super) gpu_profiler: Option<GpuProfiler>,
+
+    pub(super) context_state: Rc<Cell<ContextState>>,
+    pub(super) context_loss_listener: Option<ContextLossListener>,
+    pub(super) context_restore_hooks: Vec<ContextRestoreHook<T>>,
 }
 
 impl<T: WebGlCommon + 'static> WebGlRenderer<T> {
@@ -106,33 +140,39 @@ impl<T: WebGlCommon + 'static> WebGlRenderer<T> {
             version,
             hardcoded_attribute_locations: FxHashMap::default(),
             hardcoded_ubo_locations: FxHashMap::default(),
+            shader_preprocessor: ShaderPreprocessor::default(),
+            validate_uniforms: false,
 
             last_resize_strategy: None,
             viewport: None,
 
-            shader_lookup: BeachMap::default(),
+            shader_lookup: IdMap::default(),
 
             current_program_id: None,
-            program_lookup: BeachMap::default(),
+            program_lookup: IdMap::default(),
+            program_variant_lookup: FxHashMap::default(),
 
             current_framebuffer_id: Cell::new(None),
             current_framebuffer_target: Cell::new(None),
-            framebuffer_lookup: BeachMap::default(),
+            framebuffer_lookup: IdMap::default(),
             
             current_renderbuffer_id: Cell::new(None),
-            renderbuffer_lookup: BeachMap::default(),
+            renderbuffer_lookup: IdMap::default(),
 
             current_buffer_id: Cell::new(None),
             current_buffer_target: Cell::new(None),
             current_buffer_index: Cell::new(None),
-            buffer_lookup: BeachMap::default(),
+            buffer_lookup: IdMap::default(),
+
+            texture_lookup: IdMap::default(),
 
-            texture_lookup: BeachMap::default(),
+            current_sampler_ids: (0..max_texture_units).map(|_| Cell::new(None)).collect(),
+            sampler_lookup: IdMap::default(),
 
             extension_lookup: FxHashMap::default(),
 
             current_vao_id: Cell::new(None),
-            vao_lookup: BeachMap::default(),
+            vao_lookup: IdMap::default(),
             
             toggle_flags: ToggleFlags::default(),
 
@@ -147,6 +187,17 @@ impl<T: WebGlCommon + 'static> WebGlRenderer<T> {
             },
 
             misc_settings: MiscSettings::default(),
+            stencil_settings: StencilSettings::default(),
+
+            state_stack: Vec::new(),
+
+            deletion_queue: DeletionQueue::default(),
+
+            gpu_profiler: None,
+
+            context_state: Rc::new(Cell::new(ContextState::Active)),
+            context_loss_listener: None,
+            context_restore_hooks: Vec::new(),
         })
     }
 }
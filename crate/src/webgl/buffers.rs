@@ -1,5 +1,6 @@
-use super::{BufferTarget, BufferUsage, Id, WebGlCommon, WebGlRenderer};
+use super::{BufferTarget, BufferUsage, BufferId, WebGlCommon, WebGlRenderer};
 use crate::errors::{Error, NativeError};
+use std::cell::Cell;
 use std::marker::PhantomData;
 use web_sys::WebGlBuffer;
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
@@ -292,6 +293,7 @@ pub trait BufferDataImpl {
     fn upload_buffer<G: PartialWebGlBuffer>(&self, gl: &G);
     fn get_target(&self) -> BufferTarget;
     fn get_usage(&self) -> BufferUsage;
+    fn get_byte_len(&self) -> usize;
 }
 
 //see example: https://github.com/rustwasm/wasm-bindgen/blob/master/examples/webgl/src/lib.rs#L42
@@ -306,6 +308,9 @@ impl<T: AsRef<[i32]>> BufferDataImpl for BufferData<T, i32> {
     fn get_usage(&self) -> BufferUsage {
         self.usage
     }
+    fn get_byte_len(&self) -> usize {
+        std::mem::size_of_val(self.values.as_ref())
+    }
 }
 impl<T: AsRef<[u32]>> BufferDataImpl for BufferData<T, u32> {
     fn upload_buffer<G: PartialWebGlBuffer>(&self, gl: &G) {
@@ -318,6 +323,9 @@ impl<T: AsRef<[u32]>> BufferDataImpl for BufferData<T, u32> {
     fn get_usage(&self) -> BufferUsage {
         self.usage
     }
+    fn get_byte_len(&self) -> usize {
+        std::mem::size_of_val(self.values.as_ref())
+    }
 }
 impl<T: AsRef<[f32]>> BufferDataImpl for BufferData<T, f32> {
     fn upload_buffer<G: PartialWebGlBuffer>(&self, gl: &G) {
@@ -330,6 +338,9 @@ impl<T: AsRef<[f32]>> BufferDataImpl for BufferData<T, f32> {
     fn get_usage(&self) -> BufferUsage {
         self.usage
     }
+    fn get_byte_len(&self) -> usize {
+        std::mem::size_of_val(self.values.as_ref())
+    }
 }
 
 impl<T: AsRef<[u8]>> BufferDataImpl for BufferData<T, u8> {
@@ -343,6 +354,9 @@ impl<T: AsRef<[u8]>> BufferDataImpl for BufferData<T, u8> {
     fn get_usage(&self) -> BufferUsage {
         self.usage
     }
+    fn get_byte_len(&self) -> usize {
+        std::mem::size_of_val(self.values.as_ref())
+    }
 }
 
 ///Buffer Sub Data
@@ -462,15 +476,30 @@ impl<T: AsRef<[u8]>> BufferSubDataImpl for BufferSubData<T, u8> {
 
 //renderer impl
 
+pub(super) struct BufferInfo {
+    pub(super) buffer: WebGlBuffer,
+    //from the last full upload (sub uploads don't change it)
+    pub(super) byte_len: Cell<usize>,
+}
+
 impl<T: WebGlCommon> WebGlRenderer<T> {
-    pub fn create_buffer(&mut self) -> Result<Id, Error> {
+    pub fn create_buffer(&mut self) -> Result<BufferId, Error> {
         let buffer = self.gl.awsm_create_buffer()?;
-        let id = self.buffer_lookup.insert(buffer);
+        let id = self.buffer_lookup.insert(BufferInfo {
+            buffer,
+            byte_len: Cell::new(0),
+        });
 
         Ok(id)
     }
 
-    pub fn delete_buffer(&self, buffer_id: Id) -> Result<(), Error> {
+    pub fn create_buffer_labeled(&mut self, label: &str) -> Result<BufferId, Error> {
+        let id = self.create_buffer()?;
+        self.set_label(id, label)?;
+        Ok(id)
+    }
+
+    pub fn delete_buffer(&mut self, buffer_id: BufferId) -> Result<(), Error> {
         if Some(buffer_id) == self.current_buffer_id.get() {
             if let Some(target) = self.current_buffer_target.get() {
                 self.gl.awsm_release_buffer(target);
@@ -480,12 +509,11 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
             self.current_buffer_index.set(None);
         }
 
-        let buffer = self
+        let info = self
             .buffer_lookup
-            .get(buffer_id)
-            .ok_or(Error::from(NativeError::MissingBuffer))?;
+            .try_remove(buffer_id, NativeError::MissingBuffer)?;
 
-        self.gl.awsm_delete_buffer(&buffer);
+        self.gl.awsm_delete_buffer(&info.buffer);
 
         Ok(())
     }
@@ -493,29 +521,28 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     //only pub within the module - used elsewhere like attributes
     pub(super) fn _bind_buffer_nocheck(
         &self,
-        buffer_id: Id,
+        buffer_id: BufferId,
         target: BufferTarget,
     ) -> Result<(), Error> {
         self.current_buffer_id.set(Some(buffer_id));
         self.current_buffer_target.set(Some(target));
         self.current_buffer_index.set(None);
 
-        let buffer = self
+        let info = self
             .buffer_lookup
-            .get(buffer_id)
-            .ok_or(Error::from(NativeError::MissingBuffer))?;
-        self.gl.awsm_bind_buffer(target, &buffer);
+            .try_get(buffer_id, NativeError::MissingBuffer)?;
+        self.gl.awsm_bind_buffer(target, &info.buffer);
 
         Ok(())
     }
 
     #[cfg(feature = "disable_webgl_opt")]
-    pub fn bind_buffer(&self, buffer_id: Id, target: BufferTarget) -> Result<(), Error> {
+    pub fn bind_buffer(&self, buffer_id: BufferId, target: BufferTarget) -> Result<(), Error> {
         self._bind_buffer_nocheck(buffer_id, target)
     }
 
     #[cfg(not(feature = "disable_webgl_opt"))]
-    pub fn bind_buffer(&self, buffer_id: Id, target: BufferTarget) -> Result<(), Error> {
+    pub fn bind_buffer(&self, buffer_id: BufferId, target: BufferTarget) -> Result<(), Error> {
         if Some(buffer_id) != self.current_buffer_id.get()
             || Some(target) != self.current_buffer_target.get()
         {
@@ -533,15 +560,18 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
         self.gl.awsm_release_buffer(target);
     }
 
-    pub fn upload_buffer<B: BufferDataImpl>(&self, id: Id, data: B) -> Result<(), Error> {
+    pub fn upload_buffer<B: BufferDataImpl>(&self, id: BufferId, data: B) -> Result<(), Error> {
         self.bind_buffer(id, data.get_target())?;
         data.upload_buffer(&self.gl);
+        if let Some(info) = self.buffer_lookup.get(id) {
+            info.byte_len.set(data.get_byte_len());
+        }
         Ok(())
     }
 
     pub fn upload_buffer_sub<B: BufferSubDataImpl>(
         &self,
-        id: Id,
+        id: BufferId,
         dest_byte_offset: u32,
         data: B,
     ) -> Result<(), Error> {
@@ -551,26 +581,27 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
 }
 
 impl WebGlRenderer<WebGl2RenderingContext> {
-    pub(super) fn _bind_buffer_base_nocheck( &self, buffer_id: Id, index: u32, target: BufferTarget,) -> Result<(), Error> {
+    pub(super) fn _bind_buffer_base_nocheck( &self, buffer_id: BufferId, index: u32, target: BufferTarget,) -> Result<(), Error> {
+        //a missing buffer leaves the cache alone
+        let info = self
+            .buffer_lookup
+            .try_get(buffer_id, NativeError::MissingBuffer)?;
+
         self.current_buffer_id.set(Some(buffer_id));
         self.current_buffer_target.set(Some(target));
         self.current_buffer_index.set(Some(index));
 
-        let buffer = self
-            .buffer_lookup
-            .get(buffer_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
-        self.gl.bind_buffer_base(target as u32, index, Some(buffer));
+        self.gl.bind_buffer_base(target as u32, index, Some(&info.buffer));
 
         Ok(())
     }
 
     #[cfg(feature = "disable_webgl_opt")]
-    pub fn bind_buffer_base( &self, buffer_id: Id, index: u32, target: BufferTarget,) -> Result<(), Error> {
+    pub fn bind_buffer_base( &self, buffer_id: BufferId, index: u32, target: BufferTarget,) -> Result<(), Error> {
         self._bind_buffer_base_nocheck(buffer_id, index, target)
     }
     #[cfg(not(feature = "disable_webgl_opt"))]
-    pub fn bind_buffer_base( &self, buffer_id: Id, index: u32, target: BufferTarget,) -> Result<(), Error> {
+    pub fn bind_buffer_base( &self, buffer_id: BufferId, index: u32, target: BufferTarget,) -> Result<(), Error> {
         if Some(buffer_id) != self.current_buffer_id.get()
             || Some(target) != self.current_buffer_target.get()
             || Some(index) != self.current_buffer_index.get()
@@ -0,0 +1,880 @@
+use super::{
+    CompressedTextureExtension, DataType, TextureId, PixelDataFormat, PixelInternalFormat,
+    TextureCubeFace, TextureOptions, TextureStorage, TextureSubOptions, TextureTarget,
+    WebGlCommon, WebGlRenderer, WebGlTextureSource,
+};
+use crate::errors::{Error, NativeError};
+use std::convert::TryFrom;
+
+/*
+ * Pre-compressed textures, from KTX2 or the legacy KTX1 / DDS containers
+ *
+ * Parsing is pure Rust and borrows from the bytes, e.g.
+ * fetch_url(url).await?.array_buffer().await?.to_vec_u8()
+ *
+ * Supercompressed KTX2 (Basis Universal, zstd, zlib) needs transcoding first, so isn't supported
+ * The formats need their extension registered, see register_compressed_texture_extensions()
+ */
+
+pub struct CompressedTexture<'a> {
+    pub format: PixelInternalFormat,
+    pub width: u32,
+    pub height: u32,
+    //only for 3d textures, 1 otherwise
+    pub depth: u32,
+    //0 if not an array
+    pub layers: u32,
+    //6 for cube maps, 1 otherwise
+    pub faces: u32,
+    //base level first
+    pub levels: Vec<CompressedLevel<'a>>,
+}
+
+pub struct CompressedLevel<'a> {
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    //one per cube face or array layer, otherwise just one
+    pub images: Vec<&'a [u8]>,
+}
+
+const CUBE_FACES: [TextureCubeFace; 6] = [
+    TextureCubeFace::PositiveX,
+    TextureCubeFace::NegativeX,
+    TextureCubeFace::PositiveY,
+    TextureCubeFace::NegativeY,
+    TextureCubeFace::PositiveZ,
+    TextureCubeFace::NegativeZ,
+];
+
+impl<'a> CompressedTexture<'a> {
+    pub fn bind_target(&self) -> Result<TextureTarget, Error> {
+        match (self.faces, self.layers, self.depth) {
+            (6, 0, 1) => Ok(TextureTarget::CubeMap),
+            (1, 0, 1) => Ok(TextureTarget::Texture2d),
+            (1, 0, _) => Ok(TextureTarget::Texture3d),
+            (1, _, 1) => Ok(TextureTarget::Array2d),
+            (faces, layers, depth) => Err(container_error(format!(
+                "unsupported layout ({} faces, {} layers, depth {})",
+                faces, layers, depth
+            ))),
+        }
+    }
+}
+
+/// Detects the container from its identifier
+pub fn parse_compressed_texture(bytes: &[u8]) -> Result<CompressedTexture, Error> {
+    if bytes.starts_with(&KTX2_IDENTIFIER) {
+        parse_ktx2(bytes)
+    } else if bytes.starts_with(&KTX1_IDENTIFIER) {
+        parse_ktx1(bytes)
+    } else if bytes.starts_with(DDS_MAGIC) {
+        parse_dds(bytes)
+    } else {
+        Err(container_error("not a KTX2, KTX or DDS file".to_string()))
+    }
+}
+
+const KTX2_IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const KTX1_IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x31, 0x31, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+const DDS_MAGIC: &[u8] = b"DDS ";
+
+pub fn parse_ktx2(bytes: &[u8]) -> Result<CompressedTexture, Error> {
+    let reader = Reader::new(bytes, false);
+
+    if !bytes.starts_with(&KTX2_IDENTIFIER) {
+        return Err(container_error("not a KTX2 file".to_string()));
+    }
+
+    let vk_format = reader.u32(12)?;
+    let width = reader.u32(20)?;
+    let height = reader.u32(24)?.max(1);
+    let depth = reader.u32(28)?.max(1);
+    let layers = reader.u32(32)?;
+    let faces = reader.u32(36)?.max(1);
+    //0 means "generate mips", which isn't possible for compressed formats
+    let level_count = reader.u32(40)?.max(1);
+    let supercompression = reader.u32(44)?;
+
+    if supercompression != 0 {
+        return Err(container_error(format!("supercompression scheme {} needs transcoding first", supercompression)));
+    }
+
+    let format = vk_format_to_gl(vk_format).ok_or_else(|| {
+        container_error(format!("unsupported vkFormat {}", vk_format))
+    })?;
+
+    check_level_count(level_count, width, height, depth)?;
+    let image_count = image_count(faces, layers)?;
+    let mut levels = Vec::with_capacity(level_count as usize);
+
+    for level in 0..level_count {
+        let index = 80 + (level as u64 * 24);
+        let offset = reader.u64(index)?;
+        let len = reader.u64(index + 8)?;
+        let data = reader.slice(offset, len)?;
+
+        let (level_width, level_height, level_depth) = mip_size(width, height, depth, level);
+        //cube faces and array layers are consecutive in each level
+        let images = split_images(format, data, level_width, level_height, level_depth, image_count)?;
+
+        levels.push(CompressedLevel {
+            width: level_width,
+            height: level_height,
+            depth: level_depth,
+            images,
+        });
+    }
+
+    Ok(CompressedTexture {
+        format,
+        width,
+        height,
+        depth,
+        layers,
+        faces,
+        levels,
+    })
+}
+
+pub fn parse_ktx1(bytes: &[u8]) -> Result<CompressedTexture, Error> {
+    if !bytes.starts_with(&KTX1_IDENTIFIER) {
+        return Err(container_error("not a KTX file".to_string()));
+    }
+
+    let big_endian = match Reader::new(bytes, false).u32(12)? {
+        0x04030201 => false,
+        0x01020304 => true,
+        _ => return Err(container_error("invalid endianness".to_string())),
+    };
+
+    let reader = Reader::new(bytes, big_endian);
+
+    let gl_type = reader.u32(16)?;
+    let gl_internal_format = reader.u32(28)?;
+    let width = reader.u32(36)?;
+    let height = reader.u32(40)?.max(1);
+    let depth = reader.u32(44)?.max(1);
+    let layers = reader.u32(48)?;
+    let faces = reader.u32(52)?.max(1);
+    let level_count = reader.u32(56)?.max(1);
+    let key_value_len = reader.u32(60)?;
+
+    if gl_type != 0 {
+        return Err(container_error("not a compressed texture".to_string()));
+    }
+
+    let format = compressed_format_from_gl(gl_internal_format).ok_or_else(|| {
+        container_error(format!("unsupported glInternalFormat 0x{:X}", gl_internal_format))
+    })?;
+
+    check_level_count(level_count, width, height, depth)?;
+    let image_count = image_count(faces, layers)?;
+    let mut offset = 64 + key_value_len as u64;
+    let mut levels = Vec::with_capacity(level_count as usize);
+
+    for level in 0..level_count {
+        let image_size = reader.u32(offset)? as u64;
+        offset += 4;
+
+        let (level_width, level_height, level_depth) = mip_size(width, height, depth, level);
+
+        //non-array cube maps store each face separately (with padding)
+        //everything else is one image for the whole level
+        let images = if faces == 6 && layers == 0 {
+            let mut images = Vec::with_capacity(6);
+            for _ in 0..6 {
+                images.push(reader.slice(offset, image_size)?);
+                offset += align_4(image_size);
+            }
+            check_image_sizes(format, &images, level_width, level_height, level_depth)?;
+            images
+        } else {
+            let data = reader.slice(offset, image_size)?;
+            offset += align_4(image_size);
+            split_images(format, data, level_width, level_height, level_depth, image_count)?
+        };
+
+        levels.push(CompressedLevel {
+            width: level_width,
+            height: level_height,
+            depth: level_depth,
+            images,
+        });
+    }
+
+    Ok(CompressedTexture {
+        format,
+        width,
+        height,
+        depth,
+        layers,
+        faces,
+        levels,
+    })
+}
+
+pub fn parse_dds(bytes: &[u8]) -> Result<CompressedTexture, Error> {
+    const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+    const DDSCAPS2_CUBEMAP: u32 = 0x200;
+    const DDSCAPS2_VOLUME: u32 = 0x200000;
+    const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+    let reader = Reader::new(bytes, false);
+
+    if !bytes.starts_with(DDS_MAGIC) || reader.u32(4)? != 124 {
+        return Err(container_error("not a DDS file".to_string()));
+    }
+
+    let flags = reader.u32(8)?;
+    let height = reader.u32(12)?.max(1);
+    let width = reader.u32(16)?;
+    let level_count = if flags & DDSD_MIPMAPCOUNT != 0 { reader.u32(28)?.max(1) } else { 1 };
+    let four_cc = reader.slice(84, 4)?;
+    let caps2 = reader.u32(112)?;
+
+    if caps2 & DDSCAPS2_VOLUME != 0 {
+        return Err(container_error("volume textures aren't supported".to_string()));
+    }
+
+    let (format, data_offset, layers, faces) = if four_cc == b"DX10" {
+        let dxgi_format = reader.u32(128)?;
+        let misc_flag = reader.u32(136)?;
+        let array_size = reader.u32(140)?;
+        let format = dxgi_format_to_gl(dxgi_format).ok_or_else(|| {
+            container_error(format!("unsupported DXGI format {}", dxgi_format))
+        })?;
+        let faces = if misc_flag & DDS_RESOURCE_MISC_TEXTURECUBE != 0 { 6 } else { 1 };
+        let layers = if array_size > 1 { array_size } else { 0 };
+        (format, 148, layers, faces)
+    } else {
+        let format = four_cc_to_gl(four_cc).ok_or_else(|| {
+            container_error(format!("unsupported FourCC {:?}", String::from_utf8_lossy(four_cc)))
+        })?;
+        let faces = if caps2 & DDSCAPS2_CUBEMAP != 0 { 6 } else { 1 };
+        (format, 128, 0, faces)
+    };
+
+    check_level_count(level_count, width, height, 1)?;
+    let image_count = image_count(faces, layers)?;
+    let mut levels: Vec<CompressedLevel> = (0..level_count)
+        .map(|level| {
+            let (level_width, level_height, _) = mip_size(width, height, 1, level);
+            CompressedLevel {
+                width: level_width,
+                height: level_height,
+                depth: 1,
+                images: Vec::new(),
+            }
+        })
+        .collect();
+
+    //each face or layer has its whole mip chain before the next one
+    let mut offset = data_offset;
+    for _ in 0..image_count {
+        for level in levels.iter_mut() {
+            let len = compressed_image_size(format, level.width, level.height, 1)?;
+            level.images.push(reader.slice(offset, len)?);
+            offset += len;
+        }
+    }
+
+    Ok(CompressedTexture {
+        format,
+        width,
+        height,
+        depth: 1,
+        layers,
+        faces,
+        levels,
+    })
+}
+
+/// the size in bytes of one compressed image (each block is padded out)
+pub fn compressed_image_size(format: PixelInternalFormat, width: u32, height: u32, depth: u32) -> Result<u64, Error> {
+    let (block_width, block_height, block_bytes) = format.compressed_block().unwrap_or((1, 1, 0));
+    let blocks_x = (width as u64 + block_width as u64 - 1) / block_width as u64;
+    let blocks_y = (height as u64 + block_height as u64 - 1) / block_height as u64;
+
+    blocks_x
+        .checked_mul(blocks_y)
+        .and_then(|blocks| blocks.checked_mul(depth as u64))
+        .and_then(|blocks| blocks.checked_mul(block_bytes as u64))
+        .ok_or_else(|| container_error(format!("{}x{}x{} image is too large", width, height, depth)))
+}
+
+//the level count comes straight from the file, so it's checked before anything is allocated for it
+const MAX_LEVELS: u32 = 32;
+
+fn check_level_count(level_count: u32, width: u32, height: u32, depth: u32) -> Result<(), Error> {
+    //floor(log2(largest side)) + 1
+    let full_chain = 32 - width.max(height).max(depth).max(1).leading_zeros();
+
+    if level_count > MAX_LEVELS || level_count > full_chain {
+        Err(container_error(format!(
+            "{} mip levels for {}x{}x{} (at most {})",
+            level_count, width, height, depth, full_chain
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+//cube faces times array layers
+fn image_count(faces: u32, layers: u32) -> Result<u32, Error> {
+    faces
+        .checked_mul(layers.max(1))
+        .ok_or_else(|| container_error(format!("{} faces and {} layers is too many images", faces, layers)))
+}
+
+fn mip_size(width: u32, height: u32, depth: u32, level: u32) -> (u32, u32, u32) {
+    let mip = |value: u32| value.checked_shr(level).unwrap_or(0).max(1);
+    (mip(width), mip(height), mip(depth))
+}
+
+fn split_images<'a>(
+    format: PixelInternalFormat,
+    data: &'a [u8],
+    width: u32,
+    height: u32,
+    depth: u32,
+    count: u32,
+) -> Result<Vec<&'a [u8]>, Error> {
+    let len = compressed_image_size(format, width, height, depth)?;
+    let total = len
+        .checked_mul(count as u64)
+        .ok_or_else(|| container_error(format!("{} images of {} bytes is too large", count, len)))?;
+
+    if len == 0 || data.len() as u64 != total {
+        return Err(container_error(format!(
+            "{}x{} level is {} bytes, expected {} images of {}",
+            width,
+            height,
+            data.len(),
+            count,
+            len
+        )));
+    }
+
+    Ok(data.chunks(len as usize).collect())
+}
+
+fn check_image_sizes(format: PixelInternalFormat, images: &[&[u8]], width: u32, height: u32, depth: u32) -> Result<(), Error> {
+    let len = compressed_image_size(format, width, height, depth)?;
+
+    match images.iter().find(|image| image.len() as u64 != len) {
+        Some(image) => Err(container_error(format!(
+            "{}x{} image is {} bytes, expected {}",
+            width,
+            height,
+            image.len(),
+            len
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn align_4(value: u64) -> u64 {
+    (value + 3) & !3
+}
+
+fn container_error(desc: String) -> Error {
+    Error::from(NativeError::CompressedTexture(Some(desc)))
+}
+
+fn out_of_bounds() -> Error {
+    container_error("unexpected end of file".to_string())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], big_endian: bool) -> Self {
+        Self { bytes, big_endian }
+    }
+
+    //offsets and lengths in the headers can be 64 bit
+    fn slice(&self, offset: u64, len: u64) -> Result<&'a [u8], Error> {
+        let end = offset.checked_add(len).ok_or_else(out_of_bounds)?;
+        let offset = usize::try_from(offset).map_err(|_| out_of_bounds())?;
+        let end = usize::try_from(end).map_err(|_| out_of_bounds())?;
+        self.bytes.get(offset..end).ok_or_else(out_of_bounds)
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, Error> {
+        let mut value = [0u8; 4];
+        value.copy_from_slice(self.slice(offset, 4)?);
+        Ok(if self.big_endian { u32::from_be_bytes(value) } else { u32::from_le_bytes(value) })
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64, Error> {
+        let mut value = [0u8; 8];
+        value.copy_from_slice(self.slice(offset, 8)?);
+        Ok(if self.big_endian { u64::from_be_bytes(value) } else { u64::from_le_bytes(value) })
+    }
+}
+
+fn compressed_format_from_gl(value: u32) -> Option<PixelInternalFormat> {
+    use PixelInternalFormat::*;
+
+    const FORMATS: [PixelInternalFormat; 54] = [
+        RgbS3tcDxt1, RgbaS3tcDxt1, RgbaS3tcDxt3, RgbaS3tcDxt5,
+        SrgbS3tcDxt1, SrgbAlphaS3tcDxt1, SrgbAlphaS3tcDxt3, SrgbAlphaS3tcDxt5,
+        R11Eac, SignedR11Eac, Rg11Eac, SignedRg11Eac, Rgb8Etc2, Srgb8Etc2,
+        Rgb8PunchthroughAlpha1Etc2, Srgb8PunchthroughAlpha1Etc2, Rgba8Etc2Eac, Srgb8Alpha8Etc2Eac,
+        RgbaAstc4x4, RgbaAstc5x4, RgbaAstc5x5, RgbaAstc6x5, RgbaAstc6x6, RgbaAstc8x5, RgbaAstc8x6,
+        RgbaAstc8x8, RgbaAstc10x5, RgbaAstc10x6, RgbaAstc10x8, RgbaAstc10x10, RgbaAstc12x10, RgbaAstc12x12,
+        Srgb8Alpha8Astc4x4, Srgb8Alpha8Astc5x4, Srgb8Alpha8Astc5x5, Srgb8Alpha8Astc6x5, Srgb8Alpha8Astc6x6,
+        Srgb8Alpha8Astc8x5, Srgb8Alpha8Astc8x6, Srgb8Alpha8Astc8x8, Srgb8Alpha8Astc10x5, Srgb8Alpha8Astc10x6,
+        Srgb8Alpha8Astc10x8, Srgb8Alpha8Astc10x10, Srgb8Alpha8Astc12x10, Srgb8Alpha8Astc12x12,
+        RgbaBptcUnorm, SrgbAlphaBptcUnorm, RgbBptcSignedFloat, RgbBptcUnsignedFloat,
+        RedRgtc1, SignedRedRgtc1, RedGreenRgtc2, SignedRedGreenRgtc2,
+    ];
+
+    FORMATS.iter().copied().find(|format| *format as u32 == value)
+}
+
+fn vk_format_to_gl(vk_format: u32) -> Option<PixelInternalFormat> {
+    use PixelInternalFormat::*;
+
+    match vk_format {
+        131 => Some(RgbS3tcDxt1),
+        132 => Some(SrgbS3tcDxt1),
+        133 => Some(RgbaS3tcDxt1),
+        134 => Some(SrgbAlphaS3tcDxt1),
+        135 => Some(RgbaS3tcDxt3),
+        136 => Some(SrgbAlphaS3tcDxt3),
+        137 => Some(RgbaS3tcDxt5),
+        138 => Some(SrgbAlphaS3tcDxt5),
+        139 => Some(RedRgtc1),
+        140 => Some(SignedRedRgtc1),
+        141 => Some(RedGreenRgtc2),
+        142 => Some(SignedRedGreenRgtc2),
+        143 => Some(RgbBptcUnsignedFloat),
+        144 => Some(RgbBptcSignedFloat),
+        145 => Some(RgbaBptcUnorm),
+        146 => Some(SrgbAlphaBptcUnorm),
+        147 => Some(Rgb8Etc2),
+        148 => Some(Srgb8Etc2),
+        149 => Some(Rgb8PunchthroughAlpha1Etc2),
+        150 => Some(Srgb8PunchthroughAlpha1Etc2),
+        151 => Some(Rgba8Etc2Eac),
+        152 => Some(Srgb8Alpha8Etc2Eac),
+        153 => Some(R11Eac),
+        154 => Some(SignedR11Eac),
+        155 => Some(Rg11Eac),
+        156 => Some(SignedRg11Eac),
+        //ASTC alternates UNORM / SRGB, in the same block order as the gl enums
+        157..=184 => {
+            let index = vk_format - 157;
+            let base = if index % 2 == 0 { RgbaAstc4x4 } else { Srgb8Alpha8Astc4x4 };
+            compressed_format_from_gl(base as u32 + index / 2)
+        }
+        _ => None,
+    }
+}
+
+fn dxgi_format_to_gl(dxgi_format: u32) -> Option<PixelInternalFormat> {
+    use PixelInternalFormat::*;
+
+    match dxgi_format {
+        71 => Some(RgbaS3tcDxt1),
+        72 => Some(SrgbAlphaS3tcDxt1),
+        74 => Some(RgbaS3tcDxt3),
+        75 => Some(SrgbAlphaS3tcDxt3),
+        77 => Some(RgbaS3tcDxt5),
+        78 => Some(SrgbAlphaS3tcDxt5),
+        80 => Some(RedRgtc1),
+        81 => Some(SignedRedRgtc1),
+        83 => Some(RedGreenRgtc2),
+        84 => Some(SignedRedGreenRgtc2),
+        95 => Some(RgbBptcUnsignedFloat),
+        96 => Some(RgbBptcSignedFloat),
+        98 => Some(RgbaBptcUnorm),
+        99 => Some(SrgbAlphaBptcUnorm),
+        _ => None,
+    }
+}
+
+fn four_cc_to_gl(four_cc: &[u8]) -> Option<PixelInternalFormat> {
+    use PixelInternalFormat::*;
+
+    match four_cc {
+        b"DXT1" => Some(RgbS3tcDxt1),
+        b"DXT3" => Some(RgbaS3tcDxt3),
+        b"DXT5" => Some(RgbaS3tcDxt5),
+        b"ATI1" | b"BC4U" => Some(RedRgtc1),
+        b"BC4S" => Some(SignedRedRgtc1),
+        b"ATI2" | b"BC5U" => Some(RedGreenRgtc2),
+        b"BC5S" => Some(SignedRedGreenRgtc2),
+        _ => None,
+    }
+}
+
+impl<G: WebGlCommon> WebGlRenderer<G> {
+    /// Registers every compressed texture extension the context has
+    /// and returns the ones that are available
+    pub fn register_compressed_texture_extensions(&mut self) -> Vec<CompressedTextureExtension> {
+        CompressedTextureExtension::ALL
+            .iter()
+            .copied()
+            .filter(|ext| self.register_extension(ext.name()).is_ok())
+            .collect()
+    }
+
+    pub fn supports_compressed_texture_format(&self, format: PixelInternalFormat) -> bool {
+        match format.compressed_extension() {
+            Some(ext) => self.get_extension(ext.name()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Uploads all the levels (and faces or layers) of a parsed container
+    pub fn assign_compressed_texture(
+        &mut self,
+        texture_id: TextureId,
+        texture: &CompressedTexture,
+        set_parameters: Option<impl Fn(&G) -> ()>,
+    ) -> Result<(), Error> {
+        if !self.supports_compressed_texture_format(texture.format) {
+            let name = texture.format.compressed_extension().map(|ext| ext.name().to_string());
+            return Err(Error::from(NativeError::NoExtension(name)));
+        }
+
+        let bind_target = texture.bind_target()?;
+
+        let depth = match bind_target {
+            TextureTarget::Array2d => texture.layers,
+            _ => texture.depth,
+        };
+
+        //storage from allocate_texture_storage() can't be reassigned, only filled in
+        let storage = self.texture_lookup.try_get(texture_id, NativeError::MissingTexture)?.storage;
+        if let Some(storage) = storage.filter(|storage| storage.immutable) {
+            return self.update_compressed_texture_storage(texture_id, texture, bind_target, depth, storage, set_parameters);
+        }
+
+        let (texture_info, label) = self
+            .texture_lookup
+            .try_get_mut_labeled(texture_id, NativeError::MissingTexture)?;
+
+        texture_info.set_assigned(
+            bind_target,
+            texture.format,
+            Some((texture.width, texture.height, depth)),
+            texture.levels.len() as u32,
+        ).map_err(|err| err.with_label(label))?;
+
+        let opts = |cube_face: Option<TextureCubeFace>| TextureOptions {
+            internal_format: texture.format,
+            //ignored for compressed sources
+            data_format: PixelDataFormat::Rgba,
+            data_type: DataType::UnsignedByte,
+            cube_face,
+        };
+
+        match bind_target {
+            TextureTarget::CubeMap => {
+                for (index, cube_face) in CUBE_FACES.iter().enumerate() {
+                    let srcs: Vec<WebGlTextureSource> = texture
+                        .levels
+                        .iter()
+                        .map(|level| WebGlTextureSource::CompressedBufferView(level.images[index], level.width, level.height, 1))
+                        .collect();
+                    let srcs: Vec<&WebGlTextureSource> = srcs.iter().collect();
+
+                    //parameters are for the whole texture, not each face
+                    let set_parameters = if index == 0 { set_parameters.as_ref() } else { None };
+
+                    self.gl.awsm_assign_texture_mips(bind_target, &opts(Some(*cube_face)), set_parameters, &srcs, &texture_info.texture)?;
+                }
+            }
+            TextureTarget::Array2d => {
+                //a whole level is uploaded at once, so the layers need to be contiguous
+                let levels: Vec<Vec<u8>> = texture.levels.iter().map(|level| level.images.concat()).collect();
+                let srcs: Vec<WebGlTextureSource> = texture
+                    .levels
+                    .iter()
+                    .zip(levels.iter())
+                    .map(|(level, data)| WebGlTextureSource::CompressedBufferView(data, level.width, level.height, texture.layers))
+                    .collect();
+                let srcs: Vec<&WebGlTextureSource> = srcs.iter().collect();
+
+                self.gl.awsm_assign_texture_mips(bind_target, &opts(None), set_parameters, &srcs, &texture_info.texture)?;
+            }
+            _ => {
+                let srcs: Vec<WebGlTextureSource> = texture
+                    .levels
+                    .iter()
+                    .map(|level| WebGlTextureSource::CompressedBufferView(level.images[0], level.width, level.height, level.depth))
+                    .collect();
+                let srcs: Vec<&WebGlTextureSource> = srcs.iter().collect();
+
+                self.gl.awsm_assign_texture_mips(bind_target, &opts(None), set_parameters, &srcs, &texture_info.texture)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn update_compressed_texture_storage(
+        &self,
+        texture_id: TextureId,
+        texture: &CompressedTexture,
+        bind_target: TextureTarget,
+        depth: u32,
+        storage: TextureStorage,
+        set_parameters: Option<impl Fn(&G) -> ()>,
+    ) -> Result<(), Error> {
+        let texture_info = self
+            .texture_lookup
+            .try_get(texture_id, NativeError::MissingTexture)?;
+
+        if texture_info.bind_target != Some(bind_target) || storage.internal_format != texture.format {
+            return Err(self.texture_lookup.labeled(texture_id, NativeError::TextureFormatMismatch(None)));
+        }
+        if storage.size() != (texture.width, texture.height, depth.max(1)) {
+            return Err(self.texture_lookup.labeled(texture_id, NativeError::TextureSubImageBounds(None)));
+        }
+
+        self.gl.awsm_bind_texture(bind_target, &texture_info.texture);
+        set_parameters.map(|f| f(&self.gl));
+
+        for (mip_level, level) in texture.levels.iter().enumerate() {
+            let opts = |cube_face: Option<TextureCubeFace>| TextureSubOptions {
+                mip_level: mip_level as u32,
+                cube_face,
+                ..TextureSubOptions::default()
+            };
+
+            match bind_target {
+                TextureTarget::CubeMap => {
+                    for (index, cube_face) in CUBE_FACES.iter().enumerate() {
+                        let src = WebGlTextureSource::CompressedBufferView(level.images[index], level.width, level.height, 1);
+                        self.update_texture_sub_3d(texture_id, &opts(Some(*cube_face)), 0, 0, 0, &src)?;
+                    }
+                }
+                TextureTarget::Array2d => {
+                    let data = level.images.concat();
+                    let src = WebGlTextureSource::CompressedBufferView(&data, level.width, level.height, texture.layers);
+                    self.update_texture_sub_3d(texture_id, &opts(None), 0, 0, 0, &src)?;
+                }
+                _ => {
+                    let src = WebGlTextureSource::CompressedBufferView(level.images[0], level.width, level.height, level.depth);
+                    self.update_texture_sub_3d(texture_id, &opts(None), 0, 0, 0, &src)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+
+    fn ktx2(vk_format: u32, width: u32, height: u32, faces: u32, levels: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        for value in &[vk_format, 1, width, height, 0, 0, faces, levels.len() as u32, 0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        //dfd, kvd, sgd (unused here)
+        bytes.extend_from_slice(&[0u8; 32]);
+
+        let mut offset = (80 + levels.len() * 24) as u64;
+        for level in levels {
+            for value in &[offset, level.len() as u64, level.len() as u64] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            offset += level.len() as u64;
+        }
+        for level in levels {
+            bytes.extend_from_slice(level);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_ktx2() {
+        //BC1 8x8: 4 blocks, then 1 block for 4x4, 2x2 and 1x1
+        let levels = vec![vec![1u8; 32], vec![2u8; 8], vec![3u8; 8], vec![4u8; 8]];
+        let bytes = ktx2(131, 8, 8, 1, &levels);
+        let texture = parse_compressed_texture(&bytes).unwrap();
+
+        assert_eq!(texture.format, PixelInternalFormat::RgbS3tcDxt1);
+        assert_eq!(texture.bind_target().unwrap(), TextureTarget::Texture2d);
+        assert_eq!(texture.levels.len(), 4);
+        assert_eq!((texture.levels[1].width, texture.levels[1].height), (4, 4));
+        assert_eq!(texture.levels[3].images, [&[4u8; 8][..]]);
+
+        let cube = ktx2(157, 4, 4, 6, &[vec![0u8; 16 * 6]]);
+        let cube = parse_ktx2(&cube).unwrap();
+        assert_eq!(cube.format, PixelInternalFormat::RgbaAstc4x4);
+        assert_eq!(cube.bind_target().unwrap(), TextureTarget::CubeMap);
+        assert_eq!(cube.levels[0].images.len(), 6);
+
+        assert_eq!(vk_format_to_gl(184), Some(PixelInternalFormat::Srgb8Alpha8Astc12x12));
+
+        //wrong level size, supercompressed, truncated
+        assert!(parse_ktx2(&ktx2(131, 8, 8, 1, &[vec![0u8; 16]])).is_err());
+        let mut basis = bytes.clone();
+        basis[44] = 1;
+        assert!(parse_ktx2(&basis).is_err());
+        assert!(parse_ktx2(&bytes[..bytes.len() - 1]).is_err());
+        assert!(parse_ktx2(&bytes[..30]).is_err());
+        let mut huge = bytes.clone();
+        huge[40..44].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(parse_ktx2(&huge), Err(Error::Native(NativeError::CompressedTexture(_)))));
+    }
+
+    #[test]
+    fn test_parse_dds() {
+        let mut bytes = DDS_MAGIC.to_vec();
+        let mut header = [0u32; 31];
+        header[0] = 124;
+        header[1] = 0x20000;
+        header[2] = 8;
+        header[3] = 8;
+        header[6] = 2;
+        header[20] = u32::from_le_bytes(*b"DXT5");
+        header[27] = 0x200;
+        for value in header.iter() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for face in 0..6u8 {
+            bytes.extend_from_slice(&[face; 64]);
+            bytes.extend_from_slice(&[face + 10; 16]);
+        }
+
+        let texture = parse_compressed_texture(&bytes).unwrap();
+        assert_eq!(texture.format, PixelInternalFormat::RgbaS3tcDxt5);
+        assert_eq!(texture.bind_target().unwrap(), TextureTarget::CubeMap);
+        assert_eq!(texture.levels[0].images[2], &[2u8; 64][..]);
+        assert_eq!(texture.levels[1].images[5], &[15u8; 16][..]);
+
+        //truncated header
+        assert!(parse_dds(&bytes[..60]).is_err());
+
+        //mip count that would be ~4G levels
+        let mut huge = bytes.clone();
+        huge[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(parse_dds(&huge), Err(Error::Native(NativeError::CompressedTexture(_)))));
+        //more levels than an 8x8 chain has
+        huge[28..32].copy_from_slice(&5u32.to_le_bytes());
+        assert!(parse_dds(&huge).is_err());
+
+        //huge array size
+        let mut array = bytes[..128].to_vec();
+        array[84..88].copy_from_slice(b"DX10");
+        for value in &[71u32, 3, 0x4, u32::MAX, 0] {
+            array.extend_from_slice(&value.to_le_bytes());
+        }
+        assert!(matches!(parse_dds(&array), Err(Error::Native(NativeError::CompressedTexture(_)))));
+
+        assert!(compressed_image_size(PixelInternalFormat::RgbaAstc12x12, u32::MAX, u32::MAX, u32::MAX).is_err());
+    }
+
+    #[test]
+    fn test_assign_compressed_texture() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        let id = renderer.create_texture().unwrap();
+        let bytes = ktx2(145, 8, 4, 1, &[vec![0u8; 32], vec![0u8; 16]]);
+        let texture = parse_ktx2(&bytes).unwrap();
+
+        assert!(matches!(
+            renderer.assign_compressed_texture(id, &texture, None::<fn(&MockContext)>),
+            Err(Error::Native(NativeError::NoExtension(Some(name)))) if name == "EXT_texture_compression_bptc"
+        ));
+
+        renderer.gl.add_extension(CompressedTextureExtension::Bptc.name());
+        assert_eq!(renderer.register_compressed_texture_extensions(), [CompressedTextureExtension::Bptc]);
+        renderer.gl.clear_commands();
+
+        renderer.assign_compressed_texture(id, &texture, None::<fn(&MockContext)>).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::BindTexture(TextureTarget::Texture2d),
+            GlCommand::CompressedTexImage {
+                target: TextureTarget::Texture2d as u32,
+                mip_level: 0,
+                internal_format: PixelInternalFormat::RgbaBptcUnorm,
+                size: (8, 4, 1),
+            },
+            GlCommand::CompressedTexImage {
+                target: TextureTarget::Texture2d as u32,
+                mip_level: 1,
+                internal_format: PixelInternalFormat::RgbaBptcUnorm,
+                size: (4, 2, 1),
+            },
+        ]);
+        assert_eq!(renderer.get_texture_storage(id).unwrap().levels, 2);
+    }
+
+    #[test]
+    fn test_assign_compressed_texture_storage() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        renderer.gl.add_extension(CompressedTextureExtension::Bptc.name());
+        renderer.register_compressed_texture_extensions();
+
+        let id = renderer.create_texture().unwrap();
+        renderer
+            .allocate_texture_storage(id, TextureTarget::Texture2d, 2, PixelInternalFormat::RgbaBptcUnorm, 8, 4, 1)
+            .unwrap();
+        renderer.gl.clear_commands();
+
+        let bytes = ktx2(145, 8, 4, 1, &[vec![0u8; 32], vec![0u8; 16]]);
+        let texture = parse_ktx2(&bytes).unwrap();
+        renderer.assign_compressed_texture(id, &texture, None::<fn(&MockContext)>).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::BindTexture(TextureTarget::Texture2d),
+            GlCommand::BindTexture(TextureTarget::Texture2d),
+            GlCommand::CompressedTexSubImage {
+                target: TextureTarget::Texture2d as u32,
+                mip_level: 0,
+                offset: (0, 0, 0),
+                internal_format: PixelInternalFormat::RgbaBptcUnorm,
+                size: (8, 4, 1),
+            },
+            GlCommand::BindTexture(TextureTarget::Texture2d),
+            GlCommand::CompressedTexSubImage {
+                target: TextureTarget::Texture2d as u32,
+                mip_level: 1,
+                offset: (0, 0, 0),
+                internal_format: PixelInternalFormat::RgbaBptcUnorm,
+                size: (4, 2, 1),
+            },
+        ]);
+        assert!(renderer.get_texture_storage(id).unwrap().immutable);
+
+        //a single block, which must be block aligned
+        let block = [0u8; 16];
+        renderer
+            .update_texture_sub(id, &TextureSubOptions::default(), 4, 0, &WebGlTextureSource::CompressedBufferView(&block, 4, 4, 1))
+            .unwrap();
+        assert!(matches!(
+            renderer.update_texture_sub(id, &TextureSubOptions::default(), 2, 0, &WebGlTextureSource::CompressedBufferView(&block, 4, 4, 1)),
+            Err(Error::Native(NativeError::TextureSubImageBounds(_)))
+        ));
+
+        //the container has to match the storage
+        let bytes = ktx2(145, 16, 4, 1, &[vec![0u8; 64]]);
+        let texture = parse_ktx2(&bytes).unwrap();
+        assert!(matches!(
+            renderer.assign_compressed_texture(id, &texture, None::<fn(&MockContext)>),
+            Err(Error::Native(NativeError::TextureSubImageBounds(_)))
+        ));
+
+        let uncompressed = renderer.create_texture().unwrap();
+        renderer
+            .allocate_texture_storage(uncompressed, TextureTarget::Texture2d, 1, PixelInternalFormat::Rgba8, 8, 4, 1)
+            .unwrap();
+        assert!(matches!(
+            renderer.update_texture_sub(uncompressed, &TextureSubOptions::default(), 0, 0, &WebGlTextureSource::CompressedBufferView(&block, 4, 4, 1)),
+            Err(Error::Native(NativeError::TextureFormatMismatch(_)))
+        ));
+    }
+}
@@ -6,20 +6,25 @@ use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 
 use super::attributes::PartialWebGlAttribute;
 use super::buffers::PartialWebGlBuffer;
+use super::context_loss::PartialWebGlContextLoss;
 use super::drawing::{PartialWebGlDrawing, PartialWebGl2Drawing};
 use super::enums::WebGlVersion;
 use super::extensions::PartialWebGlExtensions;
 use super::funcs::PartialWebGlFuncs;
 use super::misc::PartialWebGlMisc;
-use super::query::PartialWebGlGetParameter;
+use super::query::{PartialWebGlGetParameter, PartialWebGl2Query};
+use super::reflection::{PartialWebGlReflection, PartialWebGl2Reflection};
 use super::shader::PartialWebGlShaders;
-use super::textures::PartialWebGlTextures;
+use super::stencil::PartialWebGlStencil;
+use super::textures::{PartialWebGlTextures, PartialWebGl2Textures};
 use super::toggles::PartialWebGlToggle;
 use super::uniforms::PartialWebGlUniforms;
 use super::viewport::PartialWebGlViewport;
 use super::framebuffers::{PartialWebGlFrameBuffer, PartialWebGl2FrameBuffer};
 use super::renderbuffers::{PartialWebGlRenderBuffer, PartialWebGl2RenderBuffer};
 use super::read::{PartialWebGlReadPixels, PartialWebGl2ReadPixels};
+use super::vertex_arrays::PartialWebGl2VertexArray;
+use super::samplers::PartialWebGl2Sampler;
 use crate::errors::{Error, NativeError};
 use serde::Serialize;
 
@@ -62,6 +67,7 @@ impl PartialWebGlCanvas for WebGl2RenderingContext {
 pub trait WebGlCommon:
     PartialWebGlVersion
     + PartialWebGlCanvas
+    + PartialWebGlContextLoss
     + PartialWebGlAttribute
     + PartialWebGlGetParameter
     + PartialWebGlBuffer
@@ -69,7 +75,9 @@ pub trait WebGlCommon:
     + PartialWebGlExtensions
     + PartialWebGlFuncs
     + PartialWebGlMisc
+    + PartialWebGlReflection
     + PartialWebGlShaders
+    + PartialWebGlStencil
     + PartialWebGlTextures
     + PartialWebGlToggle
     + PartialWebGlUniforms
@@ -85,6 +93,11 @@ pub trait WebGl2Extra:
     + PartialWebGl2ReadPixels 
     + PartialWebGl2FrameBuffer 
     + PartialWebGl2Drawing
+    + PartialWebGl2VertexArray
+    + PartialWebGl2Sampler
+    + PartialWebGl2Textures
+    + PartialWebGl2Query
+    + PartialWebGl2Reflection
 {
 }
 
@@ -0,0 +1,343 @@
+use super::funcs::FuncSettings;
+use super::misc::MiscSettings;
+use super::profiler::GpuProfiler;
+use super::samplers::set_sampler_parameters;
+use super::shader::{compile_program, compile_shader, ProgramInfo};
+use super::stencil::StencilSettings;
+use super::toggles::ToggleFlags;
+use super::{WebGl2Extra, WebGlCommon, WebGlRenderer};
+use crate::errors::Error;
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlCanvasElement, WebGlShader, WebGlVertexArrayObject};
+use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
+
+/*
+ * When the context is lost, every handle in the lookups is dead
+ * Restoring swaps in fresh handles under the same Ids:
+ *
+ * shaders and programs are recompiled from their retained source
+ * samplers are recreated from their retained options
+ * buffers, textures, renderbuffers, framebuffers and vaos are recreated empty
+ * (immutable texture storage is re-allocated, but its contents are empty too)
+ *
+ * Anything that was uploaded or assigned into those (buffer data, texture images,
+ * renderbuffer storage, framebuffer attachments, vao configs, ubo bindings)
+ * is gone too - re-upload it from a hook added via add_context_restore_hook()
+ */
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ContextState {
+    Active,
+    Lost,
+    //the context is back, but restore_context() hasn't been called yet
+    Restored,
+}
+
+pub type ContextRestoreHook<T> = Box<dyn FnMut(&mut WebGlRenderer<T>) -> Result<(), Error>>;
+
+pub trait PartialWebGlContextLoss {
+    fn awsm_is_context_lost(&self) -> bool;
+}
+
+macro_rules! impl_context {
+    ($($type:ty { $($defs:tt)* })+) => {
+        $(impl PartialWebGlContextLoss for $type {
+            fn awsm_is_context_lost(&self) -> bool {
+                self.is_context_lost()
+            }
+            $($defs)*
+        })+
+    };
+}
+
+impl_context! {
+    WebGlRenderingContext{}
+    WebGl2RenderingContext{}
+}
+
+pub(super) struct ContextLossListener {
+    canvas: HtmlCanvasElement,
+    lost_cb: Closure<dyn FnMut(&Event)>,
+    restored_cb: Closure<dyn FnMut(&Event)>,
+}
+
+//nothing useful to do if removing fails, and throwing from drop would be worse
+impl Drop for ContextLossListener {
+    fn drop(&mut self) {
+        let _ = self
+            .canvas
+            .remove_event_listener_with_callback("webglcontextlost", self.lost_cb.as_ref().unchecked_ref());
+        let _ = self
+            .canvas
+            .remove_event_listener_with_callback("webglcontextrestored", self.restored_cb.as_ref().unchecked_ref());
+    }
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn context_state(&self) -> ContextState {
+        match self.context_state.get() {
+            //not listening, but can still catch it here
+            ContextState::Active if self.gl.awsm_is_context_lost() => ContextState::Lost,
+            state => state,
+        }
+    }
+
+    /// Listens for webglcontextlost / webglcontextrestored on the canvas
+    /// on_restored is where restore_context() should be called from
+    /// (or check context_state() before rendering each frame)
+    /// Calling this again replaces the previous listeners
+    pub fn listen_for_context_loss<F, G>(&mut self, mut on_lost: F, mut on_restored: G) -> Result<(), Error>
+    where
+        F: FnMut() + 'static,
+        G: FnMut() + 'static,
+    {
+        //drop the old listeners first
+        self.context_loss_listener = None;
+
+        let lost_cb = Closure::wrap(Box::new({
+            let state = Rc::clone(&self.context_state);
+            move |evt: &Event| {
+                //otherwise the browser will never restore it
+                evt.prevent_default();
+                state.set(ContextState::Lost);
+                on_lost();
+            }
+        }) as Box<dyn FnMut(&Event)>);
+
+        let restored_cb = Closure::wrap(Box::new({
+            let state = Rc::clone(&self.context_state);
+            move |_evt: &Event| {
+                state.set(ContextState::Restored);
+                on_restored();
+            }
+        }) as Box<dyn FnMut(&Event)>);
+
+        self.canvas
+            .add_event_listener_with_callback("webglcontextlost", lost_cb.as_ref().unchecked_ref())?;
+        self.canvas
+            .add_event_listener_with_callback("webglcontextrestored", restored_cb.as_ref().unchecked_ref())?;
+
+        self.context_loss_listener = Some(ContextLossListener {
+            canvas: self.canvas.clone(),
+            lost_cb,
+            restored_cb,
+        });
+
+        Ok(())
+    }
+
+    /// Hooks are called in order at the end of restore_context()
+    /// once all the Ids point to fresh (empty) objects again
+    pub fn add_context_restore_hook<F>(&mut self, hook: F)
+    where
+        F: FnMut(&mut Self) -> Result<(), Error> + 'static,
+    {
+        self.context_restore_hooks.push(Box::new(hook));
+    }
+
+    fn _restore_context<F>(&mut self, restore_version_specific: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Error>,
+    {
+        //the new context starts from scratch
+        self.current_program_id = None;
+        self.current_framebuffer_id.set(None);
+        self.current_framebuffer_target.set(None);
+        self.current_renderbuffer_id.set(None);
+        self.current_buffer_id.set(None);
+        self.current_buffer_target.set(None);
+        self.current_buffer_index.set(None);
+        self.current_vao_id.set(None);
+        self.current_sampler_ids.iter().for_each(|current| current.set(None));
+        self.toggle_flags = ToggleFlags::default();
+        self.func_settings = FuncSettings::default();
+        self.misc_settings = MiscSettings::default();
+        self.stencil_settings = StencilSettings::default();
+        //in-flight queries died with the old context
+        if self.gpu_profiler.is_some() {
+            self.gpu_profiler = Some(GpuProfiler::default());
+        }
+
+        if let Some((x, y, width, height)) = self.viewport {
+            self.gl.awsm_viewport(x, y, width, height);
+        }
+
+        //extensions first, since webgl1 vaos need one
+        let extension_names: Vec<String> = self.extension_lookup.keys().cloned().collect();
+        for name in extension_names {
+            let ext = self.gl.awsm_get_extension(&name)?;
+            self.extension_lookup.insert(name, ext);
+        }
+
+        for info in self.shader_lookup.iter_mut() {
+            info.shader = compile_shader(&self.gl, &info.source, info.source_type)?;
+            info.checked = true;
+        }
+
+        let shader_lookup = &self.shader_lookup;
+        for info in self.program_lookup.iter_mut() {
+            let shaders: Vec<&WebGlShader> = info
+                .shader_ids
+                .iter()
+                .map(|id| {
+                    shader_lookup
+                        .get(*id)
+                        .map(|shader_info| &shader_info.shader)
+                        .ok_or(Error::from("can't get shader for id!"))
+                })
+                .collect::<Result<Vec<&WebGlShader>, Error>>()?;
+
+            let program = compile_program(&self.gl, &shaders, &self.hardcoded_attribute_locations)?;
+
+            //all the cached locations belonged to the old program
+            let shader_ids = std::mem::take(&mut info.shader_ids);
+            *info = ProgramInfo::new(program, shader_ids);
+        }
+
+        for info in self.buffer_lookup.iter_mut() {
+            info.buffer = self.gl.awsm_create_buffer()?;
+            info.byte_len.set(0);
+        }
+
+        for info in self.texture_lookup.iter_mut() {
+            info.texture = self.gl.awsm_create_texture()?;
+        }
+
+        for info in self.renderbuffer_lookup.iter_mut() {
+            info.renderbuffer = self.gl.awsm_create_renderbuffer()?;
+            info.storage.set(None);
+        }
+
+        for framebuffer in self.framebuffer_lookup.iter_mut() {
+            *framebuffer = self.gl.awsm_create_framebuffer()?;
+        }
+
+        restore_version_specific(self)?;
+
+        self.context_state.set(ContextState::Active);
+
+        //hooks get the whole renderer, so take them out while they run
+        let mut hooks = std::mem::take(&mut self.context_restore_hooks);
+        let result = hooks.iter_mut().try_for_each(|hook| hook(self));
+        hooks.append(&mut self.context_restore_hooks);
+        self.context_restore_hooks = hooks;
+
+        result
+    }
+
+    fn _restore_vertex_arrays<F>(&mut self, create_vertex_array: F) -> Result<(), Error>
+    where
+        F: Fn(&Self) -> Result<WebGlVertexArrayObject, Error>,
+    {
+        let vaos = (0..self.vao_lookup.len())
+            .map(|_| create_vertex_array(self))
+            .collect::<Result<Vec<WebGlVertexArrayObject>, Error>>()?;
+        for (dest, vao) in self.vao_lookup.iter_mut().zip(vaos) {
+            *dest = vao;
+        }
+        Ok(())
+    }
+}
+
+impl WebGlRenderer<WebGlRenderingContext> {
+    pub fn restore_context(&mut self) -> Result<(), Error> {
+        self._restore_context(|renderer| {
+            renderer._restore_vertex_arrays(|renderer| renderer._create_vertex_array_handle())
+        })
+    }
+}
+
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    pub fn restore_context(&mut self) -> Result<(), Error> {
+        self._restore_context(|renderer| {
+            renderer._restore_vertex_arrays(|renderer| renderer._create_vertex_array_handle())?;
+
+            //samplers keep their options, so they come back fully configured
+            for info in renderer.sampler_lookup.iter_mut() {
+                info.sampler = renderer.gl.awsm_create_sampler()?;
+                set_sampler_parameters(&renderer.gl, &info.sampler, &info.opts);
+            }
+
+            for info in renderer.texture_lookup.iter() {
+                if let (Some(bind_target), Some(storage)) = (info.bind_target, info.storage) {
+                    if storage.immutable {
+                        renderer.gl.awsm_bind_texture(bind_target, &info.texture);
+                        renderer.gl.awsm_texture_storage(
+                            bind_target,
+                            storage.levels,
+                            storage.internal_format,
+                            storage.width,
+                            storage.height,
+                            storage.depth,
+                        );
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{BufferTarget, GlCommand, GlToggle, MockContext, MockObjectKind, ShaderType, WebGlVersion};
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_restore_context() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        let vertex = renderer.compile_shader("vertex source", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("fragment source", ShaderType::Fragment).unwrap();
+        let program = renderer.compile_program(&[vertex, fragment]).unwrap();
+        let buffer = renderer.create_buffer().unwrap();
+        let texture = renderer.create_texture().unwrap();
+        let vao = renderer.create_vertex_array().unwrap();
+        renderer.bind_buffer(buffer, BufferTarget::ArrayBuffer).unwrap();
+        renderer.toggle(GlToggle::Blend, true);
+
+        let restored_buffers = Rc::new(RefCell::new(Vec::new()));
+        renderer.add_context_restore_hook({
+            let restored_buffers = Rc::clone(&restored_buffers);
+            move |renderer| {
+                renderer.bind_buffer(buffer, BufferTarget::ArrayBuffer)?;
+                restored_buffers.borrow_mut().push(buffer);
+                Ok(())
+            }
+        });
+
+        renderer.gl.context_lost.set(true);
+        assert_eq!(renderer.context_state(), ContextState::Lost);
+        renderer.gl.context_lost.set(false);
+        renderer.gl.clear_commands();
+
+        renderer.restore_context().unwrap();
+        assert_eq!(renderer.context_state(), ContextState::Active);
+        assert_eq!(*restored_buffers.borrow(), [buffer]);
+
+        let commands = renderer.gl.take_commands();
+        assert!(commands.contains(&GlCommand::ShaderSource("vertex source".to_string())));
+        assert!(commands.contains(&GlCommand::ShaderSource("fragment source".to_string())));
+        //the hook's bind isn't skipped by the stale cache
+        assert!(commands.contains(&GlCommand::BindBuffer(BufferTarget::ArrayBuffer)));
+        assert_eq!(renderer.gl.objects_created(MockObjectKind::Program), 2);
+        assert_eq!(renderer.gl.objects_created(MockObjectKind::Texture), 2);
+        assert_eq!(renderer.gl.objects_created(MockObjectKind::VertexArray), 2);
+
+        //same ids, fresh state
+        renderer.activate_program(program).unwrap();
+        renderer.activate_vertex_array(vao).unwrap();
+        renderer.get_texture(texture).unwrap();
+        renderer.toggle(GlToggle::Blend, true);
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::UseProgram,
+            GlCommand::BindVertexArray,
+            GlCommand::Enable(GlToggle::Blend),
+        ]);
+    }
+}
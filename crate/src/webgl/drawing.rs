@@ -1,14 +1,21 @@
 use super::{BeginMode, BufferMask, DataType, WebGlCommon, WebGlRenderer, DrawBuffer, Buffer};
-use crate::errors::Error;
-use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
+use super::{WebglDrawInstancedBaseVertexBaseInstance, WebglMultiDrawInstancedBaseVertexBaseInstance};
+use crate::errors::{Error, NativeError};
+use web_sys::{WebGl2RenderingContext, WebGlRenderingContext, WebglMultiDraw};
 use crate::data::{TypedData};
-use js_sys::Array;
+use js_sys::{Array, Int32Array};
 
 pub trait PartialWebGlDrawing {
     fn awsm_clear(&self, bits: &[BufferMask]);
     fn awsm_scissor(&self, x: i32, y: i32, width: u32, height: u32);
     fn awsm_draw_arrays(&self, mode: BeginMode, first: u32, count: u32);
     fn awsm_draw_elements(&self, mode: BeginMode, count: u32, data_type: DataType, offset: u32);
+
+    //WEBGL_multi_draw, all the slices are the same length
+    fn awsm_multi_draw_arrays(&self, ext: &WebglMultiDraw, mode: BeginMode, firsts: &[u32], counts: &[u32]);
+    fn awsm_multi_draw_elements(&self, ext: &WebglMultiDraw, mode: BeginMode, counts: &[u32], data_type: DataType, offsets: &[u32]);
+    fn awsm_multi_draw_arrays_instanced(&self, ext: &WebglMultiDraw, mode: BeginMode, firsts: &[u32], counts: &[u32], instance_counts: &[u32]);
+    fn awsm_multi_draw_elements_instanced(&self, ext: &WebglMultiDraw, mode: BeginMode, counts: &[u32], data_type: DataType, offsets: &[u32], instance_counts: &[u32]);
 }
 
 pub trait PartialWebGl2Drawing {
@@ -16,6 +23,12 @@ pub trait PartialWebGl2Drawing {
     fn awsm_clear_draw_buffer_vi32(&self, buffer:Buffer, draw_buffer_index: usize, values:&[i32]);
     fn awsm_clear_draw_buffer_vu32(&self, buffer:Buffer, draw_buffer_index: usize, values:&[u32]);
     fn awsm_clear_draw_buffer_fi(&self, buffer:Buffer, draw_buffer_index: usize, depth:f32, stencil:i32);
+
+    //WEBGL_draw_instanced_base_vertex_base_instance and its multi draw version
+    fn awsm_draw_arrays_instanced_base_instance(&self, ext: &WebglDrawInstancedBaseVertexBaseInstance, mode: BeginMode, first: u32, count: u32, instance_count: u32, base_instance: u32);
+    fn awsm_draw_elements_instanced_base_vertex_base_instance(&self, ext: &WebglDrawInstancedBaseVertexBaseInstance, mode: BeginMode, count: u32, data_type: DataType, offset: u32, instance_count: u32, base_vertex: i32, base_instance: u32);
+    fn awsm_multi_draw_arrays_instanced_base_instance(&self, ext: &WebglMultiDrawInstancedBaseVertexBaseInstance, mode: BeginMode, firsts: &[u32], counts: &[u32], instance_counts: &[u32], base_instances: &[u32]);
+    fn awsm_multi_draw_elements_instanced_base_vertex_base_instance(&self, ext: &WebglMultiDrawInstancedBaseVertexBaseInstance, mode: BeginMode, counts: &[u32], data_type: DataType, offsets: &[u32], instance_counts: &[u32], base_vertices: &[i32], base_instances: &[u32]);
 }
 macro_rules! impl_context {
     ($($type:ty { $($defs:tt)* })+) => {
@@ -40,6 +53,46 @@ macro_rules! impl_context {
                 self.draw_elements_with_i32(mode as u32, count as i32, data_type as u32, offset as i32);
             }
 
+            fn awsm_multi_draw_arrays(&self, ext: &WebglMultiDraw, mode: BeginMode, firsts: &[u32], counts: &[u32]) {
+                ext.multi_draw_arrays_webgl_with_js_i32_array_and_js_i32_array(
+                    mode as u32,
+                    &i32_view(firsts), 0,
+                    &i32_view(counts), 0,
+                    firsts.len() as i32
+                );
+            }
+
+            fn awsm_multi_draw_elements(&self, ext: &WebglMultiDraw, mode: BeginMode, counts: &[u32], data_type: DataType, offsets: &[u32]) {
+                ext.multi_draw_elements_webgl_with_js_i32_array_and_js_i32_array(
+                    mode as u32,
+                    &i32_view(counts), 0,
+                    data_type as u32,
+                    &i32_view(offsets), 0,
+                    counts.len() as i32
+                );
+            }
+
+            fn awsm_multi_draw_arrays_instanced(&self, ext: &WebglMultiDraw, mode: BeginMode, firsts: &[u32], counts: &[u32], instance_counts: &[u32]) {
+                ext.multi_draw_arrays_instanced_webgl_with_js_i32_array_and_js_i32_array_and_js_i32_array(
+                    mode as u32,
+                    &i32_view(firsts), 0,
+                    &i32_view(counts), 0,
+                    &i32_view(instance_counts), 0,
+                    firsts.len() as i32
+                );
+            }
+
+            fn awsm_multi_draw_elements_instanced(&self, ext: &WebglMultiDraw, mode: BeginMode, counts: &[u32], data_type: DataType, offsets: &[u32], instance_counts: &[u32]) {
+                ext.multi_draw_elements_instanced_webgl_with_js_i32_array_and_js_i32_array_and_js_i32_array(
+                    mode as u32,
+                    &i32_view(counts), 0,
+                    data_type as u32,
+                    &i32_view(offsets), 0,
+                    &i32_view(instance_counts), 0,
+                    counts.len() as i32
+                );
+            }
+
             $($defs)*
         })+
     };
@@ -50,6 +103,13 @@ impl_context! {
     WebGl2RenderingContext{}
 }
 
+//the extension wants GLint, the values are never large enough for the sign to matter
+fn i32_view(values: &[u32]) -> Int32Array {
+    // SAFETY: u32 and i32 have the same size and alignment, and every bit pattern is valid for both
+    let values: &[i32] = unsafe { std::slice::from_raw_parts(values.as_ptr() as *const i32, values.len()) };
+    TypedData::new(values).into()
+}
+
 impl PartialWebGl2Drawing for WebGl2RenderingContext {
     fn awsm_clear_draw_buffer_vf32(&self, buffer:Buffer, draw_buffer_index: usize, values:&[f32]) {
         self.clear_bufferfv_with_f32_array(buffer as u32, draw_buffer_index as i32, values);
@@ -63,6 +123,35 @@ impl PartialWebGl2Drawing for WebGl2RenderingContext {
     fn awsm_clear_draw_buffer_fi(&self, buffer:Buffer, draw_buffer_index: usize, depth:f32, stencil:i32) {
         self.clear_bufferfi(buffer as u32, draw_buffer_index as i32, depth, stencil);
     }
+
+    fn awsm_draw_arrays_instanced_base_instance(&self, ext: &WebglDrawInstancedBaseVertexBaseInstance, mode: BeginMode, first: u32, count: u32, instance_count: u32, base_instance: u32) {
+        ext.draw_arrays_instanced_base_instance_webgl(mode as u32, first as i32, count as i32, instance_count as i32, base_instance);
+    }
+    fn awsm_draw_elements_instanced_base_vertex_base_instance(&self, ext: &WebglDrawInstancedBaseVertexBaseInstance, mode: BeginMode, count: u32, data_type: DataType, offset: u32, instance_count: u32, base_vertex: i32, base_instance: u32) {
+        ext.draw_elements_instanced_base_vertex_base_instance_webgl(mode as u32, count as i32, data_type as u32, offset as i32, instance_count as i32, base_vertex, base_instance);
+    }
+    fn awsm_multi_draw_arrays_instanced_base_instance(&self, ext: &WebglMultiDrawInstancedBaseVertexBaseInstance, mode: BeginMode, firsts: &[u32], counts: &[u32], instance_counts: &[u32], base_instances: &[u32]) {
+        ext.multi_draw_arrays_instanced_base_instance_webgl(
+            mode as u32,
+            &i32_view(firsts), 0,
+            &i32_view(counts), 0,
+            &i32_view(instance_counts), 0,
+            &TypedData::new(base_instances).into(), 0,
+            firsts.len() as i32
+        );
+    }
+    fn awsm_multi_draw_elements_instanced_base_vertex_base_instance(&self, ext: &WebglMultiDrawInstancedBaseVertexBaseInstance, mode: BeginMode, counts: &[u32], data_type: DataType, offsets: &[u32], instance_counts: &[u32], base_vertices: &[i32], base_instances: &[u32]) {
+        ext.multi_draw_elements_instanced_base_vertex_base_instance_webgl(
+            mode as u32,
+            &i32_view(counts), 0,
+            data_type as u32,
+            &i32_view(offsets), 0,
+            &i32_view(instance_counts), 0,
+            &TypedData::new(base_vertices).into(), 0,
+            &TypedData::new(base_instances).into(), 0,
+            counts.len() as i32
+        );
+    }
 }
 
 impl<T: WebGlCommon> WebGlRenderer<T> {
@@ -80,6 +169,97 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     pub fn draw_elements(&self, mode: BeginMode, count: u32, data_type: DataType, offset: u32) {
         self.gl.awsm_draw_elements(mode, count, data_type, offset);
     }
+
+    /*
+     * Multi draws go through WEBGL_multi_draw once it's registered (register_extension_multi_draw)
+     * otherwise they're the same draws one at a time
+     *
+     * the instanced variants are per-version, see instancing.rs
+     */
+    pub fn multi_draw_arrays(&self, mode: BeginMode, firsts: &[u32], counts: &[u32]) -> Result<(), Error> {
+        check_draw_counts(&[firsts.len(), counts.len()])?;
+
+        match self.get_extension_multi_draw() {
+            Ok(ext) => self.gl.awsm_multi_draw_arrays(ext, mode, firsts, counts),
+            Err(_) => {
+                for (first, count) in firsts.iter().zip(counts) {
+                    self.draw_arrays(mode, *first, *count);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn multi_draw_elements(&self, mode: BeginMode, counts: &[u32], data_type: DataType, offsets: &[u32]) -> Result<(), Error> {
+        check_draw_counts(&[counts.len(), offsets.len()])?;
+
+        match self.get_extension_multi_draw() {
+            Ok(ext) => self.gl.awsm_multi_draw_elements(ext, mode, counts, data_type, offsets),
+            Err(_) => {
+                for (count, offset) in counts.iter().zip(offsets) {
+                    self.draw_elements(mode, *count, data_type, *offset);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn _multi_draw_arrays_instanced<F>(
+        &self,
+        mode: BeginMode,
+        firsts: &[u32],
+        counts: &[u32],
+        instance_counts: &[u32],
+        draw_instanced: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(&Self, BeginMode, u32, u32, u32) -> Result<(), Error>,
+    {
+        check_draw_counts(&[firsts.len(), counts.len(), instance_counts.len()])?;
+
+        match self.get_extension_multi_draw() {
+            Ok(ext) => self.gl.awsm_multi_draw_arrays_instanced(ext, mode, firsts, counts, instance_counts),
+            Err(_) => {
+                for ((first, count), instance_count) in firsts.iter().zip(counts).zip(instance_counts) {
+                    draw_instanced(self, mode, *first, *count, *instance_count)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn _multi_draw_elements_instanced<F>(
+        &self,
+        mode: BeginMode,
+        counts: &[u32],
+        data_type: DataType,
+        offsets: &[u32],
+        instance_counts: &[u32],
+        draw_instanced: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(&Self, BeginMode, u32, DataType, u32, u32) -> Result<(), Error>,
+    {
+        check_draw_counts(&[counts.len(), offsets.len(), instance_counts.len()])?;
+
+        match self.get_extension_multi_draw() {
+            Ok(ext) => self.gl.awsm_multi_draw_elements_instanced(ext, mode, counts, data_type, offsets, instance_counts),
+            Err(_) => {
+                for ((count, offset), instance_count) in counts.iter().zip(offsets).zip(instance_counts) {
+                    draw_instanced(self, mode, *count, data_type, *offset, *instance_count)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn check_draw_counts(lens: &[usize]) -> Result<(), Error> {
+    if lens.iter().all(|len| *len == lens[0]) {
+        Ok(())
+    } else {
+        Err(NativeError::MultiDrawLength.into())
+    }
 }
 
 impl WebGlRenderer<WebGlRenderingContext> {
@@ -141,3 +321,41 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         self.clear_draw_buffer_depth_stencil_values(1.0, 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_multi_draw() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        //no extension, one draw each
+        assert!(renderer.register_extension_multi_draw().is_err());
+        renderer.gl.clear_commands();
+        renderer.multi_draw_arrays(BeginMode::Triangles, &[0, 6], &[6, 3]).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::DrawArrays { mode: BeginMode::Triangles, first: 0, count: 6 },
+            GlCommand::DrawArrays { mode: BeginMode::Triangles, first: 6, count: 3 },
+        ]);
+
+        renderer.gl.add_extension("WEBGL_multi_draw");
+        renderer.register_extension_multi_draw().unwrap();
+        renderer.gl.clear_commands();
+        renderer
+            .multi_draw_elements(BeginMode::Triangles, &[6, 6, 3], DataType::UnsignedShort, &[0, 12, 24])
+            .unwrap();
+        assert_eq!(renderer.gl.take_commands(), [GlCommand::MultiDrawElements {
+            mode: BeginMode::Triangles,
+            data_type: DataType::UnsignedShort,
+            draw_count: 3,
+        }]);
+
+        assert!(matches!(
+            renderer.multi_draw_arrays(BeginMode::Triangles, &[0, 6], &[6]),
+            Err(Error::Native(NativeError::MultiDrawLength))
+        ));
+        assert!(renderer.gl.take_commands().is_empty());
+    }
+}
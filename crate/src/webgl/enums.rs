@@ -1,3 +1,5 @@
+use super::WebGlExtension;
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -65,6 +67,13 @@ pub enum TextureParameterName {
     CompareFunc = 0x884D,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TextureCompareMode {
+    None = 0,
+    CompareRefToTexture = 0x884E,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(u32)]
 pub enum TextureWrapTarget {
@@ -99,7 +108,7 @@ pub enum TextureMinFilter {
     LinearMipMapLinear = 0x2703,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum TextureTarget {
     Texture2d = 0x0DE1,
@@ -231,7 +240,7 @@ pub enum PixelDataFormat {
     SrgbAlphaExt = 0x8C42,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum PixelInternalFormat {
     //WebGL1 and 2
@@ -282,7 +291,208 @@ pub enum PixelInternalFormat {
     Rgba16f = 0x881A,
     Rgb16f = 0x881B,
 
+    //When using WEBGL_compressed_texture_s3tc
+    RgbS3tcDxt1 = 0x83F0,
+    RgbaS3tcDxt1 = 0x83F1,
+    RgbaS3tcDxt3 = 0x83F2,
+    RgbaS3tcDxt5 = 0x83F3,
+
+    //When using WEBGL_compressed_texture_s3tc_srgb
+    SrgbS3tcDxt1 = 0x8C4C,
+    SrgbAlphaS3tcDxt1 = 0x8C4D,
+    SrgbAlphaS3tcDxt3 = 0x8C4E,
+    SrgbAlphaS3tcDxt5 = 0x8C4F,
+
+    //When using WEBGL_compressed_texture_etc
+    R11Eac = 0x9270,
+    SignedR11Eac = 0x9271,
+    Rg11Eac = 0x9272,
+    SignedRg11Eac = 0x9273,
+    Rgb8Etc2 = 0x9274,
+    Srgb8Etc2 = 0x9275,
+    Rgb8PunchthroughAlpha1Etc2 = 0x9276,
+    Srgb8PunchthroughAlpha1Etc2 = 0x9277,
+    Rgba8Etc2Eac = 0x9278,
+    Srgb8Alpha8Etc2Eac = 0x9279,
+
+    //When using WEBGL_compressed_texture_astc
+    RgbaAstc4x4 = 0x93B0,
+    RgbaAstc5x4 = 0x93B1,
+    RgbaAstc5x5 = 0x93B2,
+    RgbaAstc6x5 = 0x93B3,
+    RgbaAstc6x6 = 0x93B4,
+    RgbaAstc8x5 = 0x93B5,
+    RgbaAstc8x6 = 0x93B6,
+    RgbaAstc8x8 = 0x93B7,
+    RgbaAstc10x5 = 0x93B8,
+    RgbaAstc10x6 = 0x93B9,
+    RgbaAstc10x8 = 0x93BA,
+    RgbaAstc10x10 = 0x93BB,
+    RgbaAstc12x10 = 0x93BC,
+    RgbaAstc12x12 = 0x93BD,
+    Srgb8Alpha8Astc4x4 = 0x93D0,
+    Srgb8Alpha8Astc5x4 = 0x93D1,
+    Srgb8Alpha8Astc5x5 = 0x93D2,
+    Srgb8Alpha8Astc6x5 = 0x93D3,
+    Srgb8Alpha8Astc6x6 = 0x93D4,
+    Srgb8Alpha8Astc8x5 = 0x93D5,
+    Srgb8Alpha8Astc8x6 = 0x93D6,
+    Srgb8Alpha8Astc8x8 = 0x93D7,
+    Srgb8Alpha8Astc10x5 = 0x93D8,
+    Srgb8Alpha8Astc10x6 = 0x93D9,
+    Srgb8Alpha8Astc10x8 = 0x93DA,
+    Srgb8Alpha8Astc10x10 = 0x93DB,
+    Srgb8Alpha8Astc12x10 = 0x93DC,
+    Srgb8Alpha8Astc12x12 = 0x93DD,
+
+    //When using EXT_texture_compression_bptc
+    RgbaBptcUnorm = 0x8E8C,
+    SrgbAlphaBptcUnorm = 0x8E8D,
+    RgbBptcSignedFloat = 0x8E8E,
+    RgbBptcUnsignedFloat = 0x8E8F,
+
+    //When using EXT_texture_compression_rgtc
+    RedRgtc1 = 0x8DBB,
+    SignedRedRgtc1 = 0x8DBC,
+    RedGreenRgtc2 = 0x8DBD,
+    SignedRedGreenRgtc2 = 0x8DBE,
+}
+
+impl PixelInternalFormat {
+    //Only sized formats can be used for immutable storage
+    pub fn is_sized(self) -> bool {
+        match self {
+            Self::Alpha | Self::Rgb | Self::Rgba | Self::Luminance | Self::LuminanceAlpha
+                | Self::DepthComponent | Self::DepthStencil | Self::SrgbAlphaExt | Self::Srgb => false,
+            _ => true
+        }
+    }
+
+    //The data format that uploads into this internal format must use
+    pub fn data_format(self) -> PixelDataFormat {
+        match self {
+            Self::Alpha => PixelDataFormat::Alpha,
+            Self::Luminance => PixelDataFormat::Luminance,
+            Self::LuminanceAlpha => PixelDataFormat::LuminanceAlpha,
+            Self::DepthComponent => PixelDataFormat::DepthComponent,
+            Self::DepthStencil => PixelDataFormat::DepthStencil,
+            Self::SrgbAlphaExt => PixelDataFormat::SrgbAlphaExt,
+
+            Self::R8 => PixelDataFormat::Red,
+            Self::R8ui | Self::R8i | Self::R16ui | Self::R16i | Self::R32ui | Self::R32i => PixelDataFormat::RedInteger,
+
+            Self::Rg8 => PixelDataFormat::Rg,
+            Self::Rg8ui | Self::Rg8i | Self::Rg16ui | Self::Rg16i | Self::Rg32ui | Self::Rg32i => PixelDataFormat::RgInteger,
+
+            Self::Rgb | Self::Rgb8 | Self::Srgb | Self::Srgb8 | Self::Rgb16f | Self::Rgb32f => PixelDataFormat::Rgb,
+
+            Self::Rgba | Self::Rgba8 | Self::Rgb10a2 | Self::Srgb8Alpha8 | Self::Rgba16f | Self::Rgba32f => PixelDataFormat::Rgba,
+            Self::Rgba8ui | Self::Rba8i | Self::Rgb10a2ui | Self::Rgba16ui | Self::Rgba16i | Self::Rgba32i | Self::Rgba32ui => PixelDataFormat::RgbaInteger,
+
+            //compressed formats can't be uploaded from pixel data at all
+            //but this is what they decompress to
+            Self::R11Eac | Self::SignedR11Eac | Self::RedRgtc1 | Self::SignedRedRgtc1 => PixelDataFormat::Red,
+            Self::Rg11Eac | Self::SignedRg11Eac | Self::RedGreenRgtc2 | Self::SignedRedGreenRgtc2 => PixelDataFormat::Rg,
+            Self::RgbS3tcDxt1 | Self::SrgbS3tcDxt1 | Self::Rgb8Etc2 | Self::Srgb8Etc2
+                | Self::RgbBptcSignedFloat | Self::RgbBptcUnsignedFloat => PixelDataFormat::Rgb,
+            _ => PixelDataFormat::Rgba,
+        }
+    }
+
+    pub fn is_compressed(self) -> bool {
+        self.compressed_extension().is_some()
+    }
+
+    //None for compressed formats (see compressed_block)
+    //unsized formats are assumed to be 8 bits per channel, and depth 32 bits
+    pub fn bytes_per_pixel(self) -> Option<u32> {
+        if self.is_compressed() {
+            return None;
+        }
+        Some(match self {
+            Self::Alpha | Self::Luminance | Self::R8 | Self::R8ui | Self::R8i => 1,
+            Self::LuminanceAlpha | Self::R16ui | Self::R16i | Self::Rg8 | Self::Rg8ui | Self::Rg8i => 2,
+            Self::Rgb | Self::Rgb8 | Self::Srgb | Self::Srgb8 => 3,
+            Self::Rgb16f => 6,
+            Self::Rg32ui | Self::Rg32i | Self::Rgba16ui | Self::Rgba16i | Self::Rgba16f => 8,
+            Self::Rgb32f => 12,
+            Self::Rgba32i | Self::Rgba32ui | Self::Rgba32f => 16,
+            _ => 4,
+        })
+    }
+
+    //The extension that has to be registered before using a compressed format
+    pub fn compressed_extension(self) -> Option<CompressedTextureExtension> {
+        match self as u32 {
+            0x83F0..=0x83F3 => Some(CompressedTextureExtension::S3tc),
+            0x8C4C..=0x8C4F => Some(CompressedTextureExtension::S3tcSrgb),
+            0x9270..=0x9279 => Some(CompressedTextureExtension::Etc),
+            0x93B0..=0x93BD | 0x93D0..=0x93DD => Some(CompressedTextureExtension::Astc),
+            0x8E8C..=0x8E8F => Some(CompressedTextureExtension::Bptc),
+            0x8DBB..=0x8DBE => Some(CompressedTextureExtension::Rgtc),
+            _ => None,
+        }
+    }
+
+    //block width, block height, bytes per block
+    pub fn compressed_block(self) -> Option<(u32, u32, u32)> {
+        let ext = self.compressed_extension()?;
+        Some(match ext {
+            CompressedTextureExtension::S3tc | CompressedTextureExtension::S3tcSrgb => match self {
+                Self::RgbS3tcDxt1 | Self::RgbaS3tcDxt1 | Self::SrgbS3tcDxt1 | Self::SrgbAlphaS3tcDxt1 => (4, 4, 8),
+                _ => (4, 4, 16),
+            },
+            CompressedTextureExtension::Etc => match self {
+                Self::R11Eac | Self::SignedR11Eac | Self::Rgb8Etc2 | Self::Srgb8Etc2
+                    | Self::Rgb8PunchthroughAlpha1Etc2 | Self::Srgb8PunchthroughAlpha1Etc2 => (4, 4, 8),
+                _ => (4, 4, 16),
+            },
+            CompressedTextureExtension::Astc => {
+                const BLOCKS: [(u32, u32); 14] = [
+                    (4, 4), (5, 4), (5, 5), (6, 5), (6, 6), (8, 5), (8, 6),
+                    (8, 8), (10, 5), (10, 6), (10, 8), (10, 10), (12, 10), (12, 12),
+                ];
+                let (width, height) = BLOCKS[((self as u32) & 0xF) as usize];
+                (width, height, 16)
+            },
+            CompressedTextureExtension::Bptc => (4, 4, 16),
+            CompressedTextureExtension::Rgtc => match self {
+                Self::RedRgtc1 | Self::SignedRedRgtc1 => (4, 4, 8),
+                _ => (4, 4, 16),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CompressedTextureExtension {
+    S3tc,
+    S3tcSrgb,
+    Etc,
+    Astc,
+    Bptc,
+    Rgtc,
+}
+
+impl CompressedTextureExtension {
+    pub const ALL: [Self; 6] = [Self::S3tc, Self::S3tcSrgb, Self::Etc, Self::Astc, Self::Bptc, Self::Rgtc];
 
+    pub fn name(self) -> &'static str {
+        WebGlExtension::from(self).name()
+    }
+}
+
+impl From<CompressedTextureExtension> for WebGlExtension {
+    fn from(ext: CompressedTextureExtension) -> Self {
+        match ext {
+            CompressedTextureExtension::S3tc => Self::WebGlCompressedTextureS3tc,
+            CompressedTextureExtension::S3tcSrgb => Self::WebGlCompressedTextureS3tcSrgb,
+            CompressedTextureExtension::Etc => Self::WebGlCompressedTextureEtc,
+            CompressedTextureExtension::Astc => Self::WebGlCompressedTextureAstc,
+            CompressedTextureExtension::Bptc => Self::ExtTextureCompressionBptc,
+            CompressedTextureExtension::Rgtc => Self::ExtTextureCompressionRgtc,
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -324,9 +534,11 @@ pub enum GlParameter {
     ShadingLanguageVersion = 0x8B8C,
     CurrentProgram = 0x8B8D,
     BlendColor = 0x8005,
+    //EXT_disjoint_timer_query_webgl2
+    GpuDisjoint = 0x8FBB,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum CmpFunction {
     Never = 0x0200,
@@ -339,7 +551,20 @@ pub enum CmpFunction {
     Always = 0x0207,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum StencilOp {
+    Keep = 0x1E00,
+    Zero = 0,
+    Replace = 0x1E01,
+    Incr = 0x1E02,
+    IncrWrap = 0x8507,
+    Decr = 0x1E03,
+    DecrWrap = 0x8508,
+    Invert = 0x150A,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum BlendEquation {
     Add = 0x8006,
@@ -349,7 +574,7 @@ pub enum BlendEquation {
     Max = 0x8008,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum BlendFactor {
     Zero = 0,
@@ -404,9 +629,11 @@ pub enum WebGlSpecific {
     InvalidIndex = 0xFFFFFFFF,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum UniformDataType {
+    Float = 0x1406,
+    Int = 0x1404,
     FloatVec2 = 0x8B50,
     FloatVec3 = 0x8B51,
     FloatVec4 = 0x8B52,
@@ -424,6 +651,16 @@ pub enum UniformDataType {
     SamplerCube = 0x8B60,
 
     //WebGL2 only
+    UnsignedInt = 0x1405,
+    UnsignedIntVec2 = 0x8DC6,
+    UnsignedIntVec3 = 0x8DC7,
+    UnsignedIntVec4 = 0x8DC8,
+    FloatMat2x3 = 0x8B65,
+    FloatMat2x4 = 0x8B66,
+    FloatMat3x2 = 0x8B67,
+    FloatMat3x4 = 0x8B68,
+    FloatMat4x2 = 0x8B69,
+    FloatMat4x3 = 0x8B6A,
     Sampler3d = 0x8B5F,
     Sampler2dShadow = 0x8B62,
     SamplerCubeShadow = 0x8DC5,
@@ -442,6 +679,85 @@ pub enum UniformDataType {
     UnsignedInt24_8 = 0x84FA,
 }
 
+impl UniformDataType {
+    pub const ALL: [UniformDataType; 41] = [
+        UniformDataType::Float,
+        UniformDataType::Int,
+        UniformDataType::FloatVec2,
+        UniformDataType::FloatVec3,
+        UniformDataType::FloatVec4,
+        UniformDataType::IntVec2,
+        UniformDataType::IntVec3,
+        UniformDataType::IntVec4,
+        UniformDataType::Bool,
+        UniformDataType::BoolVec2,
+        UniformDataType::BoolVec3,
+        UniformDataType::BoolVec4,
+        UniformDataType::FloatMat2,
+        UniformDataType::FloatMat3,
+        UniformDataType::FloatMat4,
+        UniformDataType::Sampler2d,
+        UniformDataType::SamplerCube,
+        UniformDataType::UnsignedInt,
+        UniformDataType::UnsignedIntVec2,
+        UniformDataType::UnsignedIntVec3,
+        UniformDataType::UnsignedIntVec4,
+        UniformDataType::FloatMat2x3,
+        UniformDataType::FloatMat2x4,
+        UniformDataType::FloatMat3x2,
+        UniformDataType::FloatMat3x4,
+        UniformDataType::FloatMat4x2,
+        UniformDataType::FloatMat4x3,
+        UniformDataType::Sampler3d,
+        UniformDataType::Sampler2dShadow,
+        UniformDataType::SamplerCubeShadow,
+        UniformDataType::Sampler2dArray,
+        UniformDataType::Sampler2dArrayShadow,
+        UniformDataType::IntSampler2d,
+        UniformDataType::IntSampler3d,
+        UniformDataType::IntSamplerCube,
+        UniformDataType::IntSampler2dArray,
+        UniformDataType::UnsignedIntSampler2d,
+        UniformDataType::UnsignedIntSampler3d,
+        UniformDataType::UnsignedIntSamplerCube,
+        UniformDataType::UnsignedIntSampler2dArray,
+        UniformDataType::UnsignedInt24_8,
+    ];
+
+    pub fn is_sampler(self) -> bool {
+        match self {
+            UniformDataType::Sampler2d
+            | UniformDataType::SamplerCube
+            | UniformDataType::Sampler3d
+            | UniformDataType::Sampler2dShadow
+            | UniformDataType::SamplerCubeShadow
+            | UniformDataType::Sampler2dArray
+            | UniformDataType::Sampler2dArrayShadow
+            | UniformDataType::IntSampler2d
+            | UniformDataType::IntSampler3d
+            | UniformDataType::IntSamplerCube
+            | UniformDataType::IntSampler2dArray
+            | UniformDataType::UnsignedIntSampler2d
+            | UniformDataType::UnsignedIntSampler3d
+            | UniformDataType::UnsignedIntSamplerCube
+            | UniformDataType::UnsignedIntSampler2dArray => true,
+            _ => false,
+        }
+    }
+}
+
+impl TryFrom<u32> for UniformDataType {
+    type Error = &'static str;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        UniformDataType::ALL
+            .iter()
+            .copied()
+            .find(|data_type| *data_type as u32 == value)
+            .ok_or("unknown uniform data type")
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(u32)]
 pub enum ShaderQuery {
@@ -469,9 +785,11 @@ pub enum ProgramQuery {
     TransformFeedbackBufferMode = 0x8C7F,
     TransformFeedbackVaryings = 0x8C83,
     ActiveUniformBlocks = 0x8A36,
+    //KHR_parallel_shader_compile
+    CompletionStatusKhr = 0x91B1,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum RenderBufferFormat {
     Rgba4 = 0x8056,
@@ -525,6 +843,23 @@ pub enum RenderBufferFormat {
 
 }
 
+impl RenderBufferFormat {
+    //per sample
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            Self::StencilIndex8 | Self::R8 | Self::R8ui | Self::R8i => 1,
+            Self::Rgba4 | Self::Rgb4a1 | Self::Rgb565 | Self::DepthComponent16
+                | Self::R16ui | Self::R16i | Self::Rg8 | Self::Rg8ui | Self::Rg8i | Self::R16f => 2,
+            Self::Rgb8 => 3,
+            Self::Depth32fStencil8 | Self::Rg32ui | Self::Rg32i | Self::Rgba16ui | Self::Rgba16i
+                | Self::Rgba16f | Self::Rg32f => 8,
+            Self::Rgb32f => 12,
+            Self::Rgba32i | Self::Rgba32ui | Self::Rgba32f => 16,
+            _ => 4,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(u32)]
 pub enum ReadPixelFormat {
@@ -678,7 +1013,7 @@ pub enum FrameBufferTextureTarget {
 }
 
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum CullFaceMode {
     Front = 0x0404,
@@ -686,6 +1021,13 @@ pub enum CullFaceMode {
     FrontAndBack = 0x0408
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum FrontFace {
+    Cw = 0x0900,
+    Ccw = 0x0901,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[repr(u32)]
 pub enum FrameBufferStatus {
@@ -741,7 +1083,9 @@ pub enum GlQueryResult {
 pub enum GlQueryKind {
     AnySamplesPassed = 0x8C2F,
     AnySamplesPassedConservative = 0x8D6A,
-    TransformFeedbackPrimitivesWritten = 0x8C88
+    TransformFeedbackPrimitivesWritten = 0x8C88,
+    //EXT_disjoint_timer_query_webgl2
+    TimeElapsed = 0x88BF,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -1,20 +1,129 @@
-use super::{WebGlCommon, WebGlRenderer};
+use super::{WebGl2Extra, WebGlCommon, WebGlRenderer};
 use crate::errors::{Error, NativeError};
+use js_sys::{Int32Array, Uint32Array};
+use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::AngleInstancedArrays;
-use web_sys::OesVertexArrayObject;
-use web_sys::WebglDrawBuffers;
+use web_sys::{
+    AngleInstancedArrays, ExtBlendMinmax, ExtColorBufferFloat, ExtColorBufferHalfFloat, ExtDisjointTimerQuery,
+    ExtFragDepth, ExtPolygonOffsetClamp, ExtSRgb, ExtShaderTextureLod, ExtTextureFilterAnisotropic, ExtTextureNorm16,
+    OesElementIndexUint, OesStandardDerivatives, OesTextureFloat, OesTextureFloatLinear, OesTextureHalfFloat,
+    OesTextureHalfFloatLinear, OesVertexArrayObject, OvrMultiview2, WebglColorBufferFloat, WebglDebugRendererInfo,
+    WebglDebugShaders, WebglDepthTexture, WebglDrawBuffers, WebglLoseContext, WebglMultiDraw,
+};
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 
+/*
+ * Extensions
+ *
+ * WebGlExtension names every ratified and community approved extension,
+ * plus the draft base vertex / base instance ones that drawing relies on
+ * probe_extensions() says which ones the context has, without enabling any of them
+ *
+ * register_extension() enables one and keeps it around (and re-registers after a context loss)
+ * The register_extension_*() / get_extension_*() pairs do the same with the web-sys type
+ * for the extensions that have functions or constants
+ * Marker extensions (e.g. OES_texture_float_linear) only need registering
+ */
+
+macro_rules! webgl_extensions {
+    ($($variant:ident => $name:literal),* $(,)?) => {
+        #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+        pub enum WebGlExtension {
+            $($variant),*
+        }
+
+        impl WebGlExtension {
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name),*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+webgl_extensions!(
+    AngleInstancedArrays => "ANGLE_instanced_arrays",
+    ExtBlendMinmax => "EXT_blend_minmax",
+    ExtClipControl => "EXT_clip_control",
+    ExtColorBufferFloat => "EXT_color_buffer_float",
+    ExtColorBufferHalfFloat => "EXT_color_buffer_half_float",
+    ExtConservativeDepth => "EXT_conservative_depth",
+    ExtDepthClamp => "EXT_depth_clamp",
+    ExtDisjointTimerQuery => "EXT_disjoint_timer_query",
+    ExtDisjointTimerQueryWebGl2 => "EXT_disjoint_timer_query_webgl2",
+    ExtFloatBlend => "EXT_float_blend",
+    ExtFragDepth => "EXT_frag_depth",
+    ExtPolygonOffsetClamp => "EXT_polygon_offset_clamp",
+    ExtRenderSnorm => "EXT_render_snorm",
+    ExtShaderTextureLod => "EXT_shader_texture_lod",
+    ExtSrgb => "EXT_sRGB",
+    ExtTextureCompressionBptc => "EXT_texture_compression_bptc",
+    ExtTextureCompressionRgtc => "EXT_texture_compression_rgtc",
+    ExtTextureFilterAnisotropic => "EXT_texture_filter_anisotropic",
+    ExtTextureMirrorClampToEdge => "EXT_texture_mirror_clamp_to_edge",
+    ExtTextureNorm16 => "EXT_texture_norm16",
+    KhrParallelShaderCompile => "KHR_parallel_shader_compile",
+    NvShaderNoperspectiveInterpolation => "NV_shader_noperspective_interpolation",
+    OesDrawBuffersIndexed => "OES_draw_buffers_indexed",
+    OesElementIndexUint => "OES_element_index_uint",
+    OesFboRenderMipmap => "OES_fbo_render_mipmap",
+    OesSampleVariables => "OES_sample_variables",
+    OesShaderMultisampleInterpolation => "OES_shader_multisample_interpolation",
+    OesStandardDerivatives => "OES_standard_derivatives",
+    OesTextureFloat => "OES_texture_float",
+    OesTextureFloatLinear => "OES_texture_float_linear",
+    OesTextureHalfFloat => "OES_texture_half_float",
+    OesTextureHalfFloatLinear => "OES_texture_half_float_linear",
+    OesVertexArrayObject => "OES_vertex_array_object",
+    OvrMultiview2 => "OVR_multiview2",
+    WebGlBlendFuncExtended => "WEBGL_blend_func_extended",
+    WebGlClipCullDistance => "WEBGL_clip_cull_distance",
+    WebGlColorBufferFloat => "WEBGL_color_buffer_float",
+    WebGlCompressedTextureAstc => "WEBGL_compressed_texture_astc",
+    WebGlCompressedTextureEtc => "WEBGL_compressed_texture_etc",
+    WebGlCompressedTextureEtc1 => "WEBGL_compressed_texture_etc1",
+    WebGlCompressedTexturePvrtc => "WEBGL_compressed_texture_pvrtc",
+    WebGlCompressedTextureS3tc => "WEBGL_compressed_texture_s3tc",
+    WebGlCompressedTextureS3tcSrgb => "WEBGL_compressed_texture_s3tc_srgb",
+    WebGlDebugRendererInfo => "WEBGL_debug_renderer_info",
+    WebGlDebugShaders => "WEBGL_debug_shaders",
+    WebGlDepthTexture => "WEBGL_depth_texture",
+    WebGlDrawBuffers => "WEBGL_draw_buffers",
+    WebGlDrawInstancedBaseVertexBaseInstance => "WEBGL_draw_instanced_base_vertex_base_instance",
+    WebGlLoseContext => "WEBGL_lose_context",
+    WebGlMultiDraw => "WEBGL_multi_draw",
+    WebGlMultiDrawInstancedBaseVertexBaseInstance => "WEBGL_multi_draw_instanced_base_vertex_base_instance",
+    WebGlPolygonMode => "WEBGL_polygon_mode",
+    WebGlProvokingVertex => "WEBGL_provoking_vertex",
+    WebGlRenderSharedExponent => "WEBGL_render_shared_exponent",
+    WebGlStencilTexturing => "WEBGL_stencil_texturing",
+);
+
 pub trait PartialWebGlExtensions {
     fn awsm_get_extension(&self, name: &str) -> Result<js_sys::Object, Error>;
+    fn awsm_get_supported_extensions(&self) -> Vec<String>;
 }
 
 macro_rules! impl_context {
     ($($type:ty { $($defs:tt)* })+) => {
         $(impl PartialWebGlExtensions for $type {
             fn awsm_get_extension(&self, name:&str) -> Result<js_sys::Object, Error> {
-                self.get_extension(name)?.ok_or(Error::from(NativeError::NoExtension))
+                self.get_extension(name)?.ok_or(Error::from(NativeError::NoExtension(Some(name.to_string()))))
+            }
+            fn awsm_get_supported_extensions(&self) -> Vec<String> {
+                self.get_supported_extensions()
+                    .map(|names| names.iter().filter_map(|name| name.as_string()).collect())
+                    .unwrap_or_default()
             }
             $($defs)*
         })+
@@ -26,6 +135,91 @@ impl_context! {
     WebGl2RenderingContext{}
 }
 
+/// Placeholders until web-sys has these (webgl2 only)
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = WEBGL_draw_instanced_base_vertex_base_instance)]
+    pub type WebglDrawInstancedBaseVertexBaseInstance;
+
+    #[wasm_bindgen(method, js_class = "WEBGL_draw_instanced_base_vertex_base_instance", js_name = drawArraysInstancedBaseInstanceWEBGL)]
+    pub fn draw_arrays_instanced_base_instance_webgl(
+        this: &WebglDrawInstancedBaseVertexBaseInstance,
+        mode: u32,
+        first: i32,
+        count: i32,
+        instance_count: i32,
+        base_instance: u32,
+    );
+
+    #[wasm_bindgen(method, js_class = "WEBGL_draw_instanced_base_vertex_base_instance", js_name = drawElementsInstancedBaseVertexBaseInstanceWEBGL)]
+    pub fn draw_elements_instanced_base_vertex_base_instance_webgl(
+        this: &WebglDrawInstancedBaseVertexBaseInstance,
+        mode: u32,
+        count: i32,
+        type_: u32,
+        offset: i32,
+        instance_count: i32,
+        base_vertex: i32,
+        base_instance: u32,
+    );
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = WEBGL_multi_draw_instanced_base_vertex_base_instance)]
+    pub type WebglMultiDrawInstancedBaseVertexBaseInstance;
+
+    #[wasm_bindgen(method, js_class = "WEBGL_multi_draw_instanced_base_vertex_base_instance", js_name = multiDrawArraysInstancedBaseInstanceWEBGL)]
+    pub fn multi_draw_arrays_instanced_base_instance_webgl(
+        this: &WebglMultiDrawInstancedBaseVertexBaseInstance,
+        mode: u32,
+        firsts: &Int32Array,
+        firsts_offset: u32,
+        counts: &Int32Array,
+        counts_offset: u32,
+        instance_counts: &Int32Array,
+        instance_counts_offset: u32,
+        //GLuint in the spec, unlike the other arrays
+        base_instances: &Uint32Array,
+        base_instances_offset: u32,
+        draw_count: i32,
+    );
+
+    #[wasm_bindgen(method, js_class = "WEBGL_multi_draw_instanced_base_vertex_base_instance", js_name = multiDrawElementsInstancedBaseVertexBaseInstanceWEBGL)]
+    pub fn multi_draw_elements_instanced_base_vertex_base_instance_webgl(
+        this: &WebglMultiDrawInstancedBaseVertexBaseInstance,
+        mode: u32,
+        counts: &Int32Array,
+        counts_offset: u32,
+        type_: u32,
+        offsets: &Int32Array,
+        offsets_offset: u32,
+        instance_counts: &Int32Array,
+        instance_counts_offset: u32,
+        base_vertices: &Int32Array,
+        base_vertices_offset: u32,
+        base_instances: &Uint32Array,
+        base_instances_offset: u32,
+        draw_count: i32,
+    );
+}
+
+//register_extension_$name() and get_extension_$name() for each typed extension
+macro_rules! typed_extensions {
+    ($($ext:ident as $type:ty => $register:ident, $get:ident);* $(;)?) => {
+        $(
+            pub fn $register(&mut self) -> Result<&$type, Error> {
+                self.register_extension(WebGlExtension::$ext.name())
+                    .map(|ext| ext.unchecked_ref::<$type>())
+            }
+            pub fn $get(&self) -> Result<&$type, Error> {
+                self.get_extension(WebGlExtension::$ext.name())
+                    .map(|ext| ext.unchecked_ref::<$type>())
+            }
+        )*
+    };
+}
+
 impl<T: WebGlCommon> WebGlRenderer<T> {
     pub fn register_extension(&mut self, name: &str) -> Result<&js_sys::Object, Error> {
         if self.extension_lookup.get(name).is_none() {
@@ -38,35 +232,97 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     pub(super) fn get_extension(&self, name: &str) -> Result<&js_sys::Object, Error> {
         self.extension_lookup
             .get(name)
-            .ok_or(Error::from(NativeError::NoExtension))
+            .ok_or_else(|| Error::from(NativeError::NoExtension(Some(name.to_string()))))
     }
-}
 
-impl WebGlRenderer<WebGlRenderingContext> {
-    pub fn register_extension_instanced_arrays(&mut self) -> Result<&AngleInstancedArrays, Error> {
-        self.register_extension("ANGLE_instanced_arrays")
-            .map(|ext| ext.unchecked_ref::<AngleInstancedArrays>())
+    //the known extensions the context supports (in WebGlExtension::ALL order)
+    pub fn probe_extensions(&self) -> Vec<WebGlExtension> {
+        let supported = self.gl.awsm_get_supported_extensions();
+
+        WebGlExtension::ALL
+            .iter()
+            .copied()
+            .filter(|ext| supported.iter().any(|name| name == ext.name()))
+            .collect()
     }
-    pub fn get_extension_instanced_arrays(&self) -> Result<&AngleInstancedArrays, Error> {
-        self.get_extension("ANGLE_instanced_arrays")
-            .map(|ext| ext.unchecked_ref::<AngleInstancedArrays>())
+
+    pub fn is_extension_registered(&self, ext: WebGlExtension) -> bool {
+        self.extension_lookup.contains_key(ext.name())
     }
 
-    pub fn register_extension_vertex_array(&mut self) -> Result<&OesVertexArrayObject, Error> {
-        self.register_extension("OES_vertex_array_object")
-            .map(|ext| ext.unchecked_ref::<OesVertexArrayObject>())
+    //available in both webgl1 and webgl2
+    typed_extensions! {
+        WebGlMultiDraw as WebglMultiDraw => register_extension_multi_draw, get_extension_multi_draw;
+        ExtTextureFilterAnisotropic as ExtTextureFilterAnisotropic => register_extension_texture_filter_anisotropic, get_extension_texture_filter_anisotropic;
+        OesTextureFloatLinear as OesTextureFloatLinear => register_extension_texture_float_linear, get_extension_texture_float_linear;
+        ExtColorBufferHalfFloat as ExtColorBufferHalfFloat => register_extension_color_buffer_half_float, get_extension_color_buffer_half_float;
+        ExtPolygonOffsetClamp as ExtPolygonOffsetClamp => register_extension_polygon_offset_clamp, get_extension_polygon_offset_clamp;
+        WebGlLoseContext as WebglLoseContext => register_extension_lose_context, get_extension_lose_context;
+        WebGlDebugRendererInfo as WebglDebugRendererInfo => register_extension_debug_renderer_info, get_extension_debug_renderer_info;
+        WebGlDebugShaders as WebglDebugShaders => register_extension_debug_shaders, get_extension_debug_shaders;
     }
-    pub fn get_extension_vertex_array(&self) -> Result<&OesVertexArrayObject, Error> {
-        self.get_extension("OES_vertex_array_object")
-            .map(|ext| ext.unchecked_ref::<OesVertexArrayObject>())
+}
+
+//core in webgl2
+impl WebGlRenderer<WebGlRenderingContext> {
+    typed_extensions! {
+        AngleInstancedArrays as AngleInstancedArrays => register_extension_instanced_arrays, get_extension_instanced_arrays;
+        OesVertexArrayObject as OesVertexArrayObject => register_extension_vertex_array, get_extension_vertex_array;
+        WebGlDrawBuffers as WebglDrawBuffers => register_extension_draw_buffers, get_extension_draw_buffers;
+        OesStandardDerivatives as OesStandardDerivatives => register_extension_standard_derivatives, get_extension_standard_derivatives;
+        OesElementIndexUint as OesElementIndexUint => register_extension_element_index_uint, get_extension_element_index_uint;
+        OesTextureFloat as OesTextureFloat => register_extension_texture_float, get_extension_texture_float;
+        OesTextureHalfFloat as OesTextureHalfFloat => register_extension_texture_half_float, get_extension_texture_half_float;
+        OesTextureHalfFloatLinear as OesTextureHalfFloatLinear => register_extension_texture_half_float_linear, get_extension_texture_half_float_linear;
+        ExtBlendMinmax as ExtBlendMinmax => register_extension_blend_minmax, get_extension_blend_minmax;
+        ExtFragDepth as ExtFragDepth => register_extension_frag_depth, get_extension_frag_depth;
+        ExtShaderTextureLod as ExtShaderTextureLod => register_extension_shader_texture_lod, get_extension_shader_texture_lod;
+        ExtSrgb as ExtSRgb => register_extension_srgb, get_extension_srgb;
+        ExtDisjointTimerQuery as ExtDisjointTimerQuery => register_extension_disjoint_timer_query, get_extension_disjoint_timer_query;
+        WebGlDepthTexture as WebglDepthTexture => register_extension_depth_texture, get_extension_depth_texture;
+        WebGlColorBufferFloat as WebglColorBufferFloat => register_extension_webgl_color_buffer_float, get_extension_webgl_color_buffer_float;
     }
+}
 
-    pub fn register_extension_draw_buffers(&mut self) -> Result<&WebglDrawBuffers, Error> {
-        self.register_extension("WEBGL_draw_buffers")
-            .map(|ext| ext.unchecked_ref::<WebglDrawBuffers>())
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    typed_extensions! {
+        ExtColorBufferFloat as ExtColorBufferFloat => register_extension_ext_color_buffer_float, get_extension_ext_color_buffer_float;
+        ExtTextureNorm16 as ExtTextureNorm16 => register_extension_texture_norm16, get_extension_texture_norm16;
+        OvrMultiview2 as OvrMultiview2 => register_extension_multiview2, get_extension_multiview2;
+        WebGlDrawInstancedBaseVertexBaseInstance as WebglDrawInstancedBaseVertexBaseInstance => register_extension_draw_base_vertex_base_instance, get_extension_draw_base_vertex_base_instance;
+        WebGlMultiDrawInstancedBaseVertexBaseInstance as WebglMultiDrawInstancedBaseVertexBaseInstance => register_extension_multi_draw_base_vertex_base_instance, get_extension_multi_draw_base_vertex_base_instance;
     }
-    pub fn get_extension_draw_buffers(&self) -> Result<&WebglDrawBuffers, Error> {
-        self.get_extension("WEBGL_draw_buffers")
-            .map(|ext| ext.unchecked_ref::<WebglDrawBuffers>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{CompressedTextureExtension, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_probe_extensions() {
+        for ext in WebGlExtension::ALL {
+            assert_eq!(WebGlExtension::from_name(ext.name()), Some(*ext));
+        }
+        assert_eq!(CompressedTextureExtension::S3tc.name(), "WEBGL_compressed_texture_s3tc");
+
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        renderer.gl.add_extension("WEBGL_lose_context");
+        renderer.gl.add_extension("MOZ_something_else");
+        renderer.gl.add_extension("EXT_color_buffer_float");
+
+        //probing doesn't enable anything
+        assert_eq!(
+            renderer.probe_extensions(),
+            [WebGlExtension::ExtColorBufferFloat, WebGlExtension::WebGlLoseContext]
+        );
+        assert!(!renderer.is_extension_registered(WebGlExtension::ExtColorBufferFloat));
+
+        renderer.register_extension_ext_color_buffer_float().unwrap();
+        assert!(renderer.is_extension_registered(WebGlExtension::ExtColorBufferFloat));
+        assert!(matches!(
+            renderer.register_extension_texture_filter_anisotropic(),
+            Err(Error::Native(NativeError::NoExtension(Some(name)))) if name == "EXT_texture_filter_anisotropic"
+        ));
     }
 }
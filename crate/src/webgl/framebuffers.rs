@@ -1,4 +1,4 @@
-use super::{RENDERBUFFER_TARGET, Id, WebGlCommon, WebGlRenderer, ReadBuffer, FrameBufferTarget, FrameBufferStatus, FrameBufferAttachment, FrameBufferTextureTarget, BufferMask, BlitFilter};
+use super::{RENDERBUFFER_TARGET, TextureId, FramebufferId, RenderbufferId, WebGlCommon, WebGlRenderer, ReadBuffer, FrameBufferTarget, FrameBufferStatus, FrameBufferAttachment, FrameBufferTextureTarget, BufferMask, BlitFilter};
 use crate::errors::{Error, NativeError};
 use crate::data::{TypedData};
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext, WebGlFramebuffer, WebGlTexture, WebGlRenderbuffer};
@@ -149,14 +149,20 @@ impl_context! {
 
 
 impl<T: WebGlCommon> WebGlRenderer<T> {
-    pub fn create_framebuffer(&mut self) -> Result<Id, Error> {
+    pub fn create_framebuffer(&mut self) -> Result<FramebufferId, Error> {
         let framebuffer = self.gl.awsm_create_framebuffer()?;
         let id = self.framebuffer_lookup.insert(framebuffer);
 
         Ok(id)
     }
 
-    pub fn delete_framebuffer(&mut self, id: Id) -> Result<(), Error> {
+    pub fn create_framebuffer_labeled(&mut self, label: &str) -> Result<FramebufferId, Error> {
+        let id = self.create_framebuffer()?;
+        self.set_label(id, label)?;
+        Ok(id)
+    }
+
+    pub fn delete_framebuffer(&mut self, id: FramebufferId) -> Result<(), Error> {
         if Some(id) == self.current_framebuffer_id.get() {
             if let Some(target) = self.current_framebuffer_target.get() {
                 self.gl.awsm_release_framebuffer(target);
@@ -167,8 +173,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
 
         let framebuffer = self
             .framebuffer_lookup
-            .get(id)
-            .ok_or(Error::from(NativeError::MissingFrameBuffer))?;
+            .try_get(id, NativeError::MissingFrameBuffer)?;
 
         self.gl.awsm_delete_framebuffer(&framebuffer);
 
@@ -186,7 +191,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     //only pub within the module - used elsewhere like attributes
     pub(super) fn _bind_framebuffer_nocheck(
         &self,
-        framebuffer_id: Id,
+        framebuffer_id: FramebufferId,
         target: FrameBufferTarget,
     ) -> Result<(), Error> {
         self.current_framebuffer_id.set(Some(framebuffer_id));
@@ -194,20 +199,19 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
 
         let framebuffer = self
             .framebuffer_lookup
-            .get(framebuffer_id)
-            .ok_or(Error::from(NativeError::MissingFrameBuffer))?;
+            .try_get(framebuffer_id, NativeError::MissingFrameBuffer)?;
         self.gl.awsm_bind_framebuffer(target, &framebuffer);
 
         Ok(())
     }
 
     #[cfg(feature = "disable_webgl_opt")]
-    pub fn bind_framebuffer(&self, framebuffer_id: Id, target: FrameBufferTarget) -> Result<(), Error> {
+    pub fn bind_framebuffer(&self, framebuffer_id: FramebufferId, target: FrameBufferTarget) -> Result<(), Error> {
         self._bind_framebuffer_nocheck(framebuffer_id, target)
     }
 
     #[cfg(not(feature = "disable_webgl_opt"))]
-    pub fn bind_framebuffer(&self, framebuffer_id: Id, target: FrameBufferTarget) -> Result<(), Error> {
+    pub fn bind_framebuffer(&self, framebuffer_id: FramebufferId, target: FrameBufferTarget) -> Result<(), Error> {
         if Some(framebuffer_id) != self.current_framebuffer_id.get()
             || Some(target) != self.current_framebuffer_target.get()
         {
@@ -217,7 +221,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
         }
     }
     
-    pub fn assign_framebuffer_texture_2d(&mut self, framebuffer_id: Id, texture_id: Id, target: FrameBufferTarget, attachment: FrameBufferAttachment, texture_target: FrameBufferTextureTarget) -> Result<(), Error> {
+    pub fn assign_framebuffer_texture_2d(&mut self, framebuffer_id: FramebufferId, texture_id: TextureId, target: FrameBufferTarget, attachment: FrameBufferAttachment, texture_target: FrameBufferTextureTarget) -> Result<(), Error> {
         {
             let texture = self.get_texture(texture_id)?;
 
@@ -227,7 +231,7 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
         Ok(())
     }
 
-    pub fn assign_framebuffer_renderbuffer(&self, framebuffer_id: Id, renderbuffer_id: Id, target: FrameBufferTarget, attachment: FrameBufferAttachment) -> Result<(), Error> {
+    pub fn assign_framebuffer_renderbuffer(&self, framebuffer_id: FramebufferId, renderbuffer_id: RenderbufferId, target: FrameBufferTarget, attachment: FrameBufferAttachment) -> Result<(), Error> {
         self.bind_framebuffer(framebuffer_id, target)?;
         let renderbuffer = self.get_renderbuffer(renderbuffer_id)?;
         self.gl.awsm_framebuffer_renderbuffer(target, attachment, renderbuffer);
@@ -244,7 +248,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     pub fn blit_framebuffer(&self, src_x0: u32, src_y0: u32, src_x1: u32, src_y1: u32, dst_x0: u32, dst_y0: u32, dst_x1: u32, dst_y1: u32, mask: BufferMask, filter: BlitFilter) {
         self.gl.awsm_blit_framebuffer(src_x0, src_y0, src_x1, src_y1, dst_x0, dst_y0, dst_x1, dst_y1, mask, filter)
     }
-    pub fn framebuffer_texture_layer(&mut self, target: FrameBufferTarget, attachment: FrameBufferAttachment, texture_id:Id, mipmap_level: u32, layer:u32) -> Result<(), Error> {
+    pub fn framebuffer_texture_layer(&mut self, target: FrameBufferTarget, attachment: FrameBufferAttachment, texture_id: TextureId, mipmap_level: u32, layer:u32) -> Result<(), Error> {
         let texture = self.get_texture(texture_id)?;
         self.gl.awsm_framebuffer_texture_layer(target, attachment, texture, mipmap_level, layer);
 
@@ -1,4 +1,4 @@
-use super::{BlendEquation, BlendFactor, CmpFunction, WebGlCommon, WebGlRenderer};
+use super::{BlendEquation, BlendFactor, CmpFunction, CullFaceMode, FrontFace, WebGlCommon, WebGlRenderer};
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 
 pub trait PartialWebGlFuncs {
@@ -14,6 +14,9 @@ pub trait PartialWebGlFuncs {
     );
     fn awsm_blend_equation(&self, mode: BlendEquation);
     fn awsm_blend_equation_separate(&self, rgb_mode: BlendEquation, alpha_mode: BlendEquation);
+    fn awsm_cull_face(&self, mode: CullFaceMode);
+    fn awsm_front_face(&self, mode: FrontFace);
+    fn awsm_polygon_offset(&self, factor: f32, units: f32);
 }
 
 macro_rules! impl_context {
@@ -37,6 +40,15 @@ macro_rules! impl_context {
             fn awsm_blend_equation_separate(&self, rgb_mode:BlendEquation, alpha_mode:BlendEquation) {
                 self.blend_equation_separate(rgb_mode as u32, alpha_mode as u32);
             }
+            fn awsm_cull_face(&self, mode:CullFaceMode) {
+                self.cull_face(mode as u32);
+            }
+            fn awsm_front_face(&self, mode:FrontFace) {
+                self.front_face(mode as u32);
+            }
+            fn awsm_polygon_offset(&self, factor:f32, units:f32) {
+                self.polygon_offset(factor, units);
+            }
             $($defs)*
         })+
     };
@@ -47,13 +59,19 @@ impl_context! {
     WebGl2RenderingContext{}
 }
 
+#[derive(Clone, PartialEq)]
 pub struct FuncSettings {
     pub depth_func: CmpFunction,
     pub blend_color: (f32, f32, f32, f32),
+    //the separate variants are what's actually set,
+    //the plain ones only follow along when rgb and alpha match
     pub blend_func: (BlendFactor, BlendFactor),
     pub blend_func_separate: (BlendFactor, BlendFactor, BlendFactor, BlendFactor),
     pub blend_equation: BlendEquation,
     pub blend_equation_separate: (BlendEquation, BlendEquation),
+    pub cull_face: CullFaceMode,
+    pub front_face: FrontFace,
+    pub polygon_offset: (f32, f32), //factor, units
 }
 
 impl Default for FuncSettings {
@@ -70,6 +88,9 @@ impl Default for FuncSettings {
             ),
             blend_equation: BlendEquation::Add,
             blend_equation_separate: (BlendEquation::Add, BlendEquation::Add),
+            cull_face: CullFaceMode::Back,
+            front_face: FrontFace::Ccw,
+            polygon_offset: (0.0, 0.0),
         }
     }
 }
@@ -91,11 +112,12 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     }
 
     pub fn set_blend_func(&mut self, sfactor: BlendFactor, dfactor: BlendFactor) {
-        let curr = self.func_settings.blend_func;
+        let curr = self.func_settings.blend_func_separate;
 
-        if curr.0 != sfactor || curr.1 != dfactor {
+        if curr != (sfactor, dfactor, sfactor, dfactor) {
             self.gl.awsm_blend_func(sfactor, dfactor);
             self.func_settings.blend_func = (sfactor, dfactor);
+            self.func_settings.blend_func_separate = (sfactor, dfactor, sfactor, dfactor);
         }
     }
     pub fn set_blend_func_separate(
@@ -111,12 +133,16 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
             self.gl
                 .awsm_blend_func_separate(src_rgb, dest_rgb, src_alpha, dest_alpha);
             self.func_settings.blend_func_separate = (src_rgb, dest_rgb, src_alpha, dest_alpha);
+            if src_rgb == src_alpha && dest_rgb == dest_alpha {
+                self.func_settings.blend_func = (src_rgb, dest_rgb);
+            }
         }
     }
     pub fn set_blend_equation(&mut self, mode: BlendEquation) {
-        if self.func_settings.blend_equation != mode {
+        if self.func_settings.blend_equation_separate != (mode, mode) {
             self.gl.awsm_blend_equation(mode);
             self.func_settings.blend_equation = mode;
+            self.func_settings.blend_equation_separate = (mode, mode);
         }
     }
     pub fn set_blend_equation_separate(
@@ -129,6 +155,61 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
         if curr.0 != rgb_mode || curr.1 != alpha_mode {
             self.gl.awsm_blend_equation_separate(rgb_mode, alpha_mode);
             self.func_settings.blend_equation_separate = (rgb_mode, alpha_mode);
+            if rgb_mode == alpha_mode {
+                self.func_settings.blend_equation = rgb_mode;
+            }
+        }
+    }
+    pub fn set_cull_face(&mut self, mode: CullFaceMode) {
+        if self.func_settings.cull_face != mode {
+            self.gl.awsm_cull_face(mode);
+            self.func_settings.cull_face = mode;
+        }
+    }
+    pub fn set_front_face(&mut self, mode: FrontFace) {
+        if self.func_settings.front_face != mode {
+            self.gl.awsm_front_face(mode);
+            self.func_settings.front_face = mode;
         }
     }
+    pub fn set_polygon_offset(&mut self, factor: f32, units: f32) {
+        let curr = self.func_settings.polygon_offset;
+
+        if curr.0 != factor || curr.1 != units {
+            self.gl.awsm_polygon_offset(factor, units);
+            self.func_settings.polygon_offset = (factor, units);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_funcs_dedupe() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::One)).unwrap();
+
+        //already the defaults
+        renderer.set_cull_face(CullFaceMode::Back);
+        renderer.set_front_face(FrontFace::Ccw);
+        renderer.set_polygon_offset(0.0, 0.0);
+
+        renderer.set_cull_face(CullFaceMode::Front);
+        renderer.set_cull_face(CullFaceMode::Front);
+        renderer.set_front_face(FrontFace::Cw);
+        renderer.set_front_face(FrontFace::Cw);
+        renderer.set_polygon_offset(1.0, 2.0);
+        renderer.set_polygon_offset(1.0, 2.0);
+        //units differ, so it still goes through
+        renderer.set_polygon_offset(1.0, 4.0);
+
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::CullFace(CullFaceMode::Front),
+            GlCommand::FrontFace(FrontFace::Cw),
+            GlCommand::PolygonOffset(1.0, 2.0),
+            GlCommand::PolygonOffset(1.0, 4.0),
+        ]);
+    }
 }
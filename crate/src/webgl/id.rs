@@ -1,2 +1,176 @@
-use beach_map::{DefaultVersion, ID};
+use beach_map::{BeachMap, DefaultVersion, ID};
+use crate::errors::{Error, NativeError};
+use serde::Serialize;
+use std::collections::VecDeque;
+
 pub type Id = ID<DefaultVersion>;
+
+/*
+ * Each kind of resource gets its own id type
+ * so that passing e.g. a texture to bind_buffer() is a compile error
+ * rather than a MissingBuffer (or a valid slot in the wrong map)
+ *
+ * The raw Id is still reachable via id() for interop (e.g. storing it elsewhere)
+ * but only the renderer makes them - there's no going back from a raw Id (or deserializing one)
+ */
+
+pub(crate) trait ResourceIdType: Copy {
+    fn from_id(id: Id) -> Self;
+    fn id(&self) -> Id;
+}
+
+macro_rules! resource_ids {
+    ($($name:ident),*) => {
+        $(
+            #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+            #[serde(transparent)]
+            pub struct $name(Id);
+
+            impl ResourceIdType for $name {
+                fn from_id(id: Id) -> Self {
+                    Self(id)
+                }
+                fn id(&self) -> Id {
+                    self.0
+                }
+            }
+
+            impl $name {
+                pub fn id(&self) -> Id {
+                    self.0
+                }
+            }
+        )*
+    };
+}
+
+resource_ids!(
+    BufferId,
+    TextureId,
+    ProgramId,
+    ShaderId,
+    VaoId,
+    FramebufferId,
+    RenderbufferId,
+    SamplerId
+);
+
+//BeachMap keyed by one kind of id, with an optional debug label per entry
+//
+//labels of removed entries are kept for a while (the last RETIRED_LABELS of them)
+//so that using a stale id can still name what it was
+const RETIRED_LABELS: usize = 256;
+
+struct Slot<T> {
+    value: T,
+    label: Option<String>,
+}
+
+pub(super) struct IdMap<K, T> {
+    map: BeachMap<DefaultVersion, Slot<T>>,
+    retired_labels: VecDeque<(K, String)>,
+}
+
+impl<K, T> Default for IdMap<K, T> {
+    fn default() -> Self {
+        Self {
+            map: BeachMap::default(),
+            retired_labels: VecDeque::new(),
+        }
+    }
+}
+
+impl<K: ResourceIdType + PartialEq, T> IdMap<K, T> {
+    pub fn insert(&mut self, value: T) -> K {
+        K::from_id(self.map.insert(Slot { value, label: None }))
+    }
+    pub fn get(&self, id: K) -> Option<&T> {
+        self.map.get(id.id()).map(|slot| &slot.value)
+    }
+    pub fn get_mut(&mut self, id: K) -> Option<&mut T> {
+        self.map.get_mut(id.id()).map(|slot| &mut slot.value)
+    }
+    pub fn contains(&self, id: K) -> bool {
+        self.map.contains(id.id())
+    }
+    pub fn remove(&mut self, id: K) -> Option<T> {
+        let slot = self.map.remove(id.id())?;
+        if let Some(label) = slot.label {
+            if self.retired_labels.len() == RETIRED_LABELS {
+                self.retired_labels.pop_front();
+            }
+            self.retired_labels.push_back((id, label));
+        }
+        Some(slot.value)
+    }
+
+    //missing is the error variant, it gets the label if there is one
+    pub fn try_get(&self, id: K, missing: fn(Option<String>) -> NativeError) -> Result<&T, Error> {
+        self.get(id).ok_or_else(|| self.missing(id, missing))
+    }
+    pub fn try_get_mut(&mut self, id: K, missing: fn(Option<String>) -> NativeError) -> Result<&mut T, Error> {
+        self.try_get_mut_labeled(id, missing).map(|(value, _)| value)
+    }
+    //along with the label, for errors about the value itself
+    pub fn try_get_mut_labeled(&mut self, id: K, missing: fn(Option<String>) -> NativeError) -> Result<(&mut T, Option<&str>), Error> {
+        if !self.contains(id) {
+            return Err(self.missing(id, missing));
+        }
+        let slot = self.map.get_mut(id.id()).unwrap();
+        Ok((&mut slot.value, slot.label.as_deref()))
+    }
+    pub fn try_remove(&mut self, id: K, missing: fn(Option<String>) -> NativeError) -> Result<T, Error> {
+        if !self.contains(id) {
+            return Err(self.missing(id, missing));
+        }
+        Ok(self.remove(id).unwrap())
+    }
+    fn missing(&self, id: K, missing: fn(Option<String>) -> NativeError) -> Error {
+        Error::from(missing(self.label(id).map(String::from)))
+    }
+
+    pub fn label(&self, id: K) -> Option<&str> {
+        match self.map.get(id.id()) {
+            Some(slot) => slot.label.as_deref(),
+            None => self
+                .retired_labels
+                .iter()
+                .rev()
+                .find(|(retired_id, _)| *retired_id == id)
+                .map(|(_, label)| label.as_str()),
+        }
+    }
+    pub fn try_set_label(&mut self, id: K, label: Option<String>, missing: fn(Option<String>) -> NativeError) -> Result<(), Error> {
+        if !self.contains(id) {
+            return Err(self.missing(id, missing));
+        }
+        self.map.get_mut(id.id()).unwrap().label = label;
+        Ok(())
+    }
+    //fills in the label of an error about this entry
+    pub fn labeled(&self, id: K, err: NativeError) -> Error {
+        Error::from(err.with_label(self.label(id)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.map.iter().map(|slot| &slot.value)
+    }
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.map.iter_mut().map(|slot| &mut slot.value)
+    }
+    pub fn iter_with_id(&self) -> impl Iterator<Item = (K, &T)> {
+        self.map.iter_with_id().map(|(id, slot)| (K::from_id(id), &slot.value))
+    }
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.map.drain().map(|slot| slot.value)
+    }
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+}
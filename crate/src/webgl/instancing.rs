@@ -1,4 +1,5 @@
-use super::{BeginMode, DataType, WebGlRenderer};
+use super::drawing::check_draw_counts;
+use super::{BeginMode, DataType, WebGl2Extra, WebGlCommon, WebGlRenderer};
 use crate::errors::Error;
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 
@@ -39,6 +40,30 @@ impl WebGlRenderer<WebGlRenderingContext> {
         );
         Ok(())
     }
+
+    //the extension needs ANGLE_instanced_arrays for these too
+    pub fn multi_draw_arrays_instanced(
+        &self,
+        mode: BeginMode,
+        firsts: &[u32],
+        counts: &[u32],
+        instance_counts: &[u32],
+    ) -> Result<(), Error> {
+        self.get_extension_instanced_arrays()?;
+        self._multi_draw_arrays_instanced(mode, firsts, counts, instance_counts, Self::draw_arrays_instanced)
+    }
+
+    pub fn multi_draw_elements_instanced(
+        &self,
+        mode: BeginMode,
+        counts: &[u32],
+        data_type: DataType,
+        offsets: &[u32],
+        instance_counts: &[u32],
+    ) -> Result<(), Error> {
+        self.get_extension_instanced_arrays()?;
+        self._multi_draw_elements_instanced(mode, counts, data_type, offsets, instance_counts, Self::draw_elements_instanced)
+    }
 }
 
 impl WebGlRenderer<WebGl2RenderingContext> {
@@ -76,4 +101,227 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         );
         Ok(())
     }
+
+    pub fn multi_draw_arrays_instanced(
+        &self,
+        mode: BeginMode,
+        firsts: &[u32],
+        counts: &[u32],
+        instance_counts: &[u32],
+    ) -> Result<(), Error> {
+        self._multi_draw_arrays_instanced(mode, firsts, counts, instance_counts, Self::draw_arrays_instanced)
+    }
+
+    pub fn multi_draw_elements_instanced(
+        &self,
+        mode: BeginMode,
+        counts: &[u32],
+        data_type: DataType,
+        offsets: &[u32],
+        instance_counts: &[u32],
+    ) -> Result<(), Error> {
+        self._multi_draw_elements_instanced(mode, counts, data_type, offsets, instance_counts, Self::draw_elements_instanced)
+    }
+}
+
+/*
+ * Base vertex / base instance (webgl2 only)
+ *
+ * For many meshes packed into one vertex/index buffer, without rebasing indices on the cpu
+ * supports_base_vertex_base_instance() registers the extensions,
+ * without them the draws are a NoExtension error
+ *
+ * The multi draws go one at a time if only the single draw extension is there
+ */
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    pub fn supports_base_vertex_base_instance(&mut self) -> bool {
+        let _ = self.register_extension_multi_draw_base_vertex_base_instance();
+        self.register_extension_draw_base_vertex_base_instance().is_ok()
+    }
+
+    pub fn draw_arrays_instanced_base_instance(
+        &self,
+        mode: BeginMode,
+        first: u32,
+        count: u32,
+        instance_count: u32,
+        base_instance: u32,
+    ) -> Result<(), Error> {
+        let ext = self.get_extension_draw_base_vertex_base_instance()?;
+        self.gl
+            .awsm_draw_arrays_instanced_base_instance(ext, mode, first, count, instance_count, base_instance);
+        Ok(())
+    }
+
+    pub fn draw_elements_instanced_base_vertex_base_instance(
+        &self,
+        mode: BeginMode,
+        count: u32,
+        data_type: DataType,
+        offset: u32,
+        instance_count: u32,
+        base_vertex: i32,
+        base_instance: u32,
+    ) -> Result<(), Error> {
+        let ext = self.get_extension_draw_base_vertex_base_instance()?;
+        self.gl.awsm_draw_elements_instanced_base_vertex_base_instance(
+            ext,
+            mode,
+            count,
+            data_type,
+            offset,
+            instance_count,
+            base_vertex,
+            base_instance,
+        );
+        Ok(())
+    }
+
+    pub fn multi_draw_arrays_instanced_base_instance(
+        &self,
+        mode: BeginMode,
+        firsts: &[u32],
+        counts: &[u32],
+        instance_counts: &[u32],
+        base_instances: &[u32],
+    ) -> Result<(), Error> {
+        check_draw_counts(&[firsts.len(), counts.len(), instance_counts.len(), base_instances.len()])?;
+
+        match self.get_extension_multi_draw_base_vertex_base_instance() {
+            Ok(ext) => self.gl.awsm_multi_draw_arrays_instanced_base_instance(
+                ext,
+                mode,
+                firsts,
+                counts,
+                instance_counts,
+                base_instances,
+            ),
+            Err(_) => {
+                for i in 0..firsts.len() {
+                    self.draw_arrays_instanced_base_instance(mode, firsts[i], counts[i], instance_counts[i], base_instances[i])?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn multi_draw_elements_instanced_base_vertex_base_instance(
+        &self,
+        mode: BeginMode,
+        counts: &[u32],
+        data_type: DataType,
+        offsets: &[u32],
+        instance_counts: &[u32],
+        base_vertices: &[i32],
+        base_instances: &[u32],
+    ) -> Result<(), Error> {
+        check_draw_counts(&[
+            counts.len(),
+            offsets.len(),
+            instance_counts.len(),
+            base_vertices.len(),
+            base_instances.len(),
+        ])?;
+
+        match self.get_extension_multi_draw_base_vertex_base_instance() {
+            Ok(ext) => self.gl.awsm_multi_draw_elements_instanced_base_vertex_base_instance(
+                ext,
+                mode,
+                counts,
+                data_type,
+                offsets,
+                instance_counts,
+                base_vertices,
+                base_instances,
+            ),
+            Err(_) => {
+                for i in 0..counts.len() {
+                    self.draw_elements_instanced_base_vertex_base_instance(
+                        mode,
+                        counts[i],
+                        data_type,
+                        offsets[i],
+                        instance_counts[i],
+                        base_vertices[i],
+                        base_instances[i],
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::NativeError;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_base_vertex_base_instance() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        assert!(!renderer.supports_base_vertex_base_instance());
+        assert!(matches!(
+            renderer.draw_arrays_instanced_base_instance(BeginMode::Triangles, 0, 3, 1, 0),
+            Err(Error::Native(NativeError::NoExtension(Some(name)))) if name == "WEBGL_draw_instanced_base_vertex_base_instance"
+        ));
+        assert!(matches!(
+            renderer.multi_draw_arrays_instanced_base_instance(BeginMode::Triangles, &[0], &[3], &[1], &[0]),
+            Err(Error::Native(NativeError::NoExtension(Some(name)))) if name == "WEBGL_draw_instanced_base_vertex_base_instance"
+        ));
+
+        //only the single draw extension, multi draws go one at a time
+        renderer.gl.add_extension("WEBGL_draw_instanced_base_vertex_base_instance");
+        assert!(renderer.supports_base_vertex_base_instance());
+        renderer.gl.clear_commands();
+        renderer
+            .multi_draw_elements_instanced_base_vertex_base_instance(
+                BeginMode::Triangles,
+                &[6, 3],
+                DataType::UnsignedShort,
+                &[0, 12],
+                &[1, 4],
+                &[0, 100],
+                &[0, 1],
+            )
+            .unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::DrawElementsInstancedBaseVertexBaseInstance {
+                mode: BeginMode::Triangles,
+                count: 6,
+                data_type: DataType::UnsignedShort,
+                offset: 0,
+                instance_count: 1,
+                base_vertex: 0,
+                base_instance: 0,
+            },
+            GlCommand::DrawElementsInstancedBaseVertexBaseInstance {
+                mode: BeginMode::Triangles,
+                count: 3,
+                data_type: DataType::UnsignedShort,
+                offset: 12,
+                instance_count: 4,
+                base_vertex: 100,
+                base_instance: 1,
+            },
+        ]);
+
+        renderer.gl.add_extension("WEBGL_multi_draw_instanced_base_vertex_base_instance");
+        assert!(renderer.supports_base_vertex_base_instance());
+        renderer.gl.clear_commands();
+        renderer
+            .multi_draw_arrays_instanced_base_instance(BeginMode::Triangles, &[0, 3], &[3, 3], &[1, 1], &[0, 1])
+            .unwrap();
+        assert_eq!(renderer.gl.take_commands(), [GlCommand::MultiDrawArraysInstancedBaseInstance {
+            mode: BeginMode::Triangles,
+            draw_count: 2,
+        }]);
+
+        assert!(matches!(
+            renderer.multi_draw_arrays_instanced_base_instance(BeginMode::Triangles, &[0, 3], &[3, 3], &[1], &[0, 1]),
+            Err(Error::Native(NativeError::MultiDrawLength))
+        ));
+    }
 }
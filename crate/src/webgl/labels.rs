@@ -0,0 +1,97 @@
+use super::{ResourceId, WebGlCommon, WebGlRenderer};
+use crate::errors::{Error, NativeError};
+
+/*
+ * Debug labels
+ *
+ * Any resource can have a label, either from create_*_labeled() or set_label()
+ * Errors about a labeled resource include it (e.g. "couldn't get texture [shadow_map]")
+ * and so does resource_report()
+ *
+ * Labels outlive their resource for a while (see IdMap),
+ * since a stale id is exactly when the label is most useful
+ */
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn set_label<I: Into<ResourceId>>(&mut self, id: I, label: &str) -> Result<(), Error> {
+        self._set_label(id.into(), Some(label.to_string()))
+    }
+
+    pub fn clear_label<I: Into<ResourceId>>(&mut self, id: I) -> Result<(), Error> {
+        self._set_label(id.into(), None)
+    }
+
+    fn _set_label(&mut self, id: ResourceId, label: Option<String>) -> Result<(), Error> {
+        match id {
+            ResourceId::Buffer(id) => self.buffer_lookup.try_set_label(id, label, NativeError::MissingBuffer),
+            ResourceId::Texture(id) => self.texture_lookup.try_set_label(id, label, NativeError::MissingTexture),
+            ResourceId::Program(id) => self.program_lookup.try_set_label(id, label, NativeError::MissingShaderProgram),
+            ResourceId::Shader(id) => self.shader_lookup.try_set_label(id, label, NativeError::MissingShader),
+            ResourceId::Vao(id) => self.vao_lookup.try_set_label(id, label, NativeError::VertexArrayMissing),
+            ResourceId::Framebuffer(id) => self.framebuffer_lookup.try_set_label(id, label, NativeError::MissingFrameBuffer),
+            ResourceId::Renderbuffer(id) => self.renderbuffer_lookup.try_set_label(id, label, NativeError::MissingRenderBuffer),
+            ResourceId::Sampler(id) => self.sampler_lookup.try_set_label(id, label, NativeError::SamplerMissing),
+        }
+    }
+
+    pub fn label<I: Into<ResourceId>>(&self, id: I) -> Option<&str> {
+        match id.into() {
+            ResourceId::Buffer(id) => self.buffer_lookup.label(id),
+            ResourceId::Texture(id) => self.texture_lookup.label(id),
+            ResourceId::Program(id) => self.program_lookup.label(id),
+            ResourceId::Shader(id) => self.shader_lookup.label(id),
+            ResourceId::Vao(id) => self.vao_lookup.label(id),
+            ResourceId::Framebuffer(id) => self.framebuffer_lookup.label(id),
+            ResourceId::Renderbuffer(id) => self.renderbuffer_lookup.label(id),
+            ResourceId::Sampler(id) => self.sampler_lookup.label(id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{BufferTarget, MockContext, PixelInternalFormat, TextureTarget, WebGlVersion};
+
+    #[test]
+    fn test_labels() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        let texture = renderer.create_texture_labeled("shadow_map").unwrap();
+        let buffer = renderer.create_buffer().unwrap();
+        renderer.set_label(buffer, "tiles").unwrap();
+        assert_eq!(renderer.label(texture), Some("shadow_map"));
+        assert_eq!(renderer.label(buffer), Some("tiles"));
+
+        match renderer.get_texture_storage(texture) {
+            Err(Error::Native(err)) => assert_eq!(
+                err.to_string(),
+                "texture target must be known (call assign before activate) [shadow_map]"
+            ),
+            _ => panic!("expected NoTextureTarget"),
+        }
+
+        //the label is still known for the stale id
+        renderer.delete_buffer(buffer).unwrap();
+        assert_eq!(renderer.label(buffer), Some("tiles"));
+        assert!(matches!(
+            renderer.bind_buffer(buffer, BufferTarget::ArrayBuffer),
+            Err(Error::Native(NativeError::MissingBuffer(Some(label)))) if label == "tiles"
+        ));
+        assert!(matches!(renderer.set_label(buffer, "gone"), Err(Error::Native(NativeError::MissingBuffer(_)))));
+
+        renderer.clear_label(texture).unwrap();
+        assert!(matches!(
+            renderer.get_texture_storage(texture),
+            Err(Error::Native(NativeError::NoTextureTarget(None)))
+        ));
+
+        renderer.set_label(texture, "lut").unwrap();
+        renderer
+            .allocate_texture_storage(texture, TextureTarget::Texture2d, 1, PixelInternalFormat::Rgba8, 4, 4, 1)
+            .unwrap();
+        let report = renderer.resource_report();
+        assert_eq!(report.resources.len(), 1);
+        assert_eq!(report.resources[0].label.as_deref(), Some("lut"));
+    }
+}
@@ -0,0 +1,194 @@
+use super::{ProgramId, ShaderId, WebGl2Extra, WebGlCommon, WebGlRenderer};
+use crate::errors::{Error, NativeError};
+use web_sys::WebGlRenderingContext;
+
+/*
+ * Deleting shaders and programs, and everything at once
+ *
+ * The other resources are deleted next to where they're created (buffers.rs, textures.rs, etc.)
+ * All of them free the lookup slot and forget any cached binding,
+ * so deleting the same id twice is a Missing* error
+ *
+ * Shaders can't be deleted while a program still uses them,
+ * since they're needed to relink after a context loss
+ */
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn delete_shader(&mut self, shader_id: ShaderId) -> Result<(), Error> {
+        if !self.shader_lookup.contains(shader_id) {
+            return Err(self.shader_lookup.labeled(shader_id, NativeError::MissingShader(None)));
+        }
+
+        if self
+            .program_lookup
+            .iter()
+            .any(|info| info.shader_ids.contains(&shader_id))
+        {
+            return Err(self.shader_lookup.labeled(shader_id, NativeError::ShaderInUse(None)));
+        }
+
+        if let Some(info) = self.shader_lookup.remove(shader_id) {
+            self.gl.awsm_delete_shader(&info.shader);
+        }
+
+        Ok(())
+    }
+
+    pub fn delete_program(&mut self, program_id: ProgramId) -> Result<(), Error> {
+        let info = self
+            .program_lookup
+            .try_remove(program_id, NativeError::MissingShaderProgram)?;
+
+        if Some(program_id) == self.current_program_id {
+            self.current_program_id = None;
+        }
+
+        self.program_variant_lookup.retain(|_, id| *id != program_id);
+
+        self.gl.awsm_delete_program(&info.program);
+
+        Ok(())
+    }
+
+    fn _delete_all<F>(&mut self, delete_version_specific: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        delete_version_specific(self);
+
+        self.current_program_id = None;
+        self.program_variant_lookup.clear();
+        for info in self.program_lookup.drain() {
+            self.gl.awsm_delete_program(&info.program);
+        }
+        for info in self.shader_lookup.drain() {
+            self.gl.awsm_delete_shader(&info.shader);
+        }
+
+        self.current_buffer_id.set(None);
+        self.current_buffer_target.set(None);
+        self.current_buffer_index.set(None);
+        for info in self.buffer_lookup.drain() {
+            self.gl.awsm_delete_buffer(&info.buffer);
+        }
+
+        for info in self.texture_lookup.drain() {
+            self.gl.awsm_delete_texture(&info.texture);
+        }
+
+        self.current_renderbuffer_id.set(None);
+        for info in self.renderbuffer_lookup.drain() {
+            self.gl.awsm_delete_renderbuffer(&info.renderbuffer);
+        }
+
+        self.current_framebuffer_id.set(None);
+        self.current_framebuffer_target.set(None);
+        for framebuffer in self.framebuffer_lookup.drain() {
+            self.gl.awsm_delete_framebuffer(&framebuffer);
+        }
+
+        //snapshots, pending deletions and restore hooks refer to ids that are gone now
+        self.state_stack.clear();
+        self.deletion_queue.borrow_mut().clear();
+        self.context_restore_hooks.clear();
+    }
+}
+
+impl WebGlRenderer<WebGlRenderingContext> {
+    pub fn delete_all(&mut self) {
+        self._delete_all(|renderer| {
+            renderer._delete_all_vertex_arrays();
+        })
+    }
+}
+
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    pub fn delete_all(&mut self) {
+        self._delete_all(|renderer| {
+            renderer._delete_all_vertex_arrays();
+
+            renderer.disable_gpu_profiler();
+
+            for (unit, current) in renderer.current_sampler_ids.iter().enumerate() {
+                if current.get().is_some() {
+                    renderer.gl.awsm_bind_sampler(unit as u32, None);
+                    current.set(None);
+                }
+            }
+            for info in renderer.sampler_lookup.drain() {
+                renderer.gl.awsm_delete_sampler(&info.sampler);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{MockContext, MockObjectKind, SamplerOptions, ShaderType, WebGlVersion};
+
+    #[test]
+    fn test_delete_resources() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        let program = renderer.compile_program(&[vertex, fragment]).unwrap();
+
+        assert!(matches!(renderer.delete_shader(vertex), Err(Error::Native(NativeError::ShaderInUse(None)))));
+        renderer.delete_program(program).unwrap();
+        assert_eq!(renderer.current_program_id, None);
+        assert!(matches!(renderer.delete_program(program), Err(Error::Native(NativeError::MissingShaderProgram(None)))));
+        renderer.delete_shader(vertex).unwrap();
+        assert!(matches!(renderer.delete_shader(vertex), Err(Error::Native(NativeError::MissingShader(None)))));
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Program), 0);
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Shader), 1);
+
+        let buffer = renderer.create_buffer().unwrap();
+        renderer.bind_buffer(buffer, crate::webgl::BufferTarget::ArrayBuffer).unwrap();
+        renderer.delete_buffer(buffer).unwrap();
+        assert_eq!(renderer.current_buffer_id.get(), None);
+        assert!(matches!(renderer.delete_buffer(buffer), Err(Error::Native(NativeError::MissingBuffer(None)))));
+
+        let vao = renderer.create_vertex_array().unwrap();
+        renderer.activate_vertex_array(vao).unwrap();
+        renderer.delete_vertex_array(vao).unwrap();
+        assert_eq!(renderer.current_vao_id.get(), None);
+        assert!(matches!(renderer.delete_vertex_array(vao), Err(Error::Native(NativeError::VertexArrayMissing(None)))));
+        assert_eq!(renderer.vao_lookup.len(), 0);
+
+        //everything else goes with delete_all
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        renderer.compile_program(&[vertex, fragment]).unwrap();
+        renderer.create_buffer().unwrap();
+        renderer.create_texture().unwrap();
+        renderer.create_vertex_array().unwrap();
+        renderer.create_sampler(&SamplerOptions::default()).unwrap();
+        renderer.create_framebuffer().unwrap();
+        renderer.create_renderbuffer().unwrap();
+        renderer.push_state();
+        let buffer = renderer.create_buffer().unwrap();
+        drop(renderer.own(buffer));
+        assert_eq!(renderer.pending_deletions(), 1);
+        renderer.add_context_restore_hook(|_| Ok(()));
+
+        renderer.delete_all();
+        for kind in [
+            MockObjectKind::Program,
+            MockObjectKind::Shader,
+            MockObjectKind::Buffer,
+            MockObjectKind::Texture,
+            MockObjectKind::VertexArray,
+            MockObjectKind::Sampler,
+            MockObjectKind::FrameBuffer,
+            MockObjectKind::RenderBuffer,
+        ] {
+            assert_eq!(renderer.gl.objects_alive(kind), 0, "{:?}", kind);
+        }
+        assert_eq!(renderer.current_program_id, None);
+        assert!(renderer.state_stack.is_empty());
+        assert_eq!(renderer.pending_deletions(), 0);
+        assert!(renderer.context_restore_hooks.is_empty());
+        assert!(renderer.program_lookup.is_empty() && renderer.shader_lookup.is_empty());
+    }
+}
@@ -4,6 +4,9 @@ use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 pub trait PartialWebGlMisc {
     fn awsm_depth_mask(&self, flag: bool);
     fn awsm_clear_color(&self, r: f32, g: f32, b: f32, a: f32);
+    fn awsm_color_mask(&self, r: bool, g: bool, b: bool, a: bool);
+    fn awsm_line_width(&self, width: f32);
+    fn awsm_sample_coverage(&self, value: f32, invert: bool);
 }
 
 macro_rules! impl_context {
@@ -15,14 +18,28 @@ macro_rules! impl_context {
             fn awsm_clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
                 self.clear_color(r, g, b, a);
             }
+            fn awsm_color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+                self.color_mask(r, g, b, a);
+            }
+            fn awsm_line_width(&self, width: f32) {
+                self.line_width(width);
+            }
+            fn awsm_sample_coverage(&self, value: f32, invert: bool) {
+                self.sample_coverage(value, invert);
+            }
             $($defs)*
         })+
     };
 }
 
+#[derive(Clone, PartialEq)]
 pub struct MiscSettings {
     pub depth_mask: bool,
     pub clear_color: (f32, f32, f32, f32),
+    pub color_mask: (bool, bool, bool, bool),
+    //most browsers only support 1.0
+    pub line_width: f32,
+    pub sample_coverage: (f32, bool), //value, invert
 }
 
 impl Default for MiscSettings {
@@ -30,6 +47,9 @@ impl Default for MiscSettings {
         Self {
             depth_mask: true,
             clear_color: (0.0, 0.0, 0.0, 0.0),
+            color_mask: (true, true, true, true),
+            line_width: 1.0,
+            sample_coverage: (1.0, false),
         }
     }
 }
@@ -55,4 +75,59 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
             self.misc_settings.clear_color = (r, g, b, a);
         }
     }
+
+    pub fn set_color_mask(&mut self, r: bool, g: bool, b: bool, a: bool) {
+        if self.misc_settings.color_mask != (r, g, b, a) {
+            self.gl.awsm_color_mask(r, g, b, a);
+            self.misc_settings.color_mask = (r, g, b, a);
+        }
+    }
+
+    pub fn set_line_width(&mut self, width: f32) {
+        if self.misc_settings.line_width != width {
+            self.gl.awsm_line_width(width);
+            self.misc_settings.line_width = width;
+        }
+    }
+
+    pub fn set_sample_coverage(&mut self, value: f32, invert: bool) {
+        let curr = self.misc_settings.sample_coverage;
+
+        if curr.0 != value || curr.1 != invert {
+            self.gl.awsm_sample_coverage(value, invert);
+            self.misc_settings.sample_coverage = (value, invert);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_misc_dedupe() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::One)).unwrap();
+
+        //already the defaults
+        renderer.set_color_mask(true, true, true, true);
+        renderer.set_line_width(1.0);
+        renderer.set_sample_coverage(1.0, false);
+
+        renderer.set_color_mask(true, true, true, false);
+        renderer.set_color_mask(true, true, true, false);
+        renderer.set_line_width(2.0);
+        renderer.set_line_width(2.0);
+        renderer.set_sample_coverage(0.5, false);
+        renderer.set_sample_coverage(0.5, false);
+        //invert differs, so it still goes through
+        renderer.set_sample_coverage(0.5, true);
+
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::ColorMask(true, true, true, false),
+            GlCommand::LineWidth(2.0),
+            GlCommand::SampleCoverage(0.5, false),
+            GlCommand::SampleCoverage(0.5, true),
+        ]);
+    }
 }
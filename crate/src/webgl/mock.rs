@@ -0,0 +1,1433 @@
+use super::attributes::{AttributeOptions, PartialWebGlAttribute};
+use super::buffers::PartialWebGlBuffer;
+use super::context::{PartialWebGlCanvas, PartialWebGlVersion, WebGl2Extra, WebGlCommon};
+use super::context_loss::PartialWebGlContextLoss;
+use super::drawing::{PartialWebGl2Drawing, PartialWebGlDrawing};
+use super::extensions::PartialWebGlExtensions;
+use super::framebuffers::{PartialWebGl2FrameBuffer, PartialWebGlFrameBuffer};
+use super::funcs::PartialWebGlFuncs;
+use super::misc::PartialWebGlMisc;
+use super::query::{PartialWebGl2Query, PartialWebGlGetParameter};
+use super::reflection::{ActiveInfo, PartialWebGl2Reflection, PartialWebGlReflection};
+use super::read::{PartialWebGl2ReadPixels, PartialWebGlReadPixels};
+use super::renderbuffers::{PartialWebGl2RenderBuffer, PartialWebGlRenderBuffer};
+use super::samplers::PartialWebGl2Sampler;
+use super::shader::PartialWebGlShaders;
+use super::stencil::PartialWebGlStencil;
+use super::textures::{
+    get_cube_face_u32, get_texture_options_from_simple, is_power_of_2, PartialWebGl2Textures, PartialWebGlTextures,
+    SimpleTextureOptions, TextureOptions, TextureSubOptions, WebGlTextureSource,
+};
+use super::toggles::PartialWebGlToggle;
+use super::uniforms::{PartialWebGl2Uniforms, PartialWebGlUniforms, UniformSlice, UniformType, UniformUploadImpl, UniformUploadImpl2};
+use super::vertex_arrays::PartialWebGl2VertexArray;
+use super::viewport::PartialWebGlViewport;
+use super::{
+    BeginMode, BlendEquation, BlendFactor, BlitFilter, Buffer, BufferMask, BufferTarget,
+    BufferUsage, CmpFunction, CullFaceMode, DataType, FrameBufferAttachment, FrameBufferTarget, FrontFace,
+    FrameBufferTextureTarget, GlParameter, GlQueryKind, GlToggle, PixelDataFormat, PixelInternalFormat,
+    ProgramQuery, ReadBuffer, ReadPixelDataType, ReadPixelFormat, RenderBufferFormat,
+    ShaderQuery, ShaderType, StencilOp, TextureMagFilter, TextureParameterName, TextureMinFilter, TextureTarget, TextureWrapMode,
+    TextureWrapTarget, UniformBlockActiveQuery, UniformBlockQuery, UniformDataType, WebGlSpecific, WebGlVersion,
+    WebglDrawInstancedBaseVertexBaseInstance, WebglMultiDrawInstancedBaseVertexBaseInstance,
+};
+use crate::errors::{Error, NativeError};
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    HtmlCanvasElement, WebGlActiveInfo, WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlQuery,
+    WebGlRenderbuffer, WebGlSampler, WebGlShader, WebGlTexture, WebGlUniformLocation, WebGlVertexArrayObject, WebglMultiDraw,
+};
+
+/*
+ * A pure-Rust stand-in for the browser context
+ * Every call is logged into a command list and objects are simulated
+ * so that WebGlRenderer<MockContext> can run under plain `cargo test`
+ *
+ * The handles it gives out (WebGlBuffer, WebGlTexture, etc.) are all `undefined`
+ * under the hood. That keeps them safe to create and drop on native targets,
+ * but they can't be told apart or cloned - so the log records *what* was called,
+ * and the renderer's own cached ids are what tell you *which* object it was
+ *
+ * Uniform locations are the exception: the renderer clones them into its cache,
+ * which would panic outside of wasm, so the mock reports them as missing
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GlCommand {
+    //buffers
+    CreateBuffer,
+    DeleteBuffer,
+    BindBuffer(BufferTarget),
+    ReleaseBuffer(BufferTarget),
+    BufferData { target: BufferTarget, usage: BufferUsage, byte_len: usize },
+    BufferSubData { target: BufferTarget, dest_byte_offset: u32, src_offset: u32, length: u32 },
+
+    //attributes
+    GetAttributeLocation(String),
+    ActivateAttribute { loc: u32, size: u8, data_type: DataType, normalized: bool, stride: u8, offset: u64, is_int_array: bool },
+
+    //drawing
+    Clear(u32),
+    Scissor(i32, i32, u32, u32),
+    DrawArrays { mode: BeginMode, first: u32, count: u32 },
+    DrawElements { mode: BeginMode, count: u32, data_type: DataType, offset: u32 },
+    MultiDrawArrays { mode: BeginMode, draw_count: usize },
+    MultiDrawElements { mode: BeginMode, data_type: DataType, draw_count: usize },
+    MultiDrawArraysInstanced { mode: BeginMode, draw_count: usize },
+    MultiDrawElementsInstanced { mode: BeginMode, data_type: DataType, draw_count: usize },
+    DrawArraysInstancedBaseInstance { mode: BeginMode, first: u32, count: u32, instance_count: u32, base_instance: u32 },
+    DrawElementsInstancedBaseVertexBaseInstance { mode: BeginMode, count: u32, data_type: DataType, offset: u32, instance_count: u32, base_vertex: i32, base_instance: u32 },
+    MultiDrawArraysInstancedBaseInstance { mode: BeginMode, draw_count: usize },
+    MultiDrawElementsInstancedBaseVertexBaseInstance { mode: BeginMode, data_type: DataType, draw_count: usize },
+    ClearDrawBuffer { buffer: Buffer, draw_buffer_index: usize },
+
+    //extensions
+    GetExtension(String),
+
+    //framebuffers
+    CreateFrameBuffer,
+    DeleteFrameBuffer,
+    BindFrameBuffer(FrameBufferTarget),
+    ReleaseFrameBuffer(FrameBufferTarget),
+    CheckFrameBufferStatus(FrameBufferTarget),
+    FrameBufferTexture2d { target: FrameBufferTarget, attachment: FrameBufferAttachment, texture_target: FrameBufferTextureTarget },
+    FrameBufferRenderBuffer { target: FrameBufferTarget, attachment: FrameBufferAttachment },
+    FrameBufferTextureLayer { target: FrameBufferTarget, attachment: FrameBufferAttachment, mipmap_level: u32, layer: u32 },
+    BlitFrameBuffer { src: (u32, u32, u32, u32), dst: (u32, u32, u32, u32), mask: BufferMask, filter: BlitFilter },
+    InvalidateFrameBuffer(FrameBufferTarget, Vec<FrameBufferAttachment>),
+    ReadBuffer(ReadBuffer),
+
+    //funcs
+    DepthFunc(CmpFunction),
+    BlendColor(f32, f32, f32, f32),
+    BlendFunc(BlendFactor, BlendFactor),
+    BlendFuncSeparate(BlendFactor, BlendFactor, BlendFactor, BlendFactor),
+    BlendEquation(BlendEquation),
+    BlendEquationSeparate(BlendEquation, BlendEquation),
+    CullFace(CullFaceMode),
+    FrontFace(FrontFace),
+    PolygonOffset(f32, f32),
+
+    //misc
+    DepthMask(bool),
+    ClearColor(f32, f32, f32, f32),
+    ColorMask(bool, bool, bool, bool),
+    LineWidth(f32),
+    SampleCoverage(f32, bool),
+
+    //read
+    ReadPixels { x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType },
+
+    //renderbuffers
+    CreateRenderBuffer,
+    DeleteRenderBuffer,
+    BindRenderBuffer,
+    ReleaseRenderBuffer,
+    RenderBufferStorage { format: RenderBufferFormat, width: u32, height: u32, samples: Option<u32> },
+
+    //samplers
+    CreateSampler,
+    DeleteSampler,
+    BindSampler(u32),
+    ReleaseSampler(u32),
+    SamplerParameterI(TextureParameterName, i32),
+    SamplerParameterF(TextureParameterName, f32),
+
+    //stencil (non-separate calls are recorded as FrontAndBack)
+    StencilFunc(CullFaceMode, CmpFunction, i32, u32),
+    StencilOp(CullFaceMode, StencilOp, StencilOp, StencilOp),
+    StencilMask(CullFaceMode, u32),
+    ClearStencil(i32),
+
+    //shaders
+    CreateProgram,
+    CreateShader(ShaderType),
+    AttachShader,
+    DetachShader,
+    DeleteShader,
+    DeleteProgram,
+    ShaderSource(String),
+    CompileShader,
+    LinkProgram,
+    UseProgram,
+    BindAttribLocation(u32, String),
+
+    //textures
+    CreateTexture,
+    DeleteTexture,
+    BindTexture(TextureTarget),
+    ReleaseTexture(u32),
+    ActiveTexture(u32),
+    TexWrap(TextureTarget, TextureWrapTarget, TextureWrapMode),
+    TexMinFilter(TextureTarget, TextureMinFilter),
+    TexMagFilter(TextureTarget, TextureMagFilter),
+    PixelStore(WebGlSpecific, i32),
+    GenerateMipmap(TextureTarget),
+    //target is the raw bind target, or the cube face
+    //size is only known for buffer views
+    TexImage { target: u32, mip_level: i32, internal_format: PixelInternalFormat, data_format: PixelDataFormat, data_type: DataType, size: Option<(u32, u32, u32)> },
+    TexSubImage { target: u32, mip_level: u32, offset: (u32, u32, u32), data_format: PixelDataFormat, data_type: DataType, size: Option<(u32, u32, u32)> },
+    CompressedTexImage { target: u32, mip_level: i32, internal_format: PixelInternalFormat, size: (u32, u32, u32) },
+    CompressedTexSubImage { target: u32, mip_level: u32, offset: (u32, u32, u32), internal_format: PixelInternalFormat, size: (u32, u32, u32) },
+    TexStorage { target: TextureTarget, levels: u32, internal_format: PixelInternalFormat, size: (u32, u32, u32) },
+
+    //queries
+    CreateQuery,
+    DeleteQuery,
+    BeginQuery(GlQueryKind),
+    EndQuery(GlQueryKind),
+
+    //toggles
+    Enable(GlToggle),
+    Disable(GlToggle),
+
+    //uniforms
+    UniformF(Vec<f32>),
+    UniformI(Vec<i32>),
+    UniformU(Vec<u32>),
+    UniformMatrix { dim: u8, transpose: bool, values: Vec<f32> },
+
+    //vertex arrays
+    CreateVertexArray,
+    DeleteVertexArray,
+    BindVertexArray,
+    ReleaseVertexArray,
+    VertexAttribDivisor(u32, u32),
+
+    //viewport
+    Viewport(u32, u32, u32, u32),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MockObjectKind {
+    Buffer,
+    FrameBuffer,
+    RenderBuffer,
+    Sampler,
+    Program,
+    Shader,
+    Texture,
+    VertexArray,
+    Query,
+}
+
+impl MockObjectKind {
+    fn index(self) -> usize {
+        match self {
+            Self::Buffer => 0,
+            Self::FrameBuffer => 1,
+            Self::RenderBuffer => 2,
+            Self::Sampler => 3,
+            Self::Program => 4,
+            Self::Shader => 5,
+            Self::Texture => 6,
+            Self::VertexArray => 7,
+            Self::Query => 8,
+        }
+    }
+}
+
+const OBJECT_KIND_LEN: usize = 9;
+
+/// What every program reports as active, once linked
+#[derive(Clone, Debug, Default)]
+pub struct MockProgramInterface {
+    /// name, type, size
+    pub attributes: Vec<(String, UniformDataType, u32)>,
+    /// name, type, size, and block index / offset / array stride / matrix stride (-1 outside of a block)
+    pub uniforms: Vec<(String, UniformDataType, u32, [i32; 4])>,
+    /// name, data size
+    pub uniform_blocks: Vec<(String, u32)>,
+}
+
+pub struct MockContext {
+    pub version: WebGlVersion,
+    pub drawing_buffer_size: Cell<(u32, u32)>,
+    pub context_lost: Cell<bool>,
+    //nanoseconds reported for every query, or None while they're still pending
+    pub query_result: Cell<Option<f64>>,
+    //whether KHR_parallel_shader_compile reports links as done
+    pub link_complete: Cell<bool>,
+    //limits set_compile_error() to one type of shader (checked against the most recently created one)
+    pub compile_error_type: Cell<Option<ShaderType>>,
+    pub program_interface: RefCell<MockProgramInterface>,
+
+    commands: RefCell<Vec<GlCommand>>,
+    created: RefCell<[u32; OBJECT_KIND_LEN]>,
+    deleted: RefCell<[u32; OBJECT_KIND_LEN]>,
+    parameters: RefCell<Vec<(GlParameter, usize)>>,
+    extensions: RefCell<Vec<String>>,
+    compile_error: RefCell<Option<String>>,
+    link_error: RefCell<Option<String>>,
+    last_shader_type: Cell<Option<ShaderType>>,
+}
+
+impl MockContext {
+    pub fn new(version: WebGlVersion) -> Self {
+        Self {
+            version,
+            drawing_buffer_size: Cell::new((300, 150)),
+            context_lost: Cell::new(false),
+            query_result: Cell::new(Some(0.0)),
+            link_complete: Cell::new(true),
+            compile_error_type: Cell::new(None),
+            program_interface: RefCell::new(MockProgramInterface::default()),
+            commands: RefCell::new(Vec::new()),
+            created: RefCell::new([0; OBJECT_KIND_LEN]),
+            deleted: RefCell::new([0; OBJECT_KIND_LEN]),
+            parameters: RefCell::new(Vec::new()),
+            extensions: RefCell::new(Vec::new()),
+            compile_error: RefCell::new(None),
+            link_error: RefCell::new(None),
+            last_shader_type: Cell::new(None),
+        }
+    }
+
+    /// A copy of everything recorded so far
+    pub fn commands(&self) -> Vec<GlCommand> {
+        self.commands.borrow().clone()
+    }
+
+    /// Drain the recorded commands, e.g. to only inspect what happens after setup
+    pub fn take_commands(&self) -> Vec<GlCommand> {
+        std::mem::take(&mut *self.commands.borrow_mut())
+    }
+
+    pub fn clear_commands(&self) {
+        self.commands.borrow_mut().clear();
+    }
+
+    pub fn count_commands(&self, f: impl Fn(&GlCommand) -> bool) -> usize {
+        self.commands.borrow().iter().filter(|cmd| f(cmd)).count()
+    }
+
+    pub fn objects_created(&self, kind: MockObjectKind) -> u32 {
+        self.created.borrow()[kind.index()]
+    }
+
+    pub fn objects_alive(&self, kind: MockObjectKind) -> u32 {
+        self.created.borrow()[kind.index()] - self.deleted.borrow()[kind.index()]
+    }
+
+    /// Override what awsm_get_parameter_usize() reports
+    pub fn set_parameter_usize(&self, query: GlParameter, value: usize) {
+        let mut parameters = self.parameters.borrow_mut();
+        parameters.retain(|(q, _)| *q != query);
+        parameters.push((query, value));
+    }
+
+    /// Extensions which awsm_get_extension() will succeed for
+    pub fn add_extension(&self, name: &str) {
+        self.extensions.borrow_mut().push(name.to_string());
+    }
+
+    /// While set, shader compilation fails with this info log
+    pub fn set_compile_error(&self, log: Option<&str>) {
+        *self.compile_error.borrow_mut() = log.map(|log| log.to_string());
+    }
+
+    /// While set, program linking fails with this info log
+    pub fn set_link_error(&self, log: Option<&str>) {
+        *self.link_error.borrow_mut() = log.map(|log| log.to_string());
+    }
+
+    fn record(&self, cmd: GlCommand) {
+        self.commands.borrow_mut().push(cmd);
+    }
+
+    fn create<T: JsCast>(&self, kind: MockObjectKind) -> T {
+        self.created.borrow_mut()[kind.index()] += 1;
+        mock_handle()
+    }
+
+    fn delete(&self, kind: MockObjectKind) {
+        self.deleted.borrow_mut()[kind.index()] += 1;
+    }
+}
+
+//undefined is one of the reserved values that can be dropped outside of wasm
+fn mock_handle<T: JsCast>() -> T {
+    JsValue::UNDEFINED.unchecked_into()
+}
+
+impl PartialWebGlVersion for MockContext {
+    fn awsm_get_version(&self) -> WebGlVersion {
+        self.version
+    }
+}
+
+impl PartialWebGlCanvas for MockContext {
+    fn awsm_get_canvas(&self) -> Result<HtmlCanvasElement, Error> {
+        Ok(mock_handle())
+    }
+}
+
+impl PartialWebGlContextLoss for MockContext {
+    fn awsm_is_context_lost(&self) -> bool {
+        self.context_lost.get()
+    }
+}
+
+impl PartialWebGlAttribute for MockContext {
+    fn awsm_get_attribute_location(&self, _program: &WebGlProgram, name: &str) -> Result<u32, Error> {
+        self.record(GlCommand::GetAttributeLocation(name.to_string()));
+        self.program_interface
+            .borrow()
+            .attributes
+            .iter()
+            .position(|(attribute_name, _, _)| attribute_name == name)
+            .map(|location| location as u32)
+            .ok_or(Error::from(NativeError::AttributeLocation(Some(name.to_owned()))))
+    }
+
+    fn awsm_activate_attribute(&self, loc: u32, opts: &AttributeOptions) {
+        self.record(GlCommand::ActivateAttribute {
+            loc,
+            size: opts.size,
+            data_type: opts.data_type,
+            normalized: opts.normalized,
+            stride: opts.stride,
+            offset: opts.offset,
+            is_int_array: opts.is_int_array,
+        });
+    }
+}
+
+impl PartialWebGlGetParameter for MockContext {
+    fn awsm_get_parameter_usize(&self, query: GlParameter) -> Result<usize, Error> {
+        if let Some((_, value)) = self.parameters.borrow().iter().find(|(q, _)| *q == query) {
+            return Ok(*value);
+        }
+
+        //the minimums required by the spec
+        match query {
+            GlParameter::MaxVertexAttribs => Ok(16),
+            GlParameter::MaxVertexUniformVectors => Ok(128),
+            GlParameter::MaxVaryingVectors => Ok(8),
+            GlParameter::MaxCombinedTextureImageUnits => Ok(8),
+            GlParameter::MaxVertexTextureImageUnits => Ok(0),
+            GlParameter::MaxTextureImageUnits => Ok(8),
+            GlParameter::MaxFragmentUniformVectors => Ok(16),
+            GlParameter::MaxSamples => Ok(4),
+            _ => Err(Error::from(NativeError::JsValueExpectedNumber)),
+        }
+    }
+
+    fn awsm_get_parameter_vf32(&self, query: GlParameter) -> Result<Vec<f32>, Error> {
+        match query {
+            GlParameter::BlendColor => Ok(vec![0.0, 0.0, 0.0, 0.0]),
+            _ => Err(Error::from(NativeError::JsValueExpectedNumber)),
+        }
+    }
+
+    //reads set_parameter_usize() as non-zero, false otherwise
+    fn awsm_get_parameter_bool(&self, query: GlParameter) -> Result<bool, Error> {
+        Ok(self.parameters.borrow().iter().any(|(q, value)| *q == query && *value != 0))
+    }
+}
+
+impl PartialWebGl2Query for MockContext {
+    fn awsm_create_query(&self) -> Result<WebGlQuery, Error> {
+        self.record(GlCommand::CreateQuery);
+        Ok(self.create(MockObjectKind::Query))
+    }
+    fn awsm_delete_query(&self, _query: &WebGlQuery) {
+        self.record(GlCommand::DeleteQuery);
+        self.delete(MockObjectKind::Query);
+    }
+    fn awsm_begin_query(&self, kind: GlQueryKind, _query: &WebGlQuery) {
+        self.record(GlCommand::BeginQuery(kind));
+    }
+    fn awsm_end_query(&self, kind: GlQueryKind) {
+        self.record(GlCommand::EndQuery(kind));
+    }
+    fn awsm_query_available(&self, _query: &WebGlQuery) -> Result<bool, Error> {
+        Ok(self.query_result.get().is_some())
+    }
+    fn awsm_query_result_f64(&self, _query: &WebGlQuery) -> Result<f64, Error> {
+        self.query_result.get().ok_or(Error::from("Unable to get query result"))
+    }
+}
+
+impl PartialWebGlBuffer for MockContext {
+    fn awsm_upload_buffer_vi32<T: AsRef<[i32]>>(&self, target: BufferTarget, usage: BufferUsage, data: T) {
+        self.record(GlCommand::BufferData { target, usage, byte_len: data.as_ref().len() * 4 });
+    }
+    fn awsm_upload_buffer_vi32_sub<T: AsRef<[i32]>>(&self, target: BufferTarget, dest_byte_offset: u32, src_offset: u32, length: u32, _data: T) -> Result<(), Error> {
+        self.buffer_sub_data(target, dest_byte_offset, src_offset, length)
+    }
+    fn awsm_upload_buffer_vu32<T: AsRef<[u32]>>(&self, target: BufferTarget, usage: BufferUsage, data: T) {
+        self.record(GlCommand::BufferData { target, usage, byte_len: data.as_ref().len() * 4 });
+    }
+    fn awsm_upload_buffer_vu32_sub<T: AsRef<[u32]>>(&self, target: BufferTarget, dest_byte_offset: u32, src_offset: u32, length: u32, _data: T) -> Result<(), Error> {
+        self.buffer_sub_data(target, dest_byte_offset, src_offset, length)
+    }
+    fn awsm_upload_buffer_vf32<T: AsRef<[f32]>>(&self, target: BufferTarget, usage: BufferUsage, data: T) {
+        self.record(GlCommand::BufferData { target, usage, byte_len: data.as_ref().len() * 4 });
+    }
+    fn awsm_upload_buffer_vf32_sub<T: AsRef<[f32]>>(&self, target: BufferTarget, dest_byte_offset: u32, src_offset: u32, length: u32, _data: T) -> Result<(), Error> {
+        self.buffer_sub_data(target, dest_byte_offset, src_offset, length)
+    }
+    fn awsm_upload_buffer_vu8<T: AsRef<[u8]>>(&self, target: BufferTarget, usage: BufferUsage, data: T) {
+        self.record(GlCommand::BufferData { target, usage, byte_len: data.as_ref().len() });
+    }
+    fn awsm_upload_buffer_vu8_sub<T: AsRef<[u8]>>(&self, target: BufferTarget, dest_byte_offset: u32, src_offset: u32, length: u32, _data: T) -> Result<(), Error> {
+        self.buffer_sub_data(target, dest_byte_offset, src_offset, length)
+    }
+
+    fn awsm_bind_buffer(&self, target: BufferTarget, _buffer: &WebGlBuffer) {
+        self.record(GlCommand::BindBuffer(target));
+    }
+    fn awsm_release_buffer(&self, target: BufferTarget) {
+        self.record(GlCommand::ReleaseBuffer(target));
+    }
+    fn awsm_create_buffer(&self) -> Result<WebGlBuffer, Error> {
+        self.record(GlCommand::CreateBuffer);
+        Ok(self.create(MockObjectKind::Buffer))
+    }
+    fn awsm_delete_buffer(&self, _buffer: &WebGlBuffer) {
+        self.record(GlCommand::DeleteBuffer);
+        self.delete(MockObjectKind::Buffer);
+    }
+}
+
+impl MockContext {
+    //same rules as the real contexts
+    fn buffer_sub_data(&self, target: BufferTarget, dest_byte_offset: u32, src_offset: u32, length: u32) -> Result<(), Error> {
+        if self.version == WebGlVersion::One && src_offset != 0 {
+            return Err(Error::from(NativeError::WebGlBufferSourceOneNonZero));
+        }
+        self.record(GlCommand::BufferSubData { target, dest_byte_offset, src_offset, length });
+        Ok(())
+    }
+}
+
+impl PartialWebGlDrawing for MockContext {
+    fn awsm_clear(&self, bits: &[BufferMask]) {
+        let combined = bits.iter().fold(0u32, |acc, bit| acc | *bit as u32);
+        self.record(GlCommand::Clear(combined));
+    }
+    fn awsm_scissor(&self, x: i32, y: i32, width: u32, height: u32) {
+        self.record(GlCommand::Scissor(x, y, width, height));
+    }
+    fn awsm_draw_arrays(&self, mode: BeginMode, first: u32, count: u32) {
+        self.record(GlCommand::DrawArrays { mode, first, count });
+    }
+    fn awsm_draw_elements(&self, mode: BeginMode, count: u32, data_type: DataType, offset: u32) {
+        self.record(GlCommand::DrawElements { mode, count, data_type, offset });
+    }
+    fn awsm_multi_draw_arrays(&self, _ext: &WebglMultiDraw, mode: BeginMode, firsts: &[u32], _counts: &[u32]) {
+        self.record(GlCommand::MultiDrawArrays { mode, draw_count: firsts.len() });
+    }
+    fn awsm_multi_draw_elements(&self, _ext: &WebglMultiDraw, mode: BeginMode, counts: &[u32], data_type: DataType, _offsets: &[u32]) {
+        self.record(GlCommand::MultiDrawElements { mode, data_type, draw_count: counts.len() });
+    }
+    fn awsm_multi_draw_arrays_instanced(&self, _ext: &WebglMultiDraw, mode: BeginMode, firsts: &[u32], _counts: &[u32], _instance_counts: &[u32]) {
+        self.record(GlCommand::MultiDrawArraysInstanced { mode, draw_count: firsts.len() });
+    }
+    fn awsm_multi_draw_elements_instanced(&self, _ext: &WebglMultiDraw, mode: BeginMode, counts: &[u32], data_type: DataType, _offsets: &[u32], _instance_counts: &[u32]) {
+        self.record(GlCommand::MultiDrawElementsInstanced { mode, data_type, draw_count: counts.len() });
+    }
+}
+
+impl PartialWebGl2Drawing for MockContext {
+    fn awsm_clear_draw_buffer_vf32(&self, buffer: Buffer, draw_buffer_index: usize, _values: &[f32]) {
+        self.record(GlCommand::ClearDrawBuffer { buffer, draw_buffer_index });
+    }
+    fn awsm_clear_draw_buffer_vi32(&self, buffer: Buffer, draw_buffer_index: usize, _values: &[i32]) {
+        self.record(GlCommand::ClearDrawBuffer { buffer, draw_buffer_index });
+    }
+    fn awsm_clear_draw_buffer_vu32(&self, buffer: Buffer, draw_buffer_index: usize, _values: &[u32]) {
+        self.record(GlCommand::ClearDrawBuffer { buffer, draw_buffer_index });
+    }
+    fn awsm_clear_draw_buffer_fi(&self, buffer: Buffer, draw_buffer_index: usize, _depth: f32, _stencil: i32) {
+        self.record(GlCommand::ClearDrawBuffer { buffer, draw_buffer_index });
+    }
+    fn awsm_draw_arrays_instanced_base_instance(&self, _ext: &WebglDrawInstancedBaseVertexBaseInstance, mode: BeginMode, first: u32, count: u32, instance_count: u32, base_instance: u32) {
+        self.record(GlCommand::DrawArraysInstancedBaseInstance { mode, first, count, instance_count, base_instance });
+    }
+    fn awsm_draw_elements_instanced_base_vertex_base_instance(&self, _ext: &WebglDrawInstancedBaseVertexBaseInstance, mode: BeginMode, count: u32, data_type: DataType, offset: u32, instance_count: u32, base_vertex: i32, base_instance: u32) {
+        self.record(GlCommand::DrawElementsInstancedBaseVertexBaseInstance { mode, count, data_type, offset, instance_count, base_vertex, base_instance });
+    }
+    fn awsm_multi_draw_arrays_instanced_base_instance(&self, _ext: &WebglMultiDrawInstancedBaseVertexBaseInstance, mode: BeginMode, firsts: &[u32], _counts: &[u32], _instance_counts: &[u32], _base_instances: &[u32]) {
+        self.record(GlCommand::MultiDrawArraysInstancedBaseInstance { mode, draw_count: firsts.len() });
+    }
+    fn awsm_multi_draw_elements_instanced_base_vertex_base_instance(&self, _ext: &WebglMultiDrawInstancedBaseVertexBaseInstance, mode: BeginMode, counts: &[u32], data_type: DataType, _offsets: &[u32], _instance_counts: &[u32], _base_vertices: &[i32], _base_instances: &[u32]) {
+        self.record(GlCommand::MultiDrawElementsInstancedBaseVertexBaseInstance { mode, data_type, draw_count: counts.len() });
+    }
+}
+
+impl PartialWebGlExtensions for MockContext {
+    fn awsm_get_extension(&self, name: &str) -> Result<js_sys::Object, Error> {
+        self.record(GlCommand::GetExtension(name.to_string()));
+        if self.extensions.borrow().iter().any(|ext| ext == name) {
+            Ok(mock_handle())
+        } else {
+            Err(Error::from(NativeError::NoExtension(Some(name.to_string()))))
+        }
+    }
+    fn awsm_get_supported_extensions(&self) -> Vec<String> {
+        self.extensions.borrow().clone()
+    }
+}
+
+impl PartialWebGlFrameBuffer for MockContext {
+    fn awsm_bind_framebuffer(&self, target: FrameBufferTarget, _buffer: &WebGlFramebuffer) {
+        self.record(GlCommand::BindFrameBuffer(target));
+    }
+    fn awsm_delete_framebuffer(&self, _buffer: &WebGlFramebuffer) {
+        self.record(GlCommand::DeleteFrameBuffer);
+        self.delete(MockObjectKind::FrameBuffer);
+    }
+    fn awsm_is_framebuffer(&self, _buffer: &WebGlFramebuffer) -> bool {
+        true
+    }
+    fn awsm_create_framebuffer(&self) -> Result<WebGlFramebuffer, Error> {
+        self.record(GlCommand::CreateFrameBuffer);
+        Ok(self.create(MockObjectKind::FrameBuffer))
+    }
+    fn awsm_release_framebuffer(&self, target: FrameBufferTarget) {
+        self.record(GlCommand::ReleaseFrameBuffer(target));
+    }
+    fn awsm_check_framebuffer_status(&self, target: FrameBufferTarget) -> Result<(), Error> {
+        self.record(GlCommand::CheckFrameBufferStatus(target));
+        Ok(())
+    }
+    fn awsm_framebuffer_texture_2d(&self, target: FrameBufferTarget, attachment: FrameBufferAttachment, texture_target: FrameBufferTextureTarget, _texture: &WebGlTexture) {
+        self.record(GlCommand::FrameBufferTexture2d { target, attachment, texture_target });
+    }
+    fn awsm_framebuffer_renderbuffer(&self, target: FrameBufferTarget, attachment: FrameBufferAttachment, _renderbuffer: &WebGlRenderbuffer) {
+        self.record(GlCommand::FrameBufferRenderBuffer { target, attachment });
+    }
+}
+
+impl PartialWebGl2FrameBuffer for MockContext {
+    fn awsm_blit_framebuffer(&self, src_x0: u32, src_y0: u32, src_x1: u32, src_y1: u32, dst_x0: u32, dst_y0: u32, dst_x1: u32, dst_y1: u32, mask: BufferMask, filter: BlitFilter) {
+        self.record(GlCommand::BlitFrameBuffer {
+            src: (src_x0, src_y0, src_x1, src_y1),
+            dst: (dst_x0, dst_y0, dst_x1, dst_y1),
+            mask,
+            filter,
+        });
+    }
+    fn awsm_framebuffer_texture_layer(&self, target: FrameBufferTarget, attachment: FrameBufferAttachment, _texture: &WebGlTexture, mipmap_level: u32, layer: u32) {
+        self.record(GlCommand::FrameBufferTextureLayer { target, attachment, mipmap_level, layer });
+    }
+    fn awsm_invalidate_framebuffer(&self, target: FrameBufferTarget, attachments: &[FrameBufferAttachment]) -> Result<(), Error> {
+        self.record(GlCommand::InvalidateFrameBuffer(target, attachments.to_vec()));
+        Ok(())
+    }
+    fn awsm_invalidate_sub_framebuffer(&self, target: FrameBufferTarget, attachments: &[FrameBufferAttachment], _x: u32, _y: u32, _width: usize, _height: usize) -> Result<(), Error> {
+        self.record(GlCommand::InvalidateFrameBuffer(target, attachments.to_vec()));
+        Ok(())
+    }
+    fn awsm_read_buffer(&self, src: ReadBuffer) {
+        self.record(GlCommand::ReadBuffer(src));
+    }
+}
+
+impl PartialWebGlFuncs for MockContext {
+    fn awsm_depth_func(&self, func: CmpFunction) {
+        self.record(GlCommand::DepthFunc(func));
+    }
+    fn awsm_blend_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        self.record(GlCommand::BlendColor(r, g, b, a));
+    }
+    fn awsm_blend_func(&self, sfactor: BlendFactor, dfactor: BlendFactor) {
+        self.record(GlCommand::BlendFunc(sfactor, dfactor));
+    }
+    fn awsm_blend_func_separate(&self, src_rgb: BlendFactor, dest_rgb: BlendFactor, src_alpha: BlendFactor, dest_alpha: BlendFactor) {
+        self.record(GlCommand::BlendFuncSeparate(src_rgb, dest_rgb, src_alpha, dest_alpha));
+    }
+    fn awsm_blend_equation(&self, mode: BlendEquation) {
+        self.record(GlCommand::BlendEquation(mode));
+    }
+    fn awsm_blend_equation_separate(&self, rgb_mode: BlendEquation, alpha_mode: BlendEquation) {
+        self.record(GlCommand::BlendEquationSeparate(rgb_mode, alpha_mode));
+    }
+    fn awsm_cull_face(&self, mode: CullFaceMode) {
+        self.record(GlCommand::CullFace(mode));
+    }
+    fn awsm_front_face(&self, mode: FrontFace) {
+        self.record(GlCommand::FrontFace(mode));
+    }
+    fn awsm_polygon_offset(&self, factor: f32, units: f32) {
+        self.record(GlCommand::PolygonOffset(factor, units));
+    }
+}
+
+impl PartialWebGlMisc for MockContext {
+    fn awsm_depth_mask(&self, flag: bool) {
+        self.record(GlCommand::DepthMask(flag));
+    }
+    fn awsm_clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        self.record(GlCommand::ClearColor(r, g, b, a));
+    }
+    fn awsm_color_mask(&self, r: bool, g: bool, b: bool, a: bool) {
+        self.record(GlCommand::ColorMask(r, g, b, a));
+    }
+    fn awsm_line_width(&self, width: f32) {
+        self.record(GlCommand::LineWidth(width));
+    }
+    fn awsm_sample_coverage(&self, value: f32, invert: bool) {
+        self.record(GlCommand::SampleCoverage(value, invert));
+    }
+}
+
+impl PartialWebGlReadPixels for MockContext {
+    fn awsm_read_pixels_u8(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, data: &mut [u8]) -> Result<(), Error> {
+        data.iter_mut().for_each(|value| *value = 0);
+        self._awsm_read_pixels_typed_data(x, y, width, height, format, data_type, &mock_handle())
+    }
+    fn awsm_read_pixels_u8_typed(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, data: &mut [u8]) -> Result<(), Error> {
+        self.awsm_read_pixels_u8(x, y, width, height, format, data_type, data)
+    }
+    fn awsm_read_pixels_u16(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, data: &mut [u16]) -> Result<(), Error> {
+        data.iter_mut().for_each(|value| *value = 0);
+        self._awsm_read_pixels_typed_data(x, y, width, height, format, data_type, &mock_handle())
+    }
+    fn awsm_read_pixels_f32(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, data: &mut [f32]) -> Result<(), Error> {
+        data.iter_mut().for_each(|value| *value = 0.0);
+        self._awsm_read_pixels_typed_data(x, y, width, height, format, data_type, &mock_handle())
+    }
+    fn _awsm_read_pixels_typed_data(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, _data: &js_sys::Object) -> Result<(), Error> {
+        self.record(GlCommand::ReadPixels { x, y, width, height, format, data_type });
+        Ok(())
+    }
+}
+
+impl PartialWebGl2ReadPixels for MockContext {
+    fn awsm_read_pixels_u8_offset(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, offset: usize, data: &mut [u8]) -> Result<(), Error> {
+        self.awsm_read_pixels_u8(x, y, width, height, format, data_type, &mut data[offset..])
+    }
+    fn awsm_read_pixels_u8_typed_offset(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, offset: usize, data: &mut [u8]) -> Result<(), Error> {
+        self.awsm_read_pixels_u8(x, y, width, height, format, data_type, &mut data[offset..])
+    }
+    fn awsm_read_pixels_u16_offset(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, offset: usize, data: &mut [u16]) -> Result<(), Error> {
+        self.awsm_read_pixels_u16(x, y, width, height, format, data_type, &mut data[offset..])
+    }
+    fn awsm_read_pixels_f32_offset(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, offset: usize, data: &mut [f32]) -> Result<(), Error> {
+        self.awsm_read_pixels_f32(x, y, width, height, format, data_type, &mut data[offset..])
+    }
+    fn _awsm_read_pixels_typed_data_offset(&self, x: u32, y: u32, width: u32, height: u32, format: ReadPixelFormat, data_type: ReadPixelDataType, _offset: usize, data: &js_sys::Object) -> Result<(), Error> {
+        self._awsm_read_pixels_typed_data(x, y, width, height, format, data_type, data)
+    }
+}
+
+impl PartialWebGlRenderBuffer for MockContext {
+    fn awsm_bind_renderbuffer(&self, _buffer: &WebGlRenderbuffer) {
+        self.record(GlCommand::BindRenderBuffer);
+    }
+    fn awsm_delete_renderbuffer(&self, _buffer: &WebGlRenderbuffer) {
+        self.record(GlCommand::DeleteRenderBuffer);
+        self.delete(MockObjectKind::RenderBuffer);
+    }
+    fn awsm_is_renderbuffer(&self, _buffer: &WebGlRenderbuffer) -> bool {
+        true
+    }
+    fn awsm_create_renderbuffer(&self) -> Result<WebGlRenderbuffer, Error> {
+        self.record(GlCommand::CreateRenderBuffer);
+        Ok(self.create(MockObjectKind::RenderBuffer))
+    }
+    fn awsm_renderbuffer_storage(&self, format: RenderBufferFormat, width: u32, height: u32) {
+        self.record(GlCommand::RenderBufferStorage { format, width, height, samples: None });
+    }
+    fn awsm_release_renderbuffer(&self) {
+        self.record(GlCommand::ReleaseRenderBuffer);
+    }
+}
+
+impl PartialWebGl2RenderBuffer for MockContext {
+    fn awsm_renderbuffer_storage_multisample(&self, samples: u32, format: RenderBufferFormat, width: u32, height: u32) {
+        self.record(GlCommand::RenderBufferStorage { format, width, height, samples: Some(samples) });
+    }
+}
+
+impl PartialWebGl2Sampler for MockContext {
+    fn awsm_create_sampler(&self) -> Result<WebGlSampler, Error> {
+        self.record(GlCommand::CreateSampler);
+        Ok(self.create(MockObjectKind::Sampler))
+    }
+    fn awsm_delete_sampler(&self, _sampler: &WebGlSampler) {
+        self.record(GlCommand::DeleteSampler);
+        self.delete(MockObjectKind::Sampler);
+    }
+    fn awsm_bind_sampler(&self, unit: u32, sampler: Option<&WebGlSampler>) {
+        match sampler {
+            Some(_) => self.record(GlCommand::BindSampler(unit)),
+            None => self.record(GlCommand::ReleaseSampler(unit)),
+        }
+    }
+    fn awsm_sampler_parameter_i(&self, _sampler: &WebGlSampler, pname: TextureParameterName, param: i32) {
+        self.record(GlCommand::SamplerParameterI(pname, param));
+    }
+    fn awsm_sampler_parameter_f(&self, _sampler: &WebGlSampler, pname: TextureParameterName, param: f32) {
+        self.record(GlCommand::SamplerParameterF(pname, param));
+    }
+}
+
+impl PartialWebGlShaders for MockContext {
+    fn awsm_create_program(&self) -> Result<WebGlProgram, Error> {
+        self.record(GlCommand::CreateProgram);
+        Ok(self.create(MockObjectKind::Program))
+    }
+    fn awsm_create_shader(&self, type_: ShaderType) -> Option<WebGlShader> {
+        self.record(GlCommand::CreateShader(type_));
+        self.last_shader_type.set(Some(type_));
+        Some(self.create(MockObjectKind::Shader))
+    }
+    fn awsm_attach_shader(&self, _program: &WebGlProgram, _shader: &WebGlShader) {
+        self.record(GlCommand::AttachShader);
+    }
+    fn awsm_detach_shader(&self, _program: &WebGlProgram, _shader: &WebGlShader) {
+        self.record(GlCommand::DetachShader);
+    }
+    fn awsm_delete_shader(&self, _shader: &WebGlShader) {
+        self.record(GlCommand::DeleteShader);
+        self.delete(MockObjectKind::Shader);
+    }
+    fn awsm_delete_program(&self, _program: &WebGlProgram) {
+        self.record(GlCommand::DeleteProgram);
+        self.delete(MockObjectKind::Program);
+    }
+    fn awsm_shader_source(&self, _shader: &WebGlShader, source: &str) {
+        self.record(GlCommand::ShaderSource(source.to_string()));
+    }
+    fn awsm_compile_shader(&self, _shader: &WebGlShader) {
+        self.record(GlCommand::CompileShader);
+    }
+    fn awsm_link_program(&self, _program: &WebGlProgram) {
+        self.record(GlCommand::LinkProgram);
+    }
+    fn awsm_get_shader_parameter_bool(&self, _shader: &WebGlShader, query: ShaderQuery) -> Result<bool, Error> {
+        match query {
+            ShaderQuery::CompileStatus => {
+                let applies = match self.compile_error_type.get() {
+                    Some(type_) => self.last_shader_type.get() == Some(type_),
+                    None => true,
+                };
+                Ok(self.compile_error.borrow().is_none() || !applies)
+            },
+            _ => Ok(false),
+        }
+    }
+    fn awsm_get_program_parameter_bool(&self, _program: &WebGlProgram, query: ProgramQuery) -> Result<bool, Error> {
+        match query {
+            ProgramQuery::LinkStatus | ProgramQuery::ValidateStatus => Ok(self.link_error.borrow().is_none()),
+            ProgramQuery::CompletionStatusKhr => Ok(self.link_complete.get()),
+            _ => Ok(false),
+        }
+    }
+    fn awsm_get_program_parameter_u32(&self, _program: &WebGlProgram, query: ProgramQuery) -> Result<u32, Error> {
+        let interface = self.program_interface.borrow();
+        let count = match query {
+            ProgramQuery::ActiveAttributes => interface.attributes.len(),
+            ProgramQuery::ActiveUniforms => interface.uniforms.len(),
+            ProgramQuery::ActiveUniformBlocks => interface.uniform_blocks.len(),
+            _ => 0,
+        };
+        Ok(count as u32)
+    }
+    fn awsm_get_shader_info_log(&self, _shader: &WebGlShader) -> Option<String> {
+        self.compile_error.borrow().clone()
+    }
+    fn awsm_get_program_info_log(&self, _program: &WebGlProgram) -> Option<String> {
+        self.link_error.borrow().clone()
+    }
+    fn awsm_use_program(&self, _program: &WebGlProgram) {
+        self.record(GlCommand::UseProgram);
+    }
+    fn awsm_get_active_uniform(&self, _program: &WebGlProgram, _index: u32) -> Result<WebGlActiveInfo, Error> {
+        Err(Error::from(NativeError::UniformLocation(None)))
+    }
+    fn awsm_get_active_attrib(&self, _program: &WebGlProgram, _index: u32) -> Result<WebGlActiveInfo, Error> {
+        Err(Error::from(NativeError::AttributeLocation(None)))
+    }
+    fn awsm_bind_attrib_location(&self, _program: &WebGlProgram, index: u32, name: &str) {
+        self.record(GlCommand::BindAttribLocation(index, name.to_string()));
+    }
+}
+
+impl PartialWebGlReflection for MockContext {
+    fn awsm_get_active_attrib_info(&self, _program: &WebGlProgram, index: u32) -> Result<ActiveInfo, Error> {
+        self.program_interface
+            .borrow()
+            .attributes
+            .get(index as usize)
+            .map(|(name, data_type, size)| (name.clone(), *data_type as u32, *size))
+            .ok_or(Error::from(NativeError::AttributeLocation(None)))
+    }
+    fn awsm_get_active_uniform_info(&self, _program: &WebGlProgram, index: u32) -> Result<ActiveInfo, Error> {
+        self.program_interface
+            .borrow()
+            .uniforms
+            .get(index as usize)
+            .map(|(name, data_type, size, _)| (name.clone(), *data_type as u32, *size))
+            .ok_or(Error::from(NativeError::UniformLocation(None)))
+    }
+}
+
+impl PartialWebGl2Reflection for MockContext {
+    fn awsm_get_active_uniforms_i32(&self, _program: &WebGlProgram, indices: &[u32], query: UniformBlockActiveQuery) -> Result<Vec<i32>, Error> {
+        let field = match query {
+            UniformBlockActiveQuery::BlockIndex => 0,
+            UniformBlockActiveQuery::Offset => 1,
+            UniformBlockActiveQuery::ArrayStride => 2,
+            UniformBlockActiveQuery::MatrixStride => 3,
+            _ => return Err(Error::from(NativeError::JsValueExpectedNumber)),
+        };
+
+        let interface = self.program_interface.borrow();
+        indices
+            .iter()
+            .map(|index| {
+                interface
+                    .uniforms
+                    .get(*index as usize)
+                    .map(|(_, _, _, values)| values[field])
+                    .ok_or(Error::from(NativeError::UniformLocation(None)))
+            })
+            .collect()
+    }
+    fn awsm_get_active_uniform_block_name(&self, _program: &WebGlProgram, index: u32) -> Result<String, Error> {
+        self.program_interface
+            .borrow()
+            .uniform_blocks
+            .get(index as usize)
+            .map(|(name, _)| name.clone())
+            .ok_or(Error::from(NativeError::UniformBufferName))
+    }
+    fn awsm_get_active_uniform_block_parameter_u32(&self, _program: &WebGlProgram, index: u32, query: UniformBlockQuery) -> Result<u32, Error> {
+        let interface = self.program_interface.borrow();
+        let (_, data_size) = interface
+            .uniform_blocks
+            .get(index as usize)
+            .ok_or(Error::from(NativeError::UniformBufferParameter))?;
+
+        match query {
+            UniformBlockQuery::DataSize => Ok(*data_size),
+            //nothing is bound to a block until it's asked for
+            UniformBlockQuery::BindingPoint => Ok(0),
+            _ => Err(Error::from(NativeError::UniformBufferParameter)),
+        }
+    }
+}
+
+impl PartialWebGlStencil for MockContext {
+    fn awsm_stencil_func(&self, func: CmpFunction, reference: i32, mask: u32) {
+        self.record(GlCommand::StencilFunc(CullFaceMode::FrontAndBack, func, reference, mask));
+    }
+    fn awsm_stencil_func_separate(&self, face: CullFaceMode, func: CmpFunction, reference: i32, mask: u32) {
+        self.record(GlCommand::StencilFunc(face, func, reference, mask));
+    }
+    fn awsm_stencil_op(&self, fail: StencilOp, zfail: StencilOp, zpass: StencilOp) {
+        self.record(GlCommand::StencilOp(CullFaceMode::FrontAndBack, fail, zfail, zpass));
+    }
+    fn awsm_stencil_op_separate(&self, face: CullFaceMode, fail: StencilOp, zfail: StencilOp, zpass: StencilOp) {
+        self.record(GlCommand::StencilOp(face, fail, zfail, zpass));
+    }
+    fn awsm_stencil_mask(&self, mask: u32) {
+        self.record(GlCommand::StencilMask(CullFaceMode::FrontAndBack, mask));
+    }
+    fn awsm_stencil_mask_separate(&self, face: CullFaceMode, mask: u32) {
+        self.record(GlCommand::StencilMask(face, mask));
+    }
+    fn awsm_clear_stencil(&self, s: i32) {
+        self.record(GlCommand::ClearStencil(s));
+    }
+}
+
+impl PartialWebGlTextures for MockContext {
+    fn awsm_create_texture(&self) -> Result<WebGlTexture, Error> {
+        self.record(GlCommand::CreateTexture);
+        Ok(self.create(MockObjectKind::Texture))
+    }
+    fn awsm_delete_texture(&self, _texture: &WebGlTexture) {
+        self.record(GlCommand::DeleteTexture);
+        self.delete(MockObjectKind::Texture);
+    }
+    fn awsm_bind_texture(&self, bind_target: TextureTarget, _texture: &WebGlTexture) {
+        self.record(GlCommand::BindTexture(bind_target));
+    }
+    fn awsm_release_texture_target(&self, bind_target: TextureTarget) {
+        self.record(GlCommand::ReleaseTexture(bind_target as u32));
+    }
+    fn awsm_release_texture_target_raw(&self, bind_target: u32) {
+        self.record(GlCommand::ReleaseTexture(bind_target));
+    }
+    fn awsm_texture_set_wrap(&self, bind_target: TextureTarget, wrap_target: TextureWrapTarget, wrap_mode: TextureWrapMode) {
+        self.record(GlCommand::TexWrap(bind_target, wrap_target, wrap_mode));
+    }
+    fn awsm_texture_set_min_filter(&self, bind_target: TextureTarget, filter: TextureMinFilter) {
+        self.record(GlCommand::TexMinFilter(bind_target, filter));
+    }
+    fn awsm_texture_set_mag_filter(&self, bind_target: TextureTarget, filter: TextureMagFilter) {
+        self.record(GlCommand::TexMagFilter(bind_target, filter));
+    }
+    fn awsm_texture_sources_can_mipmap(&self, srcs: &[&WebGlTextureSource]) -> Result<(), Error> {
+        if self.version == WebGlVersion::One && !srcs.iter().all(|&src| is_power_of_2(src)) {
+            Err(Error::from(NativeError::MipsPowerOf2))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn awsm_assign_simple_texture(&self, bind_target: TextureTarget, opts: &SimpleTextureOptions, src: &WebGlTextureSource, dest: &WebGlTexture) -> Result<(), Error> {
+        let set_parameters = Some(|_: &MockContext| {
+            self.awsm_simple_parameters(bind_target, opts, false);
+        });
+
+        self.awsm_assign_texture(bind_target, &get_texture_options_from_simple(opts), set_parameters, src, dest)
+    }
+
+    fn awsm_assign_simple_texture_mips(&self, bind_target: TextureTarget, opts: &SimpleTextureOptions, srcs: &[&WebGlTextureSource], dest: &WebGlTexture) -> Result<(), Error> {
+        self.awsm_texture_sources_can_mipmap(srcs)?;
+        let set_parameters = Some(|_: &MockContext| {
+            self.awsm_simple_parameters(bind_target, opts, true);
+        });
+
+        self.awsm_assign_texture_mips(bind_target, &get_texture_options_from_simple(opts), set_parameters, srcs, dest)
+    }
+
+    fn awsm_simple_parameters(&self, bind_target: TextureTarget, opts: &SimpleTextureOptions, use_mips: bool) {
+        if let Some(flip_y) = opts.flip_y {
+            self.record(GlCommand::PixelStore(WebGlSpecific::UnpackFlipY, flip_y as i32));
+        }
+        if let Some(premultiply_alpha) = opts.premultiply_alpha {
+            self.record(GlCommand::PixelStore(WebGlSpecific::UnpackPremultiplyAlpha, premultiply_alpha as i32));
+        }
+
+        if use_mips {
+            self.record(GlCommand::GenerateMipmap(bind_target));
+        } else {
+            if let Some(wrap_s) = opts.wrap_s {
+                self.awsm_texture_set_wrap(bind_target, TextureWrapTarget::S, wrap_s);
+            }
+            if let Some(wrap_t) = opts.wrap_t {
+                self.awsm_texture_set_wrap(bind_target, TextureWrapTarget::T, wrap_t);
+            }
+            if let Some(wrap_r) = opts.wrap_r {
+                self.awsm_texture_set_wrap(bind_target, TextureWrapTarget::R, wrap_r);
+            }
+            if let Some(filter_min) = opts.filter_min {
+                self.awsm_texture_set_min_filter(bind_target, filter_min);
+            }
+            if let Some(filter_mag) = opts.filter_mag {
+                self.awsm_texture_set_mag_filter(bind_target, filter_mag);
+            }
+        }
+    }
+
+    fn awsm_assign_texture(&self, bind_target: TextureTarget, opts: &TextureOptions, set_parameters: Option<impl Fn(&Self) -> ()>, src: &WebGlTextureSource, dest: &WebGlTexture) -> Result<(), Error> {
+        self.awsm_assign_texture_mips(bind_target, opts, set_parameters, &[src], dest)
+    }
+
+    fn awsm_assign_texture_mips(&self, bind_target: TextureTarget, opts: &TextureOptions, set_parameters: Option<impl Fn(&Self) -> ()>, srcs: &[&WebGlTextureSource], dest: &WebGlTexture) -> Result<(), Error> {
+        self.awsm_bind_texture(bind_target, dest);
+
+        if let Some(f) = set_parameters {
+            f(self);
+        }
+
+        for (mip_level, src) in srcs.iter().enumerate() {
+            self._awsm_assign_texture(bind_target, mip_level as i32, opts, src)?;
+        }
+
+        Ok(())
+    }
+
+    fn awsm_activate_texture_sampler_index(&self, sampler_index: u32) {
+        self.record(GlCommand::ActiveTexture(sampler_index));
+    }
+
+    fn awsm_update_texture_sub(&self, bind_target: TextureTarget, opts: &TextureSubOptions, x: u32, y: u32, z: u32, src: &WebGlTextureSource, dest: &WebGlTexture) -> Result<(), Error> {
+        self.awsm_bind_texture(bind_target, dest);
+        self._awsm_update_texture_sub(bind_target, opts, x, y, z, src)
+    }
+
+    fn _awsm_update_texture_sub(&self, bind_target: TextureTarget, opts: &TextureSubOptions, x: u32, y: u32, z: u32, src: &WebGlTextureSource) -> Result<(), Error> {
+        let cube_face_u32 = get_cube_face_u32(bind_target, opts.cube_face)?;
+
+        if self.version == WebGlVersion::One {
+            match bind_target {
+                TextureTarget::Texture3d => return Err(Error::from(NativeError::WebGl1Texture3d)),
+                TextureTarget::Array2d => return Err(Error::from(NativeError::WebGl1TextureArray2d)),
+                _ => {}
+            }
+        }
+
+        let size = match src {
+            WebGlTextureSource::ArrayBufferView(_, width, height, depth) => Some((*width, *height, *depth)),
+            WebGlTextureSource::EmptyBufferView(_, _, _) => {
+                return Err(Error::from("can't update a texture from an empty buffer view"));
+            }
+            WebGlTextureSource::CompressedBufferView(_, _, _, _) => {
+                return Err(Error::from("can't update a compressed texture from a sub image"));
+            }
+            _ => None,
+        };
+
+        self.record(GlCommand::TexSubImage {
+            target: if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_target as u32 },
+            mip_level: opts.mip_level,
+            offset: (x, y, z),
+            data_format: opts.data_format,
+            data_type: opts.data_type,
+            size,
+        });
+
+        Ok(())
+    }
+
+    fn awsm_update_compressed_texture_sub(&self, bind_target: TextureTarget, internal_format: PixelInternalFormat, opts: &TextureSubOptions, x: u32, y: u32, z: u32, src: &WebGlTextureSource, dest: &WebGlTexture) -> Result<(), Error> {
+        self.awsm_bind_texture(bind_target, dest);
+        self._awsm_update_compressed_texture_sub(bind_target, internal_format, opts, x, y, z, src)
+    }
+
+    fn _awsm_update_compressed_texture_sub(&self, bind_target: TextureTarget, internal_format: PixelInternalFormat, opts: &TextureSubOptions, x: u32, y: u32, z: u32, src: &WebGlTextureSource) -> Result<(), Error> {
+        let cube_face_u32 = get_cube_face_u32(bind_target, opts.cube_face)?;
+
+        if self.version == WebGlVersion::One {
+            match bind_target {
+                TextureTarget::Texture3d => return Err(Error::from(NativeError::WebGl1Texture3d)),
+                TextureTarget::Array2d => return Err(Error::from(NativeError::WebGl1TextureArray2d)),
+                _ => {}
+            }
+        }
+
+        match src {
+            WebGlTextureSource::CompressedBufferView(_, width, height, depth) => {
+                self.record(GlCommand::CompressedTexSubImage {
+                    target: if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_target as u32 },
+                    mip_level: opts.mip_level,
+                    offset: (x, y, z),
+                    internal_format,
+                    size: (*width, *height, *depth),
+                });
+                Ok(())
+            }
+            _ => Err(Error::from("can't update a compressed texture from an uncompressed source")),
+        }
+    }
+
+    fn _awsm_assign_texture(&self, bind_target: TextureTarget, mip_level: i32, opts: &TextureOptions, src: &WebGlTextureSource) -> Result<(), Error> {
+        let cube_face_u32 = get_cube_face_u32(bind_target, opts.cube_face)?;
+
+        if self.version == WebGlVersion::One {
+            match bind_target {
+                TextureTarget::Texture3d => return Err(Error::from(NativeError::WebGl1Texture3d)),
+                TextureTarget::Array2d => return Err(Error::from(NativeError::WebGl1TextureArray2d)),
+                _ => {}
+            }
+        }
+
+        let size = match src {
+            WebGlTextureSource::ArrayBufferView(_, width, height, depth) => Some((*width, *height, *depth)),
+            WebGlTextureSource::EmptyBufferView(width, height, depth) => Some((*width, *height, *depth)),
+            WebGlTextureSource::CompressedBufferView(_, width, height, depth) => {
+                self.record(GlCommand::CompressedTexImage {
+                    target: if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_target as u32 },
+                    mip_level,
+                    internal_format: opts.internal_format,
+                    size: (*width, *height, *depth),
+                });
+                return Ok(());
+            }
+            _ => None,
+        };
+
+        self.record(GlCommand::TexImage {
+            target: if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_target as u32 },
+            mip_level,
+            internal_format: opts.internal_format,
+            data_format: opts.data_format,
+            data_type: opts.data_type,
+            size,
+        });
+
+        Ok(())
+    }
+}
+
+impl PartialWebGl2Textures for MockContext {
+    fn awsm_texture_storage(&self, bind_target: TextureTarget, levels: u32, internal_format: PixelInternalFormat, width: u32, height: u32, depth: u32) {
+        self.record(GlCommand::TexStorage { target: bind_target, levels, internal_format, size: (width, height, depth) });
+    }
+}
+
+impl PartialWebGlToggle for MockContext {
+    fn awsm_enable(&self, toggle: GlToggle) {
+        self.record(GlCommand::Enable(toggle));
+    }
+    fn awsm_disable(&self, toggle: GlToggle) {
+        self.record(GlCommand::Disable(toggle));
+    }
+}
+
+impl PartialWebGlUniforms for MockContext {
+    fn awsm_get_uniform_location(&self, _program: &WebGlProgram, name: &str) -> Result<WebGlUniformLocation, Error> {
+        //see the note at the top - these can't be cached natively
+        Err(Error::from(NativeError::UniformLocation(Some(name.to_owned()))))
+    }
+    fn awsm_upload_uniform_fvec<T: AsRef<[f32]>>(&self, loc: &WebGlUniformLocation, _type: UniformType, data: &T) -> Result<(), Error> {
+        UniformSlice::new(data, _type).upload(self, loc)
+    }
+    fn awsm_upload_uniform_ivec<T: AsRef<[i32]>>(&self, loc: &WebGlUniformLocation, _type: UniformType, data: &T) -> Result<(), Error> {
+        UniformSlice::new(data, _type).upload(self, loc)
+    }
+
+    fn awsm_uniform1f(&self, _loc: &WebGlUniformLocation, x: f32) {
+        self.record(GlCommand::UniformF(vec![x]));
+    }
+    fn awsm_uniform2f(&self, _loc: &WebGlUniformLocation, x: f32, y: f32) {
+        self.record(GlCommand::UniformF(vec![x, y]));
+    }
+    fn awsm_uniform3f(&self, _loc: &WebGlUniformLocation, x: f32, y: f32, z: f32) {
+        self.record(GlCommand::UniformF(vec![x, y, z]));
+    }
+    fn awsm_uniform4f(&self, _loc: &WebGlUniformLocation, x: f32, y: f32, z: f32, w: f32) {
+        self.record(GlCommand::UniformF(vec![x, y, z, w]));
+    }
+
+    fn awsm_uniform1fv_with_f32_array(&self, _loc: &WebGlUniformLocation, data: &[f32]) {
+        self.record(GlCommand::UniformF(data.to_vec()));
+    }
+    fn awsm_uniform2fv_with_f32_array(&self, _loc: &WebGlUniformLocation, data: &[f32]) {
+        self.record(GlCommand::UniformF(data.to_vec()));
+    }
+    fn awsm_uniform3fv_with_f32_array(&self, _loc: &WebGlUniformLocation, data: &[f32]) {
+        self.record(GlCommand::UniformF(data.to_vec()));
+    }
+    fn awsm_uniform4fv_with_f32_array(&self, _loc: &WebGlUniformLocation, data: &[f32]) {
+        self.record(GlCommand::UniformF(data.to_vec()));
+    }
+
+    fn awsm_uniform1i(&self, _loc: &WebGlUniformLocation, x: i32) {
+        self.record(GlCommand::UniformI(vec![x]));
+    }
+    fn awsm_uniform2i(&self, _loc: &WebGlUniformLocation, x: i32, y: i32) {
+        self.record(GlCommand::UniformI(vec![x, y]));
+    }
+    fn awsm_uniform3i(&self, _loc: &WebGlUniformLocation, x: i32, y: i32, z: i32) {
+        self.record(GlCommand::UniformI(vec![x, y, z]));
+    }
+    fn awsm_uniform4i(&self, _loc: &WebGlUniformLocation, x: i32, y: i32, z: i32, w: i32) {
+        self.record(GlCommand::UniformI(vec![x, y, z, w]));
+    }
+
+    fn awsm_uniform1iv_with_i32_array(&self, _loc: &WebGlUniformLocation, data: &[i32]) {
+        self.record(GlCommand::UniformI(data.to_vec()));
+    }
+    fn awsm_uniform2iv_with_i32_array(&self, _loc: &WebGlUniformLocation, data: &[i32]) {
+        self.record(GlCommand::UniformI(data.to_vec()));
+    }
+    fn awsm_uniform3iv_with_i32_array(&self, _loc: &WebGlUniformLocation, data: &[i32]) {
+        self.record(GlCommand::UniformI(data.to_vec()));
+    }
+    fn awsm_uniform4iv_with_i32_array(&self, _loc: &WebGlUniformLocation, data: &[i32]) {
+        self.record(GlCommand::UniformI(data.to_vec()));
+    }
+
+    fn awsm_uniform_matrix2fv_with_f32_array(&self, _loc: &WebGlUniformLocation, transpose: bool, data: &[f32]) {
+        self.record(GlCommand::UniformMatrix { dim: 2, transpose, values: data.to_vec() });
+    }
+    fn awsm_uniform_matrix3fv_with_f32_array(&self, _loc: &WebGlUniformLocation, transpose: bool, data: &[f32]) {
+        self.record(GlCommand::UniformMatrix { dim: 3, transpose, values: data.to_vec() });
+    }
+    fn awsm_uniform_matrix4fv_with_f32_array(&self, _loc: &WebGlUniformLocation, transpose: bool, data: &[f32]) {
+        self.record(GlCommand::UniformMatrix { dim: 4, transpose, values: data.to_vec() });
+    }
+}
+
+impl PartialWebGl2Uniforms for MockContext {
+    fn awsm_upload_uniform_uvec<T: AsRef<[u32]>>(&self, loc: &WebGlUniformLocation, _type: UniformType, data: &T) -> Result<(), Error> {
+        UniformSlice::new(data, _type).upload(self, loc)
+    }
+    fn awsm_uniform1ui(&self, _loc: &WebGlUniformLocation, x: u32) {
+        self.record(GlCommand::UniformU(vec![x]));
+    }
+    fn awsm_uniform2ui(&self, _loc: &WebGlUniformLocation, x: u32, y: u32) {
+        self.record(GlCommand::UniformU(vec![x, y]));
+    }
+    fn awsm_uniform3ui(&self, _loc: &WebGlUniformLocation, x: u32, y: u32, z: u32) {
+        self.record(GlCommand::UniformU(vec![x, y, z]));
+    }
+    fn awsm_uniform4ui(&self, _loc: &WebGlUniformLocation, x: u32, y: u32, z: u32, w: u32) {
+        self.record(GlCommand::UniformU(vec![x, y, z, w]));
+    }
+    fn awsm_uniform1uiv_with_u32_array(&self, _loc: &WebGlUniformLocation, data: &[u32]) {
+        self.record(GlCommand::UniformU(data.to_vec()));
+    }
+    fn awsm_uniform2uiv_with_u32_array(&self, _loc: &WebGlUniformLocation, data: &[u32]) {
+        self.record(GlCommand::UniformU(data.to_vec()));
+    }
+    fn awsm_uniform3uiv_with_u32_array(&self, _loc: &WebGlUniformLocation, data: &[u32]) {
+        self.record(GlCommand::UniformU(data.to_vec()));
+    }
+    fn awsm_uniform4uiv_with_u32_array(&self, _loc: &WebGlUniformLocation, data: &[u32]) {
+        self.record(GlCommand::UniformU(data.to_vec()));
+    }
+    fn awsm_get_uniform_indices(&self, _program: &WebGlProgram, _uniform_names: &[&str]) -> Option<Vec<u32>> {
+        None
+    }
+}
+
+impl PartialWebGl2VertexArray for MockContext {
+    fn awsm_create_vertex_array(&self) -> Result<WebGlVertexArrayObject, Error> {
+        self.record(GlCommand::CreateVertexArray);
+        Ok(self.create(MockObjectKind::VertexArray))
+    }
+    fn awsm_bind_vertex_array(&self, vao: Option<&WebGlVertexArrayObject>) {
+        match vao {
+            Some(_) => self.record(GlCommand::BindVertexArray),
+            None => self.record(GlCommand::ReleaseVertexArray),
+        }
+    }
+    fn awsm_delete_vertex_array(&self, _vao: &WebGlVertexArrayObject) {
+        self.record(GlCommand::DeleteVertexArray);
+        self.delete(MockObjectKind::VertexArray);
+    }
+    fn awsm_vertex_attrib_divisor(&self, loc: u32, divisor: u32) {
+        self.record(GlCommand::VertexAttribDivisor(loc, divisor));
+    }
+}
+
+impl PartialWebGlViewport for MockContext {
+    fn awsm_viewport(&self, x: u32, y: u32, width: u32, height: u32) {
+        self.record(GlCommand::Viewport(x, y, width, height));
+    }
+    fn awsm_drawing_buffer_height(&self) -> u32 {
+        self.drawing_buffer_size.get().1
+    }
+    fn awsm_drawing_buffer_width(&self) -> u32 {
+        self.drawing_buffer_size.get().0
+    }
+}
+
+impl WebGlCommon for MockContext {}
+
+impl WebGl2Extra for MockContext {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{ResizeStrategy, WebGlRenderer};
+
+    fn renderer() -> WebGlRenderer<MockContext> {
+        WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap()
+    }
+
+    #[test]
+    fn test_bind_buffer_dedupe() {
+        let mut renderer = renderer();
+        let a = renderer.create_buffer().unwrap();
+        let b = renderer.create_buffer().unwrap();
+        renderer.gl.clear_commands();
+
+        renderer.bind_buffer(a, BufferTarget::ArrayBuffer).unwrap();
+        renderer.bind_buffer(a, BufferTarget::ArrayBuffer).unwrap();
+        renderer.bind_buffer(a, BufferTarget::ElementArrayBuffer).unwrap();
+        renderer.bind_buffer(b, BufferTarget::ElementArrayBuffer).unwrap();
+
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::BindBuffer(BufferTarget::ArrayBuffer),
+            GlCommand::BindBuffer(BufferTarget::ElementArrayBuffer),
+            GlCommand::BindBuffer(BufferTarget::ElementArrayBuffer),
+        ]);
+    }
+
+    #[test]
+    fn test_activate_program() {
+        let mut renderer = renderer();
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        let a = renderer.compile_program(&[vertex, fragment]).unwrap();
+        let b = renderer.compile_program(&[vertex, fragment]).unwrap();
+        assert_eq!(renderer.gl.objects_created(MockObjectKind::Program), 2);
+        renderer.gl.clear_commands();
+
+        renderer.activate_program(b).unwrap();
+        renderer.activate_program(a).unwrap();
+        renderer.activate_program(a).unwrap();
+
+        assert_eq!(renderer.gl.count_commands(|cmd| *cmd == GlCommand::UseProgram), 1);
+    }
+
+    #[test]
+    fn test_failed_link() {
+        let mut renderer = renderer();
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        renderer.gl.set_link_error(Some("bad link"));
+
+        assert!(renderer.compile_program(&[vertex]).is_err());
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Program), 0);
+        assert_eq!(renderer.gl.count_commands(|cmd| *cmd == GlCommand::DetachShader), 1);
+    }
+
+    #[test]
+    fn test_toggles_and_funcs() {
+        let mut renderer = renderer();
+
+        renderer.toggle(GlToggle::Blend, true);
+        renderer.toggle(GlToggle::Blend, true);
+        renderer.toggle(GlToggle::DepthTest, false);
+        renderer.toggle(GlToggle::Blend, false);
+        renderer.set_depth_func(CmpFunction::Less);
+        renderer.set_depth_func(CmpFunction::Lequal);
+        renderer.set_depth_mask(true);
+        renderer.set_cull_face(CullFaceMode::Back);
+        renderer.set_cull_face(CullFaceMode::Front);
+        renderer.set_front_face(FrontFace::Ccw);
+        renderer.set_polygon_offset(1.0, 1.0);
+        renderer.set_polygon_offset(1.0, 1.0);
+        renderer.set_color_mask(true, true, true, true);
+        renderer.set_color_mask(false, false, false, false);
+        renderer.set_line_width(1.0);
+        renderer.set_sample_coverage(0.5, false);
+
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::Enable(GlToggle::Blend),
+            GlCommand::Disable(GlToggle::Blend),
+            GlCommand::DepthFunc(CmpFunction::Lequal),
+            GlCommand::CullFace(CullFaceMode::Front),
+            GlCommand::PolygonOffset(1.0, 1.0),
+            GlCommand::ColorMask(false, false, false, false),
+            GlCommand::SampleCoverage(0.5, false),
+        ]);
+    }
+
+    #[test]
+    fn test_vertex_array_tracking() {
+        let mut renderer = renderer();
+        let vao = renderer.create_vertex_array().unwrap();
+        let buffer = renderer.create_buffer().unwrap();
+        renderer.gl.clear_commands();
+
+        renderer.activate_vertex_array(vao).unwrap();
+        renderer.activate_vertex_array(vao).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [GlCommand::BindVertexArray]);
+
+        //assigning always leaves the vao released
+        renderer.assign_vertex_array(vao, Some(buffer), &[]).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::BindVertexArray,
+            GlCommand::BindBuffer(BufferTarget::ElementArrayBuffer),
+            GlCommand::ReleaseVertexArray,
+        ]);
+
+        renderer.activate_vertex_array(vao).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [GlCommand::BindVertexArray]);
+    }
+
+    #[test]
+    fn test_resize_viewport() {
+        let mut renderer = renderer();
+        renderer.gl.drawing_buffer_size.set((640, 480));
+
+        assert!(renderer.resize(ResizeStrategy::ViewportMatchSize));
+        assert!(!renderer.resize(ResizeStrategy::ViewportMatchSize));
+        assert_eq!(renderer.get_viewport(), (0, 0, 640, 480));
+        assert_eq!(renderer.gl.take_commands(), [GlCommand::Viewport(0, 0, 640, 480)]);
+    }
+}
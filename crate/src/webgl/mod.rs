@@ -1,32 +1,52 @@
 mod attributes;
 mod base;
 mod buffers;
+mod compressed_textures;
 mod context;
+mod context_loss;
 mod drawing;
 mod enums;
 mod extensions;
 mod funcs;
 mod id;
 mod instancing;
+mod labels;
+mod lifecycle;
 mod misc;
+mod owned;
+mod parallel_compile;
+mod pipeline;
+mod profiler;
 mod query;
 mod shader;
+mod shader_errors;
+mod state;
+mod std140;
+mod stencil;
 mod textures;
 mod toggles;
 mod uniform_buffers;
 mod uniforms;
 mod vertex_arrays;
+mod vertex_layout;
 mod viewport;
 mod framebuffers;
 mod renderbuffers;
 mod read;
+mod reflection;
+mod report;
+mod samplers;
+#[cfg(any(test, feature = "webgl_mock"))]
+mod mock;
 /*
  * (RE)EXPORTS
  */
 pub use self::attributes::*;
 pub use self::base::*;
 pub use self::buffers::*;
+pub use self::compressed_textures::*;
 pub use self::context::*;
+pub use self::context_loss::*;
 pub use self::drawing::*;
 pub use self::enums::*;
 pub use self::extensions::*;
@@ -34,14 +54,30 @@ pub use self::framebuffers::*;
 pub use self::funcs::*;
 pub use self::id::*;
 pub use self::instancing::*;
+pub use self::labels::*;
+pub use self::lifecycle::*;
 pub use self::misc::*;
+pub use self::owned::*;
+pub use self::parallel_compile::*;
+pub use self::pipeline::*;
+pub use self::profiler::*;
+#[cfg(any(test, feature = "webgl_mock"))]
+pub use self::mock::*;
 pub use self::query::*;
 pub use self::read::*;
+pub use self::reflection::*;
+pub use self::report::*;
 pub use self::renderbuffers::*;
+pub use self::samplers::*;
 pub use self::shader::*;
+pub use self::shader_errors::*;
+pub use self::state::*;
+pub use self::std140::*;
+pub use self::stencil::*;
 pub use self::textures::*;
 pub use self::toggles::*;
 pub use self::uniform_buffers::*;
 pub use self::uniforms::*;
 pub use self::vertex_arrays::*;
+pub use self::vertex_layout::*;
 pub use self::viewport::*;
@@ -0,0 +1,225 @@
+use super::{
+    BufferId, FramebufferId, ProgramId, RenderbufferId, SamplerId, ShaderId, TextureId, VaoId,
+    WebGl2Extra, WebGlCommon, WebGlRenderer,
+};
+use crate::errors::{Error, NativeError};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::WebGlRenderingContext;
+
+/*
+ * Optional owning handles
+ *
+ * own() wraps an id so that dropping the handle queues its deletion
+ * The renderer isn't reachable from Drop, so nothing is deleted until process_deletions()
+ * (call it once per frame or so)
+ *
+ * Ids that were already deleted some other way (or by delete_all) are skipped
+ * Shaders that a program still uses stay queued until that program is gone
+ */
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum ResourceId {
+    Buffer(BufferId),
+    Texture(TextureId),
+    Program(ProgramId),
+    Shader(ShaderId),
+    Vao(VaoId),
+    Framebuffer(FramebufferId),
+    Renderbuffer(RenderbufferId),
+    Sampler(SamplerId),
+}
+
+macro_rules! impl_resource_id {
+    ($($id:ident => $variant:ident),*) => {
+        $(impl From<$id> for ResourceId {
+            fn from(id: $id) -> Self {
+                ResourceId::$variant(id)
+            }
+        })*
+    };
+}
+
+impl_resource_id!(
+    BufferId => Buffer,
+    TextureId => Texture,
+    ProgramId => Program,
+    ShaderId => Shader,
+    VaoId => Vao,
+    FramebufferId => Framebuffer,
+    RenderbufferId => Renderbuffer,
+    SamplerId => Sampler
+);
+
+pub(super) type DeletionQueue = Rc<RefCell<Vec<ResourceId>>>;
+
+pub struct Owned<I: Into<ResourceId> + Copy> {
+    id: I,
+    //None once released
+    queue: Option<DeletionQueue>,
+}
+
+impl<I: Into<ResourceId> + Copy> Owned<I> {
+    pub fn id(&self) -> I {
+        self.id
+    }
+
+    //give up ownership, it won't be deleted on drop
+    pub fn release(mut self) -> I {
+        self.queue = None;
+        self.id
+    }
+}
+
+impl<I: Into<ResourceId> + Copy> Drop for Owned<I> {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue.take() {
+            queue.borrow_mut().push(self.id.into());
+        }
+    }
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn own<I: Into<ResourceId> + Copy>(&self, id: I) -> Owned<I> {
+        Owned {
+            id,
+            queue: Some(self.deletion_queue.clone()),
+        }
+    }
+
+    pub fn pending_deletions(&self) -> usize {
+        self.deletion_queue.borrow().len()
+    }
+
+    fn _process_deletions<F>(&mut self, delete_version_specific: F) -> Result<(), Error>
+    where
+        F: Fn(&mut Self, ResourceId) -> Result<(), Error>,
+    {
+        let mut pending: Vec<ResourceId> = self.deletion_queue.borrow_mut().drain(..).collect();
+
+        //programs go first so that their shaders are free to go in the same pass
+        pending.sort_by_key(|id| match id {
+            ResourceId::Program(_) => 0,
+            ResourceId::Shader(_) => 2,
+            _ => 1,
+        });
+
+        let mut still_in_use = Vec::new();
+
+        for id in pending {
+            match id {
+                ResourceId::Program(id) => {
+                    if self.program_lookup.contains(id) {
+                        self.delete_program(id)?;
+                    }
+                }
+                ResourceId::Shader(id) => {
+                    if self.shader_lookup.contains(id) {
+                        match self.delete_shader(id) {
+                            Err(Error::Native(NativeError::ShaderInUse(_))) => {
+                                still_in_use.push(ResourceId::Shader(id))
+                            }
+                            result => result?,
+                        }
+                    }
+                }
+                ResourceId::Buffer(id) => {
+                    if self.buffer_lookup.contains(id) {
+                        self.delete_buffer(id)?;
+                    }
+                }
+                ResourceId::Texture(id) => {
+                    if self.texture_lookup.contains(id) {
+                        self.delete_texture(id)?;
+                    }
+                }
+                ResourceId::Framebuffer(id) => {
+                    if self.framebuffer_lookup.contains(id) {
+                        self.delete_framebuffer(id)?;
+                    }
+                }
+                ResourceId::Renderbuffer(id) => {
+                    if self.renderbuffer_lookup.contains(id) {
+                        self.delete_renderbuffer(id)?;
+                    }
+                }
+                ResourceId::Vao(_) | ResourceId::Sampler(_) => delete_version_specific(self, id)?,
+            }
+        }
+
+        self.deletion_queue.borrow_mut().extend(still_in_use);
+
+        Ok(())
+    }
+}
+
+impl WebGlRenderer<WebGlRenderingContext> {
+    pub fn process_deletions(&mut self) -> Result<(), Error> {
+        self._process_deletions(|renderer, id| match id {
+            ResourceId::Vao(id) if renderer.vao_lookup.contains(id) => renderer.delete_vertex_array(id),
+            //samplers can't be created in webgl1
+            _ => Ok(()),
+        })
+    }
+}
+
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    pub fn process_deletions(&mut self) -> Result<(), Error> {
+        self._process_deletions(|renderer, id| match id {
+            ResourceId::Vao(id) if renderer.vao_lookup.contains(id) => renderer.delete_vertex_array(id),
+            ResourceId::Sampler(id) if renderer.sampler_lookup.contains(id) => renderer.delete_sampler(id),
+            _ => Ok(()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{MockContext, MockObjectKind, ShaderType, WebGlVersion};
+
+    #[test]
+    fn test_owned_handles() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        let program = renderer.compile_program(&[vertex, fragment]).unwrap();
+        let vertex = renderer.own(vertex);
+        let fragment = renderer.own(fragment);
+        let buffer = renderer.create_buffer().unwrap();
+        let buffer = renderer.own(buffer);
+        let texture = renderer.create_texture().unwrap();
+        let kept = renderer.own(texture).release();
+        assert_eq!(kept, texture);
+
+        //nothing happens until the queue is processed
+        drop(buffer);
+        drop(vertex);
+        assert_eq!(renderer.pending_deletions(), 2);
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Buffer), 1);
+
+        //the shader is still linked into a program
+        renderer.process_deletions().unwrap();
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Buffer), 0);
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Shader), 2);
+        assert_eq!(renderer.pending_deletions(), 1);
+
+        drop(renderer.own(program));
+        drop(fragment);
+        renderer.process_deletions().unwrap();
+        assert_eq!(renderer.pending_deletions(), 0);
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Program), 0);
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Shader), 0);
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Texture), 1);
+
+        //already gone
+        let vao = renderer.create_vertex_array().unwrap();
+        let owned_vao = renderer.own(vao);
+        renderer.delete_vertex_array(vao).unwrap();
+        drop(owned_vao);
+        renderer.process_deletions().unwrap();
+        assert_eq!(renderer.pending_deletions(), 0);
+    }
+}
@@ -0,0 +1,229 @@
+use super::shader::{check_program, check_shader, link_program};
+use super::{ProgramId, ShaderId, ProgramQuery, WebGlCommon, WebGlExtension, WebGlRenderer};
+use crate::errors::{Error, NativeError};
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::WebGlProgram;
+
+/*
+ * Linking without stalling the main thread, via KHR_parallel_shader_compile
+ *
+ * begin_compile_program() only kicks off the link,
+ * poll_compile_program() doesn't touch LinkStatus until COMPLETION_STATUS_KHR says it's done
+ * compile_program_async() wraps those up, polling once per animation frame
+ * and only borrowing the renderer while it polls, so rendering (and other compiles) carry on in between
+ *
+ * Shaders from begin_compile_shader() are checked along with the program, once it's done
+ *
+ * Without the extension the first poll just waits on the link, same as compile_program()
+ */
+
+pub struct PendingProgram {
+    program: WebGlProgram,
+    shader_ids: Vec<ShaderId>,
+    parallel: bool,
+}
+
+pub enum ProgramCompileStatus {
+    Ready(ProgramId),
+    Pending(PendingProgram),
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn begin_compile_program(&mut self, shader_ids: &[ShaderId]) -> Result<PendingProgram, Error> {
+        let parallel = self.register_extension(WebGlExtension::KhrParallelShaderCompile.name()).is_ok();
+
+        let shaders = self.get_shaders(shader_ids)?;
+        let program = link_program(&self.gl, &shaders, &self.hardcoded_attribute_locations)?;
+
+        Ok(PendingProgram {
+            program,
+            shader_ids: shader_ids.to_vec(),
+            parallel,
+        })
+    }
+
+    pub fn poll_compile_program(&mut self, pending: PendingProgram) -> Result<ProgramCompileStatus, Error> {
+        if pending.parallel
+            && !self
+                .gl
+                .awsm_get_program_parameter_bool(&pending.program, ProgramQuery::CompletionStatusKhr)?
+        {
+            return Ok(ProgramCompileStatus::Pending(pending));
+        }
+
+        let PendingProgram { program, shader_ids, .. } = pending;
+
+        for shader_id in shader_ids.iter() {
+            let info = self.shader_lookup.try_get_mut(*shader_id, NativeError::MissingShader)?;
+            if !info.checked {
+                if let Err(err) = check_shader(&self.gl, &info.shader, &info.source, info.source_type) {
+                    //the shader itself was deleted by the check
+                    self.shader_lookup.remove(*shader_id);
+                    self.gl.awsm_delete_program(&program);
+                    return Err(err);
+                }
+                info.checked = true;
+            }
+        }
+
+        let shaders = self.get_shaders(&shader_ids)?;
+        let program = check_program(&self.gl, program, &shaders)?;
+
+        self.insert_program(program, &shader_ids)
+            .map(ProgramCompileStatus::Ready)
+    }
+
+    pub async fn compile_program_async(renderer: &Rc<RefCell<Self>>, shader_ids: &[ShaderId]) -> Result<ProgramId, Error> {
+        let mut pending = renderer.borrow_mut().begin_compile_program(shader_ids)?;
+
+        loop {
+            //the borrow has to end before the await
+            let status = renderer.borrow_mut().poll_compile_program(pending)?;
+
+            match status {
+                ProgramCompileStatus::Ready(id) => return Ok(id),
+                ProgramCompileStatus::Pending(still_pending) => {
+                    pending = still_pending;
+                    AnimationFrame::default().await?;
+                }
+            }
+        }
+    }
+}
+
+//resolves on the next requestAnimationFrame
+#[derive(Default)]
+struct AnimationFrame {
+    fired: Rc<Cell<bool>>,
+    waker: Rc<RefCell<Option<Waker>>>,
+    request: Option<(i32, Closure<dyn FnMut(f64)>)>,
+}
+
+impl Future for AnimationFrame {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        if self.fired.get() {
+            return Poll::Ready(Ok(()));
+        }
+
+        *self.waker.borrow_mut() = Some(ctx.waker().clone());
+
+        if self.request.is_none() {
+            let window = web_sys::window().ok_or(Error::from(NativeError::Window))?;
+
+            let closure = {
+                let fired = self.fired.clone();
+                let waker = self.waker.clone();
+                Closure::wrap(Box::new(move |_time| {
+                    fired.set(true);
+                    if let Some(waker) = waker.borrow_mut().take() {
+                        waker.wake();
+                    }
+                }) as Box<dyn FnMut(f64)>)
+            };
+
+            let id = window.request_animation_frame(closure.as_ref().unchecked_ref())?;
+            self.request = Some((id, closure));
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for AnimationFrame {
+    fn drop(&mut self) {
+        if let Some((id, _closure)) = self.request.take() {
+            if !self.fired.get() {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.cancel_animation_frame(id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, MockObjectKind, ShaderType, WebGlVersion};
+
+    #[test]
+    fn test_parallel_compile() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        renderer.gl.add_extension("KHR_parallel_shader_compile");
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+
+        renderer.gl.link_complete.set(false);
+        let mut pending = renderer.begin_compile_program(&[vertex, fragment]).unwrap();
+        for _ in 0..2 {
+            pending = match renderer.poll_compile_program(pending).unwrap() {
+                ProgramCompileStatus::Pending(pending) => pending,
+                ProgramCompileStatus::Ready(_) => panic!("link isn't done yet"),
+            };
+        }
+        assert_eq!(renderer.program_lookup.len(), 0);
+
+        renderer.gl.link_complete.set(true);
+        renderer.gl.clear_commands();
+        match renderer.poll_compile_program(pending).unwrap() {
+            ProgramCompileStatus::Ready(id) => assert_eq!(renderer.current_program_id, Some(id)),
+            ProgramCompileStatus::Pending(_) => panic!("link is done"),
+        }
+        assert_eq!(renderer.gl.take_commands(), [GlCommand::UseProgram]);
+
+        //failures clean up once they're done
+        renderer.gl.set_link_error(Some("bad link"));
+        let pending = renderer.begin_compile_program(&[vertex, fragment]).unwrap();
+        assert!(renderer.poll_compile_program(pending).is_err());
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Program), 1);
+        renderer.gl.set_link_error(None);
+
+        //shader compile errors show up once the program is done, not when the shader is started
+        renderer.gl.set_compile_error(Some("ERROR: 0:1: 'x' : undeclared identifier"));
+        let vertex = renderer.begin_compile_shader("void main() { x; }", ShaderType::Vertex).unwrap();
+        let fragment = renderer.begin_compile_shader("void main() { x; }", ShaderType::Fragment).unwrap();
+        renderer.gl.link_complete.set(false);
+        let pending = match renderer.begin_compile_program(&[vertex, fragment]).and_then(|pending| renderer.poll_compile_program(pending)).unwrap() {
+            ProgramCompileStatus::Pending(pending) => pending,
+            ProgramCompileStatus::Ready(_) => panic!("link isn't done yet"),
+        };
+        renderer.gl.link_complete.set(true);
+        match renderer.poll_compile_program(pending) {
+            Err(Error::Native(NativeError::ShaderCompile(err))) => assert_eq!(err.shader_type, Some(ShaderType::Vertex)),
+            _ => panic!("expected a compile error"),
+        }
+        assert!(renderer.shader_lookup.get(vertex).is_none());
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Program), 1);
+    }
+
+    #[test]
+    fn test_compile_program_async() {
+        let renderer = Rc::new(RefCell::new(WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap()));
+        let (vertex, fragment) = {
+            let mut renderer = renderer.borrow_mut();
+            (
+                renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap(),
+                renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap(),
+            )
+        };
+
+        //without the extension it's ready on the first poll
+        let shader_ids = [vertex, fragment];
+        let mut future = Box::pin(WebGlRenderer::compile_program_async(&renderer, &shader_ids));
+        let id = match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => result.unwrap(),
+            Poll::Pending => panic!("expected the program right away"),
+        };
+        drop(future);
+
+        assert_eq!(renderer.borrow().current_program_id, Some(id));
+    }
+}
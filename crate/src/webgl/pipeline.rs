@@ -0,0 +1,196 @@
+use super::{
+    BlendEquation, BlendFactor, CmpFunction, CullFaceMode, FrontFace, GlToggle, ProgramId,
+    StencilFaceSettings, WebGlCommon, WebGlRenderer,
+};
+use crate::errors::Error;
+use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
+
+/*
+ * A fixed description of everything that's usually set up before a draw call
+ * apply_pipeline() goes through the cached setters, so only what differs hits GL
+ *
+ * Fields left out of a data file fall back to the defaults (which match GL's)
+ * A program_id of None leaves the current program alone
+ * Ids are only meaningful for the renderer that made them, so the program isn't serialized
+ */
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineState {
+    #[serde(skip)]
+    pub program_id: Option<ProgramId>,
+    //None means blending is disabled
+    pub blend: Option<BlendState>,
+    //None means depth testing is disabled
+    pub depth_test: Option<CmpFunction>,
+    pub depth_write: bool,
+    //None means stencil testing is disabled
+    pub stencil: Option<StencilState>,
+    //None means culling is disabled
+    pub cull_face: Option<CullFaceMode>,
+    pub front_face: FrontFace,
+    pub color_mask: (bool, bool, bool, bool),
+}
+
+impl Default for PipelineState {
+    fn default() -> Self {
+        Self {
+            program_id: None,
+            blend: None,
+            depth_test: None,
+            depth_write: true,
+            stencil: None,
+            cull_face: None,
+            front_face: FrontFace::Ccw,
+            color_mask: (true, true, true, true),
+        }
+    }
+}
+
+//ProgramId isn't Hash, so the program is left out of the hash - still consistent with Eq
+impl Hash for PipelineState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.blend.hash(state);
+        self.depth_test.hash(state);
+        self.depth_write.hash(state);
+        self.stencil.hash(state);
+        self.cull_face.hash(state);
+        self.front_face.hash(state);
+        self.color_mask.hash(state);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlendState {
+    pub func: (BlendFactor, BlendFactor, BlendFactor, BlendFactor), //src_rgb, dest_rgb, src_alpha, dest_alpha
+    pub equation: (BlendEquation, BlendEquation), //rgb, alpha
+}
+
+impl BlendState {
+    pub fn new(sfactor: BlendFactor, dfactor: BlendFactor) -> Self {
+        Self {
+            func: (sfactor, dfactor, sfactor, dfactor),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for BlendState {
+    fn default() -> Self {
+        Self {
+            func: (
+                BlendFactor::One,
+                BlendFactor::Zero,
+                BlendFactor::One,
+                BlendFactor::Zero,
+            ),
+            equation: (BlendEquation::Add, BlendEquation::Add),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StencilState {
+    pub front: StencilFaceSettings,
+    pub back: StencilFaceSettings,
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn apply_pipeline(&mut self, pipeline: &PipelineState) -> Result<(), Error> {
+        if let Some(program_id) = pipeline.program_id {
+            self.activate_program(program_id)?;
+        }
+
+        self.toggle(GlToggle::Blend, pipeline.blend.is_some());
+        if let Some(blend) = pipeline.blend {
+            let (src_rgb, dest_rgb, src_alpha, dest_alpha) = blend.func;
+            self.set_blend_func_separate(src_rgb, dest_rgb, src_alpha, dest_alpha);
+            self.set_blend_equation_separate(blend.equation.0, blend.equation.1);
+        }
+
+        self.toggle(GlToggle::DepthTest, pipeline.depth_test.is_some());
+        if let Some(func) = pipeline.depth_test {
+            self.set_depth_func(func);
+        }
+        self.set_depth_mask(pipeline.depth_write);
+
+        self.toggle(GlToggle::StencilTest, pipeline.stencil.is_some());
+        if let Some(stencil) = pipeline.stencil {
+            self.set_stencil_face(CullFaceMode::Front, &stencil.front);
+            self.set_stencil_face(CullFaceMode::Back, &stencil.back);
+        }
+
+        self.toggle(GlToggle::CullFace, pipeline.cull_face.is_some());
+        if let Some(mode) = pipeline.cull_face {
+            self.set_cull_face(mode);
+        }
+        self.set_front_face(pipeline.front_face);
+
+        let (r, g, b, a) = pipeline.color_mask;
+        self.set_color_mask(r, g, b, a);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, ShaderType, WebGlVersion};
+
+    #[test]
+    fn test_apply_pipeline() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        let opaque = PipelineState {
+            depth_test: Some(CmpFunction::Lequal),
+            cull_face: Some(CullFaceMode::Back),
+            ..PipelineState::default()
+        };
+
+        let transparent = PipelineState {
+            blend: Some(BlendState::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha)),
+            depth_write: false,
+            ..opaque
+        };
+
+        renderer.apply_pipeline(&opaque).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::Enable(GlToggle::DepthTest),
+            GlCommand::DepthFunc(CmpFunction::Lequal),
+            GlCommand::Enable(GlToggle::CullFace),
+        ]);
+
+        renderer.apply_pipeline(&opaque).unwrap();
+        assert!(renderer.gl.take_commands().is_empty());
+
+        renderer.apply_pipeline(&transparent).unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::Enable(GlToggle::Blend),
+            GlCommand::BlendFuncSeparate(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha, BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+            GlCommand::DepthMask(false),
+        ]);
+
+        //the plain setter shares the cache with the pipeline
+        renderer.set_blend_func(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha);
+        renderer.apply_pipeline(&opaque).unwrap();
+        renderer.set_blend_func(BlendFactor::One, BlendFactor::One);
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::Disable(GlToggle::Blend),
+            GlCommand::DepthMask(true),
+            GlCommand::BlendFunc(BlendFactor::One, BlendFactor::One),
+        ]);
+
+        //the program belongs to this renderer, so it's left out of the data
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        let program_id = renderer.compile_program(&[vertex, fragment]).unwrap();
+        let with_program = PipelineState { program_id: Some(program_id), ..opaque };
+        let json = serde_json::to_string(&with_program).unwrap();
+        assert!(!json.contains("program_id"));
+        assert_eq!(serde_json::from_str::<PipelineState>(&json).unwrap(), opaque);
+    }
+}
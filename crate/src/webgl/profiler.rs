@@ -0,0 +1,219 @@
+use super::{GlParameter, GlQueryKind, WebGl2Extra, WebGlCommon, WebGlExtension, WebGlRenderer};
+use crate::errors::{Error, NativeError};
+use rustc_hash::FxHashMap;
+use std::collections::VecDeque;
+use web_sys::WebGlQuery;
+
+/*
+ * GPU timings via EXT_disjoint_timer_query_webgl2
+ *
+ * Scopes are timed with TIME_ELAPSED queries, so they can't nest
+ * The same name can be used more than once per frame, it's summed up
+ *
+ * Results only arrive a few frames later, so call end_gpu_frame() once per frame
+ * (e.g. from MainLoop's end callback) and read gpu_timings() whenever
+ *
+ * If the GPU reports a disjoint operation, everything in flight is thrown away
+ */
+
+//frames that never resolve (e.g. the tab was hidden) don't pile up forever
+const MAX_PENDING_FRAMES: usize = 8;
+
+type GpuScope = (String, WebGlQuery);
+
+#[derive(Default)]
+pub(super) struct GpuProfiler {
+    //queries that aren't in flight
+    pool: Vec<WebGlQuery>,
+    open_scope: Option<GpuScope>,
+    frame: Vec<GpuScope>,
+    pending_frames: VecDeque<Vec<GpuScope>>,
+    timings: Option<FxHashMap<String, f64>>,
+}
+
+impl GpuProfiler {
+    fn recycle(&mut self, frame: Vec<GpuScope>) {
+        self.pool.extend(frame.into_iter().map(|(_, query)| query));
+    }
+
+    fn into_queries(self) -> impl Iterator<Item = WebGlQuery> {
+        self.pool
+            .into_iter()
+            .chain(self.open_scope.into_iter().map(|(_, query)| query))
+            .chain(self.frame.into_iter().map(|(_, query)| query))
+            .chain(self.pending_frames.into_iter().flatten().map(|(_, query)| query))
+    }
+}
+
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    pub fn enable_gpu_profiler(&mut self) -> Result<(), Error> {
+        self.register_extension(WebGlExtension::ExtDisjointTimerQueryWebGl2.name())?;
+
+        if self.gpu_profiler.is_none() {
+            self.gpu_profiler = Some(GpuProfiler::default());
+        }
+
+        Ok(())
+    }
+
+    pub fn disable_gpu_profiler(&mut self) {
+        if let Some(profiler) = self.gpu_profiler.take() {
+            if profiler.open_scope.is_some() {
+                self.gl.awsm_end_query(GlQueryKind::TimeElapsed);
+            }
+
+            for query in profiler.into_queries() {
+                self.gl.awsm_delete_query(&query);
+            }
+        }
+    }
+
+    pub fn begin_gpu_scope(&mut self, name: &str) -> Result<(), Error> {
+        let profiler = self
+            .gpu_profiler
+            .as_mut()
+            .ok_or(Error::from(NativeError::GpuProfilerDisabled))?;
+
+        if profiler.open_scope.is_some() {
+            return Err(Error::from(NativeError::GpuScopeOpen));
+        }
+
+        let query = match profiler.pool.pop() {
+            Some(query) => query,
+            None => self.gl.awsm_create_query()?,
+        };
+
+        self.gl.awsm_begin_query(GlQueryKind::TimeElapsed, &query);
+        profiler.open_scope = Some((name.to_string(), query));
+
+        Ok(())
+    }
+
+    pub fn end_gpu_scope(&mut self) -> Result<(), Error> {
+        let profiler = self
+            .gpu_profiler
+            .as_mut()
+            .ok_or(Error::from(NativeError::GpuProfilerDisabled))?;
+
+        let scope = profiler
+            .open_scope
+            .take()
+            .ok_or(Error::from(NativeError::GpuScopeNotOpen))?;
+
+        self.gl.awsm_end_query(GlQueryKind::TimeElapsed);
+        profiler.frame.push(scope);
+
+        Ok(())
+    }
+
+    /// Closes out this frame's scopes and collects any results that are ready
+    pub fn end_gpu_frame(&mut self) -> Result<(), Error> {
+        let profiler = self
+            .gpu_profiler
+            .as_mut()
+            .ok_or(Error::from(NativeError::GpuProfilerDisabled))?;
+
+        if profiler.open_scope.is_some() {
+            return Err(Error::from(NativeError::GpuScopeOpen));
+        }
+
+        let frame = std::mem::take(&mut profiler.frame);
+        if !frame.is_empty() {
+            profiler.pending_frames.push_back(frame);
+        }
+
+        if self.gl.awsm_get_parameter_bool(GlParameter::GpuDisjoint)? {
+            let pending_frames = std::mem::take(&mut profiler.pending_frames);
+            pending_frames.into_iter().for_each(|frame| profiler.recycle(frame));
+            return Ok(());
+        }
+
+        //frames resolve in order, no need to look past the first one that isn't ready
+        while let Some(frame) = profiler.pending_frames.front() {
+            let mut ready = true;
+            for (_, query) in frame.iter() {
+                if !self.gl.awsm_query_available(query)? {
+                    ready = false;
+                    break;
+                }
+            }
+            if !ready {
+                break;
+            }
+
+            let frame = profiler.pending_frames.pop_front().unwrap();
+            let mut timings = FxHashMap::default();
+            for (name, query) in frame.iter() {
+                let nanoseconds = self.gl.awsm_query_result_f64(query)?;
+                *timings.entry(name.clone()).or_insert(0.0) += nanoseconds / 1_000_000.0;
+            }
+            profiler.timings = Some(timings);
+            profiler.recycle(frame);
+        }
+
+        while profiler.pending_frames.len() > MAX_PENDING_FRAMES {
+            let frame = profiler.pending_frames.pop_front().unwrap();
+            profiler.recycle(frame);
+        }
+
+        Ok(())
+    }
+
+    /// Milliseconds per scope name, from the latest frame that has resolved
+    pub fn gpu_timings(&self) -> Option<&FxHashMap<String, f64>> {
+        self.gpu_profiler
+            .as_ref()
+            .and_then(|profiler| profiler.timings.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, MockObjectKind, WebGlVersion};
+
+    #[test]
+    fn test_gpu_profiler() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        assert!(renderer.enable_gpu_profiler().is_err());
+        renderer.gl.add_extension("EXT_disjoint_timer_query_webgl2");
+        renderer.enable_gpu_profiler().unwrap();
+
+        //still pending
+        renderer.gl.query_result.set(None);
+        renderer.begin_gpu_scope("shadows").unwrap();
+        assert!(matches!(renderer.begin_gpu_scope("nested"), Err(Error::Native(NativeError::GpuScopeOpen))));
+        renderer.end_gpu_scope().unwrap();
+        renderer.begin_gpu_scope("main").unwrap();
+        renderer.end_gpu_scope().unwrap();
+        renderer.begin_gpu_scope("main").unwrap();
+        renderer.end_gpu_scope().unwrap();
+        renderer.end_gpu_frame().unwrap();
+        assert!(renderer.gpu_timings().is_none());
+
+        //a few frames later
+        renderer.gl.query_result.set(Some(2_000_000.0));
+        renderer.end_gpu_frame().unwrap();
+        let timings = renderer.gpu_timings().unwrap();
+        assert_eq!(timings.get("shadows"), Some(&2.0));
+        assert_eq!(timings.get("main"), Some(&4.0));
+
+        //recycled rather than created again
+        renderer.gl.clear_commands();
+        renderer.begin_gpu_scope("main").unwrap();
+        renderer.end_gpu_scope().unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::BeginQuery(GlQueryKind::TimeElapsed),
+            GlCommand::EndQuery(GlQueryKind::TimeElapsed),
+        ]);
+
+        //disjoint throws the frame away
+        renderer.gl.set_parameter_usize(GlParameter::GpuDisjoint, 1);
+        renderer.gl.query_result.set(Some(1_000_000.0));
+        renderer.end_gpu_frame().unwrap();
+        assert_eq!(renderer.gpu_timings().unwrap().get("main"), Some(&4.0));
+
+        renderer.disable_gpu_profiler();
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Query), 0);
+    }
+}
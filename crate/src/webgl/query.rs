@@ -1,11 +1,12 @@
 use super::{enums::GlParameter, WebGlRenderer, GlQueryKind, GlQueryResult, GlQueryName};
-use crate::errors::Error;
+use crate::errors::{Error, NativeError};
 use wasm_bindgen::{prelude::JsValue, JsCast};
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext, WebGlQuery};
 
 pub trait PartialWebGlGetParameter {
     fn awsm_get_parameter_usize(&self, query: GlParameter) -> Result<usize, Error>;
     fn awsm_get_parameter_vf32(&self, query: GlParameter) -> Result<Vec<f32>, Error>;
+    fn awsm_get_parameter_bool(&self, query: GlParameter) -> Result<bool, Error>;
 }
 
 macro_rules! impl_context {
@@ -31,6 +32,12 @@ macro_rules! impl_context {
                         .map_err(|err| err.into())
             }
 
+            fn awsm_get_parameter_bool(&self, query:GlParameter) -> Result<bool, Error> {
+                self.get_parameter(query as u32)
+                    .and_then(|value| value.as_bool().ok_or(JsValue::null()))
+                    .map_err(|err| err.into())
+            }
+
             $($defs)*
         })+
     };
@@ -41,6 +48,41 @@ impl_context! {
     WebGl2RenderingContext{}
 }
 
+pub trait PartialWebGl2Query {
+    fn awsm_create_query(&self) -> Result<WebGlQuery, Error>;
+    fn awsm_delete_query(&self, query: &WebGlQuery);
+    fn awsm_begin_query(&self, kind: GlQueryKind, query: &WebGlQuery);
+    fn awsm_end_query(&self, kind: GlQueryKind);
+    fn awsm_query_available(&self, query: &WebGlQuery) -> Result<bool, Error>;
+    //timer results are nanoseconds and can overflow a u32
+    fn awsm_query_result_f64(&self, query: &WebGlQuery) -> Result<f64, Error>;
+}
+
+impl PartialWebGl2Query for WebGl2RenderingContext {
+    fn awsm_create_query(&self) -> Result<WebGlQuery, Error> {
+        self.create_query().ok_or(Error::from(NativeError::QueryCreate))
+    }
+    fn awsm_delete_query(&self, query: &WebGlQuery) {
+        self.delete_query(Some(query));
+    }
+    fn awsm_begin_query(&self, kind: GlQueryKind, query: &WebGlQuery) {
+        self.begin_query(kind as u32, query);
+    }
+    fn awsm_end_query(&self, kind: GlQueryKind) {
+        self.end_query(kind as u32);
+    }
+    fn awsm_query_available(&self, query: &WebGlQuery) -> Result<bool, Error> {
+        self.get_query_parameter(query, GlQueryResult::Available as u32)
+            .as_bool()
+            .ok_or(Error::from("Unable to get query result"))
+    }
+    fn awsm_query_result_f64(&self, query: &WebGlQuery) -> Result<f64, Error> {
+        self.get_query_parameter(query, GlQueryResult::Value as u32)
+            .as_f64()
+            .ok_or(Error::from("Unable to get query result"))
+    }
+}
+
 impl WebGlRenderer<WebGl2RenderingContext> {
     pub fn create_query(&self) -> Result<WebGlQuery, Error> {
         self.gl.create_query().ok_or(Error::from("Unable to create query"))
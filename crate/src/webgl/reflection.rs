@@ -0,0 +1,270 @@
+use super::{
+    ProgramId, ProgramQuery, UniformBlockActiveQuery, UniformBlockQuery, UniformDataType, WebGl2Extra,
+    WebGlCommon, WebGlRenderer,
+};
+use crate::errors::{Error, NativeError};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use wasm_bindgen::JsValue;
+use web_sys::{WebGl2RenderingContext, WebGlProgram, WebGlRenderingContext};
+
+/*
+ * Everything the driver reports about a linked program, in one place
+ *
+ * Uniform arrays are listed once, by their base name, with array_size set
+ * Offsets and strides are only known for uniforms that live in a block (WebGL2)
+ */
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgramInterface {
+    pub attributes: Vec<AttributeInterface>,
+    pub uniforms: Vec<UniformInterface>,
+    pub uniform_blocks: Vec<UniformBlockInterface>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeInterface {
+    pub name: String,
+    pub data_type: UniformDataType,
+    pub array_size: u32,
+    pub location: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniformInterface {
+    pub name: String,
+    pub data_type: UniformDataType,
+    pub array_size: u32,
+    //None for the default block
+    pub block_index: Option<u32>,
+    pub offset: Option<u32>,
+    pub array_stride: Option<u32>,
+    pub matrix_stride: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniformBlockInterface {
+    pub name: String,
+    pub index: u32,
+    pub binding: u32,
+    pub data_size: u32,
+}
+
+//name, type, size - same as WebGlActiveInfo
+pub type ActiveInfo = (String, u32, u32);
+
+pub trait PartialWebGlReflection {
+    fn awsm_get_active_attrib_info(&self, program: &WebGlProgram, index: u32) -> Result<ActiveInfo, Error>;
+    fn awsm_get_active_uniform_info(&self, program: &WebGlProgram, index: u32) -> Result<ActiveInfo, Error>;
+}
+
+pub trait PartialWebGl2Reflection {
+    //-1 where it doesn't apply (e.g. offsets outside of a block)
+    fn awsm_get_active_uniforms_i32(&self, program: &WebGlProgram, indices: &[u32], query: UniformBlockActiveQuery) -> Result<Vec<i32>, Error>;
+    fn awsm_get_active_uniform_block_name(&self, program: &WebGlProgram, index: u32) -> Result<String, Error>;
+    fn awsm_get_active_uniform_block_parameter_u32(&self, program: &WebGlProgram, index: u32, query: UniformBlockQuery) -> Result<u32, Error>;
+}
+
+macro_rules! impl_context {
+    ($($type:ty { $($defs:tt)* })+) => {
+        $(impl PartialWebGlReflection for $type {
+            fn awsm_get_active_attrib_info(&self, program: &WebGlProgram, index: u32) -> Result<ActiveInfo, Error> {
+                self.get_active_attrib(program, index)
+                    .map(|info| (info.name(), info.type_(), info.size() as u32))
+                    .ok_or(Error::from(NativeError::AttributeLocation(None)))
+            }
+            fn awsm_get_active_uniform_info(&self, program: &WebGlProgram, index: u32) -> Result<ActiveInfo, Error> {
+                self.get_active_uniform(program, index)
+                    .map(|info| (info.name(), info.type_(), info.size() as u32))
+                    .ok_or(Error::from(NativeError::UniformLocation(None)))
+            }
+            $($defs)*
+        })+
+    };
+}
+
+impl_context! {
+    WebGlRenderingContext{}
+    WebGl2RenderingContext{}
+}
+
+impl PartialWebGl2Reflection for WebGl2RenderingContext {
+    fn awsm_get_active_uniforms_i32(&self, program: &WebGlProgram, indices: &[u32], query: UniformBlockActiveQuery) -> Result<Vec<i32>, Error> {
+        let indices: JsValue = js_sys::Uint32Array::from(indices).into();
+        let values: js_sys::Array = self.get_active_uniforms(program, &indices, query as u32).into();
+
+        values
+            .iter()
+            .map(|value| value.as_f64().map(|value| value as i32))
+            .collect::<Option<Vec<i32>>>()
+            .ok_or(Error::from(NativeError::JsValueExpectedNumber))
+    }
+    fn awsm_get_active_uniform_block_name(&self, program: &WebGlProgram, index: u32) -> Result<String, Error> {
+        self.get_active_uniform_block_name(program, index)
+            .ok_or(Error::from(NativeError::UniformBufferName))
+    }
+    fn awsm_get_active_uniform_block_parameter_u32(&self, program: &WebGlProgram, index: u32, query: UniformBlockQuery) -> Result<u32, Error> {
+        self.get_active_uniform_block_parameter(program, index, query as u32)?
+            .as_f64()
+            .map(|value| value as u32)
+            .ok_or(Error::from(NativeError::JsValueExpectedNumber))
+    }
+}
+
+fn data_type(name: &str, type_: u32) -> Result<UniformDataType, Error> {
+    UniformDataType::try_from(type_)
+        .map_err(|_| Error::from(format!("unknown data type 0x{:X} for {}", type_, name)))
+}
+
+//the driver lists arrays as name[0]
+fn base_name(name: String) -> String {
+    match name.strip_suffix("[0]") {
+        Some(base) => base.to_string(),
+        None => name,
+    }
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    //attributes and default block uniforms, which is all there is in WebGL1
+    pub(super) fn _reflect_program_common(&self, program: &WebGlProgram) -> Result<ProgramInterface, Error> {
+        let attribute_count = self.gl.awsm_get_program_parameter_u32(program, ProgramQuery::ActiveAttributes)?;
+        let uniform_count = self.gl.awsm_get_program_parameter_u32(program, ProgramQuery::ActiveUniforms)?;
+
+        let attributes = (0..attribute_count)
+            .map(|index| {
+                let (name, type_, size) = self.gl.awsm_get_active_attrib_info(program, index)?;
+
+                Ok(AttributeInterface {
+                    data_type: data_type(&name, type_)?,
+                    array_size: size,
+                    location: self.gl.awsm_get_attribute_location(program, &name)?,
+                    name: base_name(name),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let uniforms = (0..uniform_count)
+            .map(|index| {
+                let (name, type_, size) = self.gl.awsm_get_active_uniform_info(program, index)?;
+
+                Ok(UniformInterface {
+                    data_type: data_type(&name, type_)?,
+                    array_size: size,
+                    name: base_name(name),
+                    block_index: None,
+                    offset: None,
+                    array_stride: None,
+                    matrix_stride: None,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(ProgramInterface {
+            attributes,
+            uniforms,
+            uniform_blocks: Vec::new(),
+        })
+    }
+}
+
+impl WebGlRenderer<WebGlRenderingContext> {
+    pub fn reflect_program(&self, program_id: ProgramId) -> Result<ProgramInterface, Error> {
+        let program_info = self
+            .program_lookup
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
+
+        self._reflect_program_common(&program_info.program)
+    }
+}
+
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    pub fn reflect_program(&self, program_id: ProgramId) -> Result<ProgramInterface, Error> {
+        let program_info = self
+            .program_lookup
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
+        let program = &program_info.program;
+
+        let mut interface = self._reflect_program_common(program)?;
+
+        if !interface.uniforms.is_empty() {
+            let indices: Vec<u32> = (0..interface.uniforms.len() as u32).collect();
+            let query = |query| self.gl.awsm_get_active_uniforms_i32(program, &indices, query);
+            let block_indices = query(UniformBlockActiveQuery::BlockIndex)?;
+            let offsets = query(UniformBlockActiveQuery::Offset)?;
+            let array_strides = query(UniformBlockActiveQuery::ArrayStride)?;
+            let matrix_strides = query(UniformBlockActiveQuery::MatrixStride)?;
+
+            let known = |value: Option<&i32>| value.filter(|value| **value >= 0).map(|value| *value as u32);
+
+            for (index, uniform) in interface.uniforms.iter_mut().enumerate() {
+                uniform.block_index = known(block_indices.get(index));
+                uniform.offset = known(offsets.get(index));
+                uniform.array_stride = known(array_strides.get(index));
+                uniform.matrix_stride = known(matrix_strides.get(index));
+            }
+        }
+
+        let block_count = self.gl.awsm_get_program_parameter_u32(program, ProgramQuery::ActiveUniformBlocks)?;
+
+        interface.uniform_blocks = (0..block_count)
+            .map(|index| {
+                Ok(UniformBlockInterface {
+                    name: self.gl.awsm_get_active_uniform_block_name(program, index)?,
+                    index,
+                    binding: self.gl.awsm_get_active_uniform_block_parameter_u32(program, index, UniformBlockQuery::BindingPoint)?,
+                    data_size: self.gl.awsm_get_active_uniform_block_parameter_u32(program, index, UniformBlockQuery::DataSize)?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(interface)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{MockContext, MockProgramInterface, ShaderType, WebGlVersion};
+
+    #[test]
+    fn test_reflect_program() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        *renderer.gl.program_interface.borrow_mut() = MockProgramInterface {
+            attributes: vec![("a_position".to_string(), UniformDataType::FloatVec3, 1)],
+            uniforms: vec![
+                ("u_color".to_string(), UniformDataType::FloatVec4, 1, [-1, -1, -1, -1]),
+                ("u_model[0]".to_string(), UniformDataType::FloatMat4, 4, [0, 16, 64, 16]),
+            ],
+            uniform_blocks: vec![("camera".to_string(), 272)],
+        };
+
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        let program_id = renderer.compile_program(&[vertex, fragment]).unwrap();
+
+        let interface = renderer.reflect_program(program_id).unwrap();
+        assert_eq!(interface.attributes, [AttributeInterface {
+            name: "a_position".to_string(),
+            data_type: UniformDataType::FloatVec3,
+            array_size: 1,
+            location: 0,
+        }]);
+        assert_eq!(interface.uniforms[0].block_index, None);
+        assert_eq!(interface.uniforms[0].offset, None);
+        assert_eq!(interface.uniforms[1], UniformInterface {
+            name: "u_model".to_string(),
+            data_type: UniformDataType::FloatMat4,
+            array_size: 4,
+            block_index: Some(0),
+            offset: Some(16),
+            array_stride: Some(64),
+            matrix_stride: Some(16),
+        });
+        assert_eq!(interface.uniform_blocks, [UniformBlockInterface {
+            name: "camera".to_string(),
+            index: 0,
+            binding: 0,
+            data_size: 272,
+        }]);
+    }
+}
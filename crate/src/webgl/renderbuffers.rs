@@ -1,5 +1,7 @@
-use super::{Id, WebGlCommon, WebGlRenderer, RenderBufferFormat, PartialWebGlGetParameter, GlParameter};
+use super::{RenderbufferId, WebGlCommon, WebGlRenderer, RenderBufferFormat, PartialWebGlGetParameter, GlParameter};
 use crate::errors::{Error, NativeError};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext, WebGlRenderbuffer};
 
 pub trait PartialWebGlRenderBuffer {
@@ -60,34 +62,69 @@ impl PartialWebGl2RenderBuffer for WebGl2RenderingContext {
     } 
 }
 
+// from the last assign_renderbuffer_storage*() call
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RenderbufferStorage {
+    pub format: RenderBufferFormat,
+    pub width: u32,
+    pub height: u32,
+    //0 if not multisampled
+    pub samples: u32,
+}
+
+impl RenderbufferStorage {
+    pub fn byte_len(&self) -> usize {
+        self.width as usize * self.height as usize * self.samples.max(1) as usize * self.format.bytes_per_pixel() as usize
+    }
+}
+
+pub(super) struct RenderbufferInfo {
+    pub(super) renderbuffer: WebGlRenderbuffer,
+    pub(super) storage: Cell<Option<RenderbufferStorage>>,
+}
 
 impl<T: WebGlCommon> WebGlRenderer<T> {
-    pub fn create_renderbuffer(&mut self) -> Result<Id, Error> {
+    pub fn create_renderbuffer(&mut self) -> Result<RenderbufferId, Error> {
         let renderbuffer = self.gl.awsm_create_renderbuffer()?;
-        let id = self.renderbuffer_lookup.insert(renderbuffer);
+        let id = self.renderbuffer_lookup.insert(RenderbufferInfo {
+            renderbuffer,
+            storage: Cell::new(None),
+        });
+
+        Ok(id)
+    }
 
+    pub fn create_renderbuffer_labeled(&mut self, label: &str) -> Result<RenderbufferId, Error> {
+        let id = self.create_renderbuffer()?;
+        self.set_label(id, label)?;
         Ok(id)
     }
 
-    pub fn get_renderbuffer(&self, renderbuffer_id: Id) -> Result<&WebGlRenderbuffer, Error> {
+    pub fn get_renderbuffer(&self, renderbuffer_id: RenderbufferId) -> Result<&WebGlRenderbuffer, Error> {
+        self
+            .renderbuffer_lookup
+            .try_get(renderbuffer_id, NativeError::MissingRenderBuffer)
+            .map(|info| &info.renderbuffer)
+    }
+
+    pub fn get_renderbuffer_storage(&self, renderbuffer_id: RenderbufferId) -> Result<Option<RenderbufferStorage>, Error> {
         self
             .renderbuffer_lookup
-            .get(renderbuffer_id)
-            .ok_or(Error::from(NativeError::MissingRenderBuffer))
+            .try_get(renderbuffer_id, NativeError::MissingRenderBuffer)
+            .map(|info| info.storage.get())
     }
 
-    pub fn delete_renderbuffer(&mut self, id: Id) -> Result<(), Error> {
+    pub fn delete_renderbuffer(&mut self, id: RenderbufferId) -> Result<(), Error> {
         if Some(id) == self.current_renderbuffer_id.get() {
             self.current_renderbuffer_id.set(None);
             self.gl.awsm_release_renderbuffer();
         }
 
-        let renderbuffer = self
+        let info = self
             .renderbuffer_lookup
-            .get(id)
-            .ok_or(Error::from(NativeError::MissingRenderBuffer))?;
+            .try_get(id, NativeError::MissingRenderBuffer)?;
 
-        self.gl.awsm_delete_renderbuffer(&renderbuffer);
+        self.gl.awsm_delete_renderbuffer(&info.renderbuffer);
 
         self.renderbuffer_lookup.remove(id);
 
@@ -101,26 +138,25 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     //only pub within the module - used elsewhere like attributes
     pub(super) fn _bind_renderbuffer_nocheck(
         &self,
-        renderbuffer_id: Id,
+        renderbuffer_id: RenderbufferId,
     ) -> Result<(), Error> {
         self.current_renderbuffer_id.set(Some(renderbuffer_id));
 
-        let renderbuffer = self
+        let info = self
             .renderbuffer_lookup
-            .get(renderbuffer_id)
-            .ok_or(Error::from(NativeError::MissingRenderBuffer))?;
-        self.gl.awsm_bind_renderbuffer(&renderbuffer);
+            .try_get(renderbuffer_id, NativeError::MissingRenderBuffer)?;
+        self.gl.awsm_bind_renderbuffer(&info.renderbuffer);
 
         Ok(())
     }
 
     #[cfg(feature = "disable_webgl_opt")]
-    pub fn bind_renderbuffer(&self, renderbuffer_id: Id) -> Result<(), Error> {
+    pub fn bind_renderbuffer(&self, renderbuffer_id: RenderbufferId) -> Result<(), Error> {
         self._bind_renderbuffer_nocheck(renderbuffer_id)
     }
 
     #[cfg(not(feature = "disable_webgl_opt"))]
-    pub fn bind_renderbuffer(&self, renderbuffer_id: Id) -> Result<(), Error> {
+    pub fn bind_renderbuffer(&self, renderbuffer_id: RenderbufferId) -> Result<(), Error> {
         if Some(renderbuffer_id) != self.current_renderbuffer_id.get()
         {
             self._bind_renderbuffer_nocheck(renderbuffer_id)
@@ -129,21 +165,29 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
         }
     }
 
-    pub fn assign_renderbuffer_storage(&self, renderbuffer_id: Id, format: RenderBufferFormat, width: u32, height: u32) -> Result<(), Error> {
+    pub fn assign_renderbuffer_storage(&self, renderbuffer_id: RenderbufferId, format: RenderBufferFormat, width: u32, height: u32) -> Result<(), Error> {
         self.bind_renderbuffer(renderbuffer_id)?;
         self.gl.awsm_renderbuffer_storage(format, width, height);
+        self.set_renderbuffer_storage(renderbuffer_id, RenderbufferStorage { format, width, height, samples: 0 });
         Ok(())
     }
+
+    fn set_renderbuffer_storage(&self, renderbuffer_id: RenderbufferId, storage: RenderbufferStorage) {
+        if let Some(info) = self.renderbuffer_lookup.get(renderbuffer_id) {
+            info.storage.set(Some(storage));
+        }
+    }
 }
 
 
 impl WebGlRenderer<WebGl2RenderingContext> {
-    pub fn assign_renderbuffer_storage_multisample(&self, renderbuffer_id: Id, samples: u32, format: RenderBufferFormat, width:u32, height: u32) -> Result<(), Error> {
+    pub fn assign_renderbuffer_storage_multisample(&self, renderbuffer_id: RenderbufferId, samples: u32, format: RenderBufferFormat, width:u32, height: u32) -> Result<(), Error> {
         self.bind_renderbuffer(renderbuffer_id)?;
         self.gl.awsm_renderbuffer_storage_multisample(samples, format, width, height);
+        self.set_renderbuffer_storage(renderbuffer_id, RenderbufferStorage { format, width, height, samples });
         Ok(())
     }
-    pub fn assign_renderbuffer_storage_multisample_max(&self, renderbuffer_id: Id, format: RenderBufferFormat, width:u32, height: u32) -> Result<(), Error> {
+    pub fn assign_renderbuffer_storage_multisample_max(&self, renderbuffer_id: RenderbufferId, format: RenderBufferFormat, width:u32, height: u32) -> Result<(), Error> {
         let max_samples: usize = self.gl.awsm_get_parameter_usize(GlParameter::MaxSamples)?;
         self.assign_renderbuffer_storage_multisample(renderbuffer_id, max_samples as u32, format, width, height)
     }
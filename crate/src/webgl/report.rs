@@ -0,0 +1,174 @@
+use super::{RenderbufferStorage, ResourceId, TextureStorage, TextureTarget, WebGlCommon, WebGlRenderer};
+use serde::{Deserialize, Serialize};
+
+/*
+ * An inventory of everything the renderer holds, with estimated GPU memory
+ *
+ * Bytes are estimates from what went through the renderer:
+ * buffers from their last full upload, textures from their format/size/levels,
+ * renderbuffers from their storage (times samples)
+ * Programs, shaders, vaos, framebuffers and samplers are counted but have no bytes
+ */
+
+//serialize only, the ids aren't meaningful outside of the renderer
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ResourceReport {
+    pub buffers: ResourceTotals,
+    pub textures: ResourceTotals,
+    pub renderbuffers: ResourceTotals,
+    pub framebuffers: ResourceTotals,
+    pub programs: ResourceTotals,
+    pub shaders: ResourceTotals,
+    pub vertex_arrays: ResourceTotals,
+    pub samplers: ResourceTotals,
+    pub total_bytes: usize,
+    pub resources: Vec<ResourceEntry>,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceTotals {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceEntry {
+    pub id: ResourceId,
+    pub bytes: usize,
+    pub storage: Option<ResourceStorage>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ResourceStorage {
+    Texture(TextureTarget, TextureStorage),
+    Renderbuffer(RenderbufferStorage),
+}
+
+impl ResourceReport {
+    fn add(&mut self, id: ResourceId, label: Option<&str>, bytes: usize, storage: Option<ResourceStorage>) {
+        let totals = match id {
+            ResourceId::Buffer(_) => &mut self.buffers,
+            ResourceId::Texture(_) => &mut self.textures,
+            ResourceId::Renderbuffer(_) => &mut self.renderbuffers,
+            ResourceId::Framebuffer(_) => &mut self.framebuffers,
+            ResourceId::Program(_) => &mut self.programs,
+            ResourceId::Shader(_) => &mut self.shaders,
+            ResourceId::Vao(_) => &mut self.vertex_arrays,
+            ResourceId::Sampler(_) => &mut self.samplers,
+        };
+        totals.count += 1;
+        totals.bytes += bytes;
+        self.total_bytes += bytes;
+
+        self.resources.push(ResourceEntry {
+            id,
+            bytes,
+            storage,
+            label: label.map(String::from),
+        });
+    }
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn resource_report(&self) -> ResourceReport {
+        let mut report = ResourceReport::default();
+
+        for (id, info) in self.buffer_lookup.iter_with_id() {
+            report.add(id.into(), self.buffer_lookup.label(id), info.byte_len.get(), None);
+        }
+
+        for (id, info) in self.texture_lookup.iter_with_id() {
+            match (info.bind_target, info.storage) {
+                (Some(bind_target), Some(storage)) => report.add(
+                    id.into(),
+                    self.texture_lookup.label(id),
+                    storage.byte_len(bind_target),
+                    Some(ResourceStorage::Texture(bind_target, storage)),
+                ),
+                _ => report.add(id.into(), self.texture_lookup.label(id), 0, None),
+            }
+        }
+
+        for (id, info) in self.renderbuffer_lookup.iter_with_id() {
+            let storage = info.storage.get();
+            report.add(
+                id.into(),
+                self.renderbuffer_lookup.label(id),
+                storage.map(|storage| storage.byte_len()).unwrap_or(0),
+                storage.map(ResourceStorage::Renderbuffer),
+            );
+        }
+
+        for (id, _) in self.framebuffer_lookup.iter_with_id() {
+            report.add(id.into(), self.framebuffer_lookup.label(id), 0, None);
+        }
+        for (id, _) in self.program_lookup.iter_with_id() {
+            report.add(id.into(), self.program_lookup.label(id), 0, None);
+        }
+        for (id, _) in self.shader_lookup.iter_with_id() {
+            report.add(id.into(), self.shader_lookup.label(id), 0, None);
+        }
+        for (id, _) in self.vao_lookup.iter_with_id() {
+            report.add(id.into(), self.vao_lookup.label(id), 0, None);
+        }
+        for (id, _) in self.sampler_lookup.iter_with_id() {
+            report.add(id.into(), self.sampler_lookup.label(id), 0, None);
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{
+        BufferData, BufferTarget, BufferUsage, MockContext, PixelInternalFormat, RenderBufferFormat, WebGlVersion,
+    };
+
+    #[test]
+    fn test_resource_report() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+
+        let buffer = renderer.create_buffer().unwrap();
+        renderer
+            .upload_buffer(buffer, BufferData::new(&[0.0f32; 16], BufferTarget::ArrayBuffer, BufferUsage::StaticDraw))
+            .unwrap();
+
+        let texture = renderer.create_texture().unwrap();
+        renderer
+            .allocate_texture_storage(texture, TextureTarget::Texture2d, 3, PixelInternalFormat::Rgba8, 8, 4, 1)
+            .unwrap();
+        let cube = renderer.create_texture().unwrap();
+        renderer
+            .allocate_texture_storage(cube, TextureTarget::CubeMap, 1, PixelInternalFormat::Rgba16f, 4, 4, 1)
+            .unwrap();
+        renderer.create_texture().unwrap();
+
+        let renderbuffer = renderer.create_renderbuffer().unwrap();
+        renderer
+            .assign_renderbuffer_storage(renderbuffer, RenderBufferFormat::DepthComponent16, 10, 10)
+            .unwrap();
+        renderer.create_framebuffer().unwrap();
+
+        let report = renderer.resource_report();
+        assert_eq!(report.buffers, ResourceTotals { count: 1, bytes: 64 });
+        //8x4 + 4x2 + 2x1 pixels, then 6 faces of 4x4
+        assert_eq!(report.textures, ResourceTotals { count: 3, bytes: 42 * 4 + 6 * 16 * 8 });
+        assert_eq!(report.renderbuffers, ResourceTotals { count: 1, bytes: 200 });
+        assert_eq!(report.framebuffers, ResourceTotals { count: 1, bytes: 0 });
+        assert_eq!(report.total_bytes, 64 + 168 + 768 + 200);
+        assert_eq!(report.resources.len(), 6);
+
+        let entry = report.resources.iter().find(|entry| entry.id == ResourceId::from(cube)).unwrap();
+        assert!(matches!(entry.storage, Some(ResourceStorage::Texture(TextureTarget::CubeMap, _))));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["total_bytes"], 64 + 168 + 768 + 200);
+        assert_eq!(json["resources"].as_array().unwrap().len(), 6);
+
+        renderer.delete_buffer(buffer).unwrap();
+        assert_eq!(renderer.resource_report().buffers, ResourceTotals::default());
+    }
+}
@@ -0,0 +1,277 @@
+use super::{
+    CmpFunction, TextureId, SamplerId, TextureCompareMode, TextureMagFilter, TextureMinFilter, TextureParameterName,
+    TextureWrapMode, WebGl2Extra, WebGlCommon, WebGlRenderer,
+};
+use crate::errors::{Error, NativeError};
+use web_sys::WebGl2RenderingContext;
+use web_sys::{WebGlSampler, WebGlUniformLocation};
+
+pub trait PartialWebGl2Sampler {
+    fn awsm_create_sampler(&self) -> Result<WebGlSampler, Error>;
+    fn awsm_delete_sampler(&self, sampler: &WebGlSampler);
+    fn awsm_bind_sampler(&self, unit: u32, sampler: Option<&WebGlSampler>);
+    fn awsm_sampler_parameter_i(&self, sampler: &WebGlSampler, pname: TextureParameterName, param: i32);
+    fn awsm_sampler_parameter_f(&self, sampler: &WebGlSampler, pname: TextureParameterName, param: f32);
+}
+
+impl PartialWebGl2Sampler for WebGl2RenderingContext {
+    fn awsm_create_sampler(&self) -> Result<WebGlSampler, Error> {
+        self.create_sampler().ok_or(Error::from(NativeError::SamplerCreate))
+    }
+    fn awsm_delete_sampler(&self, sampler: &WebGlSampler) {
+        self.delete_sampler(Some(sampler));
+    }
+    fn awsm_bind_sampler(&self, unit: u32, sampler: Option<&WebGlSampler>) {
+        self.bind_sampler(unit, sampler);
+    }
+    fn awsm_sampler_parameter_i(&self, sampler: &WebGlSampler, pname: TextureParameterName, param: i32) {
+        self.sampler_parameteri(sampler, pname as u32, param);
+    }
+    fn awsm_sampler_parameter_f(&self, sampler: &WebGlSampler, pname: TextureParameterName, param: f32) {
+        self.sampler_parameterf(sampler, pname as u32, param);
+    }
+}
+
+//Same defaults as SimpleTextureOptions
+//Anything left as None keeps the GL default
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplerOptions {
+    pub wrap_s: Option<TextureWrapMode>,
+    pub wrap_t: Option<TextureWrapMode>,
+    pub wrap_r: Option<TextureWrapMode>,
+    pub filter_min: Option<TextureMinFilter>,
+    pub filter_mag: Option<TextureMagFilter>,
+    pub compare_mode: Option<TextureCompareMode>,
+    pub compare_func: Option<CmpFunction>,
+    pub min_lod: Option<f32>,
+    pub max_lod: Option<f32>,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        Self {
+            wrap_s: Some(TextureWrapMode::ClampToEdge),
+            wrap_t: Some(TextureWrapMode::ClampToEdge),
+            wrap_r: None,
+            filter_min: Some(TextureMinFilter::Linear),
+            filter_mag: Some(TextureMagFilter::Linear),
+            compare_mode: None,
+            compare_func: None,
+            min_lod: None,
+            max_lod: None,
+        }
+    }
+}
+
+impl SamplerOptions {
+    fn merge(&mut self, other: &SamplerOptions) {
+        self.wrap_s = other.wrap_s.or(self.wrap_s);
+        self.wrap_t = other.wrap_t.or(self.wrap_t);
+        self.wrap_r = other.wrap_r.or(self.wrap_r);
+        self.filter_min = other.filter_min.or(self.filter_min);
+        self.filter_mag = other.filter_mag.or(self.filter_mag);
+        self.compare_mode = other.compare_mode.or(self.compare_mode);
+        self.compare_func = other.compare_func.or(self.compare_func);
+        self.min_lod = other.min_lod.or(self.min_lod);
+        self.max_lod = other.max_lod.or(self.max_lod);
+    }
+}
+
+//The options are kept so the sampler can be recreated after a context loss
+pub(super) struct SamplerInfo {
+    pub(super) sampler: WebGlSampler,
+    pub(super) opts: SamplerOptions,
+}
+
+pub(super) fn set_sampler_parameters<T: WebGl2Extra>(gl: &T, sampler: &WebGlSampler, opts: &SamplerOptions) {
+    if let Some(wrap_s) = opts.wrap_s {
+        gl.awsm_sampler_parameter_i(sampler, TextureParameterName::WrapS, wrap_s as i32);
+    }
+    if let Some(wrap_t) = opts.wrap_t {
+        gl.awsm_sampler_parameter_i(sampler, TextureParameterName::WrapT, wrap_t as i32);
+    }
+    if let Some(wrap_r) = opts.wrap_r {
+        gl.awsm_sampler_parameter_i(sampler, TextureParameterName::WrapR, wrap_r as i32);
+    }
+    if let Some(filter_min) = opts.filter_min {
+        gl.awsm_sampler_parameter_i(sampler, TextureParameterName::MinFilter, filter_min as i32);
+    }
+    if let Some(filter_mag) = opts.filter_mag {
+        gl.awsm_sampler_parameter_i(sampler, TextureParameterName::MagFilter, filter_mag as i32);
+    }
+    if let Some(compare_mode) = opts.compare_mode {
+        gl.awsm_sampler_parameter_i(sampler, TextureParameterName::CompareMode, compare_mode as i32);
+    }
+    if let Some(compare_func) = opts.compare_func {
+        gl.awsm_sampler_parameter_i(sampler, TextureParameterName::CompareFunc, compare_func as i32);
+    }
+    if let Some(min_lod) = opts.min_lod {
+        gl.awsm_sampler_parameter_f(sampler, TextureParameterName::MinLod, min_lod);
+    }
+    if let Some(max_lod) = opts.max_lod {
+        gl.awsm_sampler_parameter_f(sampler, TextureParameterName::MaxLod, max_lod);
+    }
+}
+
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    pub fn create_sampler(&mut self, opts: &SamplerOptions) -> Result<SamplerId, Error> {
+        let sampler = self.gl.awsm_create_sampler()?;
+        set_sampler_parameters(&self.gl, &sampler, opts);
+
+        let id = self.sampler_lookup.insert(SamplerInfo {
+            sampler,
+            opts: opts.clone(),
+        });
+
+        Ok(id)
+    }
+
+    pub fn create_sampler_labeled(&mut self, opts: &SamplerOptions, label: &str) -> Result<SamplerId, Error> {
+        let id = self.create_sampler(opts)?;
+        self.set_label(id, label)?;
+        Ok(id)
+    }
+
+    pub fn assign_sampler_options(&mut self, sampler_id: SamplerId, opts: &SamplerOptions) -> Result<(), Error> {
+        let info = self
+            .sampler_lookup
+            .try_get_mut(sampler_id, NativeError::SamplerMissing)?;
+
+        set_sampler_parameters(&self.gl, &info.sampler, opts);
+        //fields left as None keep their current value, so the restore needs to as well
+        info.opts.merge(opts);
+
+        Ok(())
+    }
+
+    pub fn delete_sampler(&mut self, sampler_id: SamplerId) -> Result<(), Error> {
+        let info = self
+            .sampler_lookup
+            .try_get(sampler_id, NativeError::SamplerMissing)?;
+
+        for (unit, current) in self.current_sampler_ids.iter().enumerate() {
+            if current.get() == Some(sampler_id) {
+                self.gl.awsm_bind_sampler(unit as u32, None);
+                current.set(None);
+            }
+        }
+
+        self.gl.awsm_delete_sampler(&info.sampler);
+        self.sampler_lookup.remove(sampler_id);
+
+        Ok(())
+    }
+
+    pub(super) fn _bind_sampler_nocheck(&self, unit: u32, sampler_id: SamplerId) -> Result<(), Error> {
+        let info = self
+            .sampler_lookup
+            .try_get(sampler_id, NativeError::SamplerMissing)?;
+
+        //units past the cached range just always get bound
+        if let Some(current) = self.current_sampler_ids.get(unit as usize) {
+            current.set(Some(sampler_id));
+        }
+
+        self.gl.awsm_bind_sampler(unit, Some(&info.sampler));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "disable_webgl_opt")]
+    pub fn bind_sampler(&self, unit: u32, sampler_id: SamplerId) -> Result<(), Error> {
+        self._bind_sampler_nocheck(unit, sampler_id)
+    }
+
+    #[cfg(not(feature = "disable_webgl_opt"))]
+    pub fn bind_sampler(&self, unit: u32, sampler_id: SamplerId) -> Result<(), Error> {
+        let current = self
+            .current_sampler_ids
+            .get(unit as usize)
+            .and_then(|current| current.get());
+
+        if Some(sampler_id) != current {
+            self._bind_sampler_nocheck(unit, sampler_id)
+        } else {
+            Ok(())
+        }
+    }
+
+    //Go back to the texture's own parameters on this unit
+    pub fn release_sampler(&self, unit: u32) {
+        if let Some(current) = self.current_sampler_ids.get(unit as usize) {
+            current.set(None);
+        }
+
+        self.gl.awsm_bind_sampler(unit, None);
+    }
+
+    pub fn activate_texture_sampler_name_with_sampler(
+        &mut self,
+        texture_id: TextureId,
+        sampler_id: SamplerId,
+        sampler_name: &str,
+    ) -> Result<(), Error> {
+        let sampler_loc = self.get_uniform_location_name(sampler_name)?;
+        //Will assign the slot if necessary too
+        let sampler_index = self.get_sampler_index_name(sampler_name)?;
+        self.activate_texture_sampler_index_with_sampler(texture_id, sampler_id, sampler_loc, sampler_index)
+    }
+
+    pub fn activate_texture_sampler_index_with_sampler(
+        &mut self,
+        texture_id: TextureId,
+        sampler_id: SamplerId,
+        sampler_loc: WebGlUniformLocation,
+        sampler_index: u32,
+    ) -> Result<(), Error> {
+        self.activate_texture_sampler_index(texture_id, sampler_loc, sampler_index)?;
+        self.bind_sampler(sampler_index, sampler_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_sampler_per_unit_cache() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        let nearest = renderer.create_sampler(&SamplerOptions {
+            filter_min: Some(TextureMinFilter::Nearest),
+            filter_mag: Some(TextureMagFilter::Nearest),
+            ..SamplerOptions::default()
+        }).unwrap();
+        let linear = renderer.create_sampler(&SamplerOptions::default()).unwrap();
+        renderer.gl.clear_commands();
+
+        renderer.bind_sampler(0, nearest).unwrap();
+        renderer.bind_sampler(0, nearest).unwrap();
+        renderer.bind_sampler(1, nearest).unwrap();
+        renderer.bind_sampler(0, linear).unwrap();
+        renderer.delete_sampler(nearest).unwrap();
+        renderer.bind_sampler(0, linear).unwrap();
+
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::BindSampler(0),
+            GlCommand::BindSampler(1),
+            GlCommand::BindSampler(0),
+            GlCommand::ReleaseSampler(1),
+            GlCommand::DeleteSampler,
+        ]);
+        assert!(renderer.bind_sampler(2, nearest).is_err());
+
+        //only the given fields change
+        renderer.assign_sampler_options(linear, &SamplerOptions {
+            wrap_s: None,
+            wrap_t: None,
+            filter_min: None,
+            filter_mag: None,
+            max_lod: Some(4.0),
+            ..SamplerOptions::default()
+        }).unwrap();
+        let opts = &renderer.sampler_lookup.get(linear).unwrap().opts;
+        assert_eq!(opts.filter_min, Some(TextureMinFilter::Linear));
+        assert_eq!(opts.max_lod, Some(4.0));
+    }
+}
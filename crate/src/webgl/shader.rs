@@ -1,16 +1,19 @@
-use super::id::Id;
+use super::{ProgramId, ShaderId};
 use super::{
-    ProgramQuery, ShaderQuery, ShaderType,
+    ProgramQuery, ShaderCompileError, ShaderQuery, ShaderType, UniformDataType,
     WebGlCommon, WebGlRenderer,
 };
 use crate::errors::{Error, NativeError};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::BTreeMap;
 use web_sys::{WebGl2RenderingContext, WebGlActiveInfo, WebGlRenderingContext};
 use web_sys::{WebGlProgram, WebGlShader, WebGlUniformLocation};
 use crate::webgl::uniform_buffers::UniformBufferActivation;
 
 pub struct ProgramInfo {
     pub program: WebGlProgram,
+    //kept around for relinking after a context loss
+    pub shader_ids: Vec<ShaderId>,
     pub attribute_lookup: FxHashMap<String, u32>,
     pub uniform_lookup: FxHashMap<String, WebGlUniformLocation>,
     pub texture_sampler_slot_lookup: FxHashMap<String, u32>,
@@ -19,23 +22,36 @@ pub struct ProgramInfo {
     pub uniform_buffer_lookup_location: FxHashMap<String, u32>,
     pub uniform_buffer_lookup_activation: FxHashMap<u32, UniformBufferActivation>,
     pub non_global_ubo_count: u32, 
+
+    //reflected on the first validated upload (see validate_uniforms)
+    pub(super) uniform_types: Option<FxHashMap<String, (UniformDataType, u32)>>,
 }
 
 
 impl ProgramInfo {
-    fn new(program: WebGlProgram) -> Self {
+    pub(super) fn new(program: WebGlProgram, shader_ids: Vec<ShaderId>) -> Self {
         Self {
             program,
+            shader_ids,
             attribute_lookup: FxHashMap::default(),
             uniform_lookup: FxHashMap::default(),
             texture_sampler_slot_lookup: FxHashMap::default(),
             uniform_buffer_lookup_location: FxHashMap::default(),
             uniform_buffer_lookup_activation: FxHashMap::default(),
             non_global_ubo_count: 0, 
+            uniform_types: None,
         }
     }
 }
 
+//The source is kept so the shader can be recompiled after a context loss
+pub(super) struct ShaderInfo {
+    pub(super) shader: WebGlShader,
+    pub(super) source: String,
+    pub(super) source_type: ShaderType,
+    //false until the compile status has been checked (see begin_compile_shader)
+    pub(super) checked: bool,
+}
 
 pub trait PartialWebGlShaders {
     fn awsm_create_program(&self) -> Result<WebGlProgram, Error>;
@@ -177,13 +193,12 @@ impl_context! {
 }
 
 impl<T: WebGlCommon> WebGlRenderer<T> {
-    pub fn activate_program(&mut self, program_id: Id) -> Result<(), Error> {
+    pub fn activate_program(&mut self, program_id: ProgramId) -> Result<(), Error> {
         if Some(program_id) != self.current_program_id {
             self.current_program_id = Some(program_id);
             let program_info = self
                 .program_lookup
-                .get(program_id)
-                .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+                .try_get(program_id, NativeError::MissingShaderProgram)?;
             self.gl.awsm_use_program(&program_info.program);
             Ok(())
         } else {
@@ -192,28 +207,51 @@ impl<T: WebGlCommon> WebGlRenderer<T> {
     }
 
     //Compile the shader - and cache it for later use
-    pub fn compile_shader(&mut self, source:&str, source_type: ShaderType) -> Result<Id, Error> {
+    pub fn compile_shader(&mut self, source:&str, source_type: ShaderType) -> Result<ShaderId, Error> {
         let shader = compile_shader(&self.gl, source, source_type)?;
 
-        Ok(self.shader_lookup.insert(shader))
+        Ok(self.shader_lookup.insert(ShaderInfo {
+            shader,
+            source: source.to_string(),
+            source_type,
+            checked: true,
+        }))
+    }
+
+    //Kicks off the compile without waiting on the result
+    //the compile status is checked when a program using it is done (see poll_compile_program)
+    pub fn begin_compile_shader(&mut self, source:&str, source_type: ShaderType) -> Result<ShaderId, Error> {
+        let shader = begin_compile_shader(&self.gl, source, source_type)?;
+
+        Ok(self.shader_lookup.insert(ShaderInfo {
+            shader,
+            source: source.to_string(),
+            source_type,
+            checked: false,
+        }))
     }
-}
 
-impl WebGlRenderer<WebGlRenderingContext> {
     //Compile the program and cache it for later use
-    pub fn compile_program(&mut self, shaders:&[Id]) -> Result<Id, Error> {
-        let shaders:Vec<&WebGlShader> = 
-            shaders
-                .iter()
-                .map(|id| {
-                    self.shader_lookup
-                        .get(*id)
-                        .ok_or(Error::from("can't get shader for id!"))
-                }).collect::<Result<Vec<&WebGlShader>, Error>>()?;
+    pub fn compile_program(&mut self, shader_ids: &[ShaderId]) -> Result<ProgramId, Error> {
+        let shaders = self.get_shaders(shader_ids)?;
 
         let program = compile_program(&self.gl, &shaders, &self.hardcoded_attribute_locations)?;
 
-        let program_info = ProgramInfo::new(program);
+        self.insert_program(program, shader_ids)
+    }
+
+    pub(super) fn get_shaders(&self, shader_ids: &[ShaderId]) -> Result<Vec<&WebGlShader>, Error> {
+        shader_ids
+            .iter()
+            .map(|id| {
+                self.shader_lookup
+                    .try_get(*id, NativeError::MissingShader)
+                    .map(|info| &info.shader)
+            }).collect()
+    }
+
+    pub(super) fn insert_program(&mut self, program: WebGlProgram, shader_ids: &[ShaderId]) -> Result<ProgramId, Error> {
+        let program_info = ProgramInfo::new(program, shader_ids.to_vec());
 
         let id = self.program_lookup.insert(program_info);
 
@@ -221,32 +259,189 @@ impl WebGlRenderer<WebGlRenderingContext> {
 
         Ok(id)
     }
-}
 
-impl WebGlRenderer<WebGl2RenderingContext> {
-    //Compile the program and cache it for later use
-    pub fn compile_program(&mut self, shaders:&[Id]) -> Result<Id, Error> {
-        let shaders:Vec<&WebGlShader> = 
-            shaders
-                .iter()
-                .map(|id| {
-                    self.shader_lookup
-                        .get(*id)
-                        .ok_or(Error::from("can't get shader for id!"))
-                }).collect::<Result<Vec<&WebGlShader>, Error>>()?;
+    //Preprocess, compile and link - or get the program back if this variant was already compiled
+    //the same define given more than once keeps its last value
+    pub fn compile_program_variant(&mut self, vertex: &str, fragment: &str, defines: &[(&str, &str)]) -> Result<ProgramId, Error> {
+        let define_set:BTreeMap<&str, &str> = defines.iter().copied().collect();
+        let defines:Vec<(&str, &str)> = define_set.into_iter().collect();
 
-        let program = compile_program(&self.gl, &shaders, &self.hardcoded_attribute_locations)?;
+        let vertex_source = self.shader_preprocessor.preprocess("vertex", vertex, &defines)?;
+        let fragment_source = self.shader_preprocessor.preprocess("fragment", fragment, &defines)?;
 
-        let program_info = ProgramInfo::new(program);
+        //keyed on the final sources, so changing a chunk makes a new variant
+        let key = (vertex_source.source.clone(), fragment_source.source.clone());
 
-        let id = self.program_lookup.insert(program_info);
+        if let Some(id) = self.program_variant_lookup.get(&key).copied() {
+            if self.program_lookup.get(id).is_some() {
+                self.activate_program(id)?;
+                return Ok(id);
+            }
+        }
 
-        self.activate_program(id)?;
+        let vertex_id = self
+            .compile_shader(&vertex_source.source, ShaderType::Vertex)
+            .map_err(|err| with_origins(err, &vertex_source))?;
+        let fragment_id = match self.compile_shader(&fragment_source.source, ShaderType::Fragment) {
+            Ok(id) => id,
+            Err(err) => {
+                let _ = self.delete_shader(vertex_id);
+                return Err(with_origins(err, &fragment_source));
+            }
+        };
+        let id = match self.compile_program(&[vertex_id, fragment_id]) {
+            Ok(id) => id,
+            Err(err) => {
+                let _ = self.delete_shader(vertex_id);
+                let _ = self.delete_shader(fragment_id);
+                return Err(err);
+            }
+        };
+
+        self.program_variant_lookup.insert(key, id);
 
         Ok(id)
     }
 }
 
+//point compile errors back at the files and lines before preprocessing
+fn with_origins(err: Error, source: &PreprocessedShader) -> Error {
+    match err {
+        Error::Native(NativeError::ShaderCompile(mut compile_error)) => {
+            compile_error.set_origins(source);
+            Error::from(NativeError::ShaderCompile(compile_error))
+        },
+        err => err
+    }
+}
+
+//vertex and fragment source, after preprocessing
+pub(super) type ProgramVariantKey = (String, String);
+
+/*
+ * Shader preprocessing
+ *
+ * #include "name" pulls in a chunk that was added with add_chunk()
+ * each chunk is only included once per shader, any later includes of it are dropped
+ *
+ * defines are injected right after the #version line (or at the top if there isn't one)
+ * and show up in the line origins as "<defines>"
+ */
+#[derive(Default)]
+pub struct ShaderPreprocessor {
+    chunks: FxHashMap<String, String>,
+}
+
+pub struct PreprocessedShader {
+    pub source: String,
+    //the (file, line) each line of the source came from, lines start at 1
+    pub line_origins: Vec<(String, u32)>,
+}
+
+impl PreprocessedShader {
+    pub fn line_origin(&self, line: u32) -> Option<(&str, u32)> {
+        line.checked_sub(1)
+            .and_then(|index| self.line_origins.get(index as usize))
+            .map(|(file, line)| (file.as_str(), *line))
+    }
+}
+
+type PreprocessedLine = (String, (String, u32));
+
+impl ShaderPreprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_chunk(&mut self, name: &str, source: &str) {
+        self.chunks.insert(name.to_string(), source.to_string());
+    }
+
+    pub fn preprocess(&self, name: &str, source: &str, defines: &[(&str, &str)]) -> Result<PreprocessedShader, Error> {
+        let mut lines:Vec<PreprocessedLine> = Vec::new();
+        self.expand(name, source, &mut vec![name], &mut FxHashSet::default(), &mut lines)?;
+
+        let define_index = lines
+            .iter()
+            .position(|(line, _)| line.trim_start().starts_with("#version"))
+            .map(|index| index + 1)
+            .unwrap_or(0);
+
+        let define_lines = defines.iter().enumerate().map(|(index, (name, value))| {
+            let line = if value.is_empty() {
+                format!("#define {}", name)
+            } else {
+                format!("#define {} {}", name, value)
+            };
+            (line, ("<defines>".to_string(), index as u32 + 1))
+        });
+        lines.splice(define_index..define_index, define_lines);
+
+        let (source_lines, line_origins):(Vec<String>, Vec<(String, u32)>) = lines.into_iter().unzip();
+
+        Ok(PreprocessedShader {
+            source: source_lines.join("\n"),
+            line_origins,
+        })
+    }
+
+    fn expand<'a>(
+        &'a self,
+        name: &'a str,
+        source: &'a str,
+        stack: &mut Vec<&'a str>,
+        included: &mut FxHashSet<&'a str>,
+        lines: &mut Vec<PreprocessedLine>
+    ) -> Result<(), Error> {
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index as u32 + 1;
+            let error = |desc: String| Error::from(NativeError::ShaderPreprocess(Some(format!("{}:{}: {}", name, line_number, desc))));
+
+            match parse_include(line) {
+                None => lines.push((line.to_string(), (name.to_string(), line_number))),
+                Some(None) => return Err(error(format!("malformed include: {}", line.trim()))),
+                Some(Some(chunk_name)) => {
+                    let (chunk_name, chunk) = self
+                        .chunks
+                        .get_key_value(chunk_name)
+                        .ok_or_else(|| error(format!("no chunk named \"{}\"", chunk_name)))?;
+
+                    if stack.contains(&chunk_name.as_str()) {
+                        return Err(error(format!("\"{}\" includes itself", chunk_name)));
+                    }
+
+                    if included.insert(chunk_name.as_str()) {
+                        stack.push(chunk_name);
+                        self.expand(chunk_name, chunk, stack, included, lines)?;
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//None if it's not an include at all, Some(None) if it's an include that can't be parsed
+fn parse_include(line:&str) -> Option<Option<&str>> {
+    let directive = line.trim_start().strip_prefix('#')?.trim_start();
+    let target = directive.strip_prefix("include")?;
+
+    //e.g. #includes
+    if !target.is_empty() && !target.starts_with(char::is_whitespace) && !target.starts_with('"') {
+        return None;
+    }
+
+    let name = target
+        .trim()
+        .strip_prefix('"')
+        .and_then(|target| target.strip_suffix('"'))
+        .filter(|name| !name.is_empty() && !name.contains('"'));
+
+    Some(name)
+}
+
 
 //These are free functions since they might
 //Be useful for compiling shaders and programs
@@ -256,85 +451,116 @@ pub fn compile_program<T: WebGlCommon>(
     shaders: &[&WebGlShader],
     hardcoded_attribute_locations: &FxHashMap<String, u32>
 ) -> Result<WebGlProgram, Error> {
+    let program = link_program(gl, shaders, hardcoded_attribute_locations)?;
 
+    check_program(gl, program, shaders)
+}
 
-    gl.awsm_create_program()
-        .and_then(|program| {
-            //Hardcode our stashed attribute locations
-            //TODO - is this necessary?
-            //I think it is for WebGl1 or if layout isn't specified in WebGl2
-            //So.... yeah?
-            //Anyway it's not expensive to do this...
-            for (name, loc) in hardcoded_attribute_locations {
-                gl.awsm_bind_attrib_location(&program, *loc, name);
-            }
-            
+//Kicks off the link without waiting on the result
+pub(super) fn link_program<T: WebGlCommon>(
+    gl: &T,
+    shaders: &[&WebGlShader],
+    hardcoded_attribute_locations: &FxHashMap<String, u32>
+) -> Result<WebGlProgram, Error> {
+    let program = gl.awsm_create_program()?;
+
+    //Hardcode our stashed attribute locations
+    //TODO - is this necessary?
+    //I think it is for WebGl1 or if layout isn't specified in WebGl2
+    //So.... yeah?
+    //Anyway it's not expensive to do this...
+    for (name, loc) in hardcoded_attribute_locations {
+        gl.awsm_bind_attrib_location(&program, *loc, name);
+    }
+
+    for shader in shaders.iter() {
+        gl.awsm_attach_shader(&program, shader);
+    }
+
+    //Link the program
+    gl.awsm_link_program(&program);
+
+    Ok(program)
+}
+
+//Waits on the link if it's still going, and cleans up if it failed
+pub(super) fn check_program<T: WebGlCommon>(
+    gl: &T,
+    program: WebGlProgram,
+    shaders: &[&WebGlShader],
+) -> Result<WebGlProgram, Error> {
+    check_link_status(gl, &program)
+        .map_err(|err| {
+            //Don't delete the shader - technically a delete will be only marked
+            //for GC, but if this is the _first_ use of it, and we want to use
+            //it after, that will collect it before we get the chance
             for shader in shaders.iter() {
-                gl.awsm_attach_shader(&program, shader);
+                gl.awsm_detach_shader(&program, shader);
             }
 
-            //Link the program
-            gl.awsm_link_program(&program);
-
-            //Check for errors
-            check_status_with(
-                || gl.awsm_get_program_parameter_bool(&program, ProgramQuery::LinkStatus),
-                || gl.awsm_get_program_info_log(&program),
-            )
-                .map_err(|err| {
-                    //Don't delete the shader - technically a delete will be only marked
-                    //for GC, but if this is the _first_ use of it, and we want to use
-                    //it after, that will collect it before we get the chance
-                    for shader in shaders.iter() {
-                        gl.awsm_detach_shader(&program, shader);
-                    }
-
-                    gl.awsm_delete_program(&program);
+            gl.awsm_delete_program(&program);
 
-                    err
-                })
-                .map(|_| program)
+            err
         })
+        .map(|_| program)
 }
 
 pub fn compile_shader<T: WebGlCommon>(
     gl: &T,
     source: &str,
     source_type: ShaderType
+) -> Result<WebGlShader, Error> {
+    let shader = begin_compile_shader(gl, source, source_type)?;
+
+    check_shader(gl, &shader, source, source_type)?;
+
+    Ok(shader)
+
+}
+
+//Kicks off the compile without waiting on the result
+pub(super) fn begin_compile_shader<T: WebGlCommon>(
+    gl: &T,
+    source: &str,
+    source_type: ShaderType
 ) -> Result<WebGlShader, Error> {
     let shader = gl.awsm_create_shader(source_type).ok_or(Error::from("bad shader (unknown error)"))?;
 
     gl.awsm_shader_source(&shader, source);
     gl.awsm_compile_shader(&shader);
-    check_status_with(
-        || gl.awsm_get_shader_parameter_bool(&shader, ShaderQuery::CompileStatus),
-        || gl.awsm_get_shader_info_log(&shader),
-    )?;
 
     Ok(shader)
+}
+
+//Waits on the compile if it's still going, and deletes the shader if it failed
+pub(super) fn check_shader<T: WebGlCommon>(
+    gl: &T,
+    shader: &WebGlShader,
+    source: &str,
+    source_type: ShaderType
+) -> Result<(), Error> {
+    if !gl.awsm_get_shader_parameter_bool(shader, ShaderQuery::CompileStatus)? {
+        let log = gl.awsm_get_shader_info_log(shader).unwrap_or_default();
+        gl.awsm_delete_shader(shader);
+
+        return Err(Error::from(NativeError::ShaderCompile(
+            ShaderCompileError::parse(Some(source_type), source, &log)
+        )));
+    }
 
+    Ok(())
 }
 
-fn check_status_with<T, U>(set_status: T, get_status: U) -> Result<(), Error>
-where
-    T: Fn() -> Result<bool, Error>,
-    U: Fn() -> Option<String>,
-{
-    match set_status() {
-        Ok(flag) => {
-            if !flag {
-                match get_status() {
-                    None => Err(String::from("unknown shader compiler error!")),
-                    Some(err) => Err(err),
-                }
-            } else {
-                Ok(())
-            }
-        }
+fn check_link_status<T: WebGlCommon>(gl: &T, program: &WebGlProgram) -> Result<(), Error> {
+    if !gl.awsm_get_program_parameter_bool(program, ProgramQuery::LinkStatus)? {
+        let log = gl.awsm_get_program_info_log(program).unwrap_or_default();
 
-        Err(err) => Err(err.to_string()),
+        return Err(Error::from(NativeError::ShaderCompile(
+            ShaderCompileError::parse(None, "", &log)
+        )));
     }
-    .map_err(|err| err.into())
+
+    Ok(())
 }
 
 // see: https://developer.mozilla.org/en-US/docs/Web/API/WebGLRenderingContext/getActiveUniform
@@ -390,4 +616,85 @@ mod tests {
     fn test_invalid_uniform_names() {
         assert_eq!(parse_uniform_names("hello[4]", 4), ["hello", "hello[0]", "hello[1]", "hello[2]", "hello[3]"]);
     }
+
+    #[test]
+    fn test_preprocess() {
+        let mut preprocessor = ShaderPreprocessor::new();
+        preprocessor.add_chunk("common", "#include \"math\"\nfloat common() { return PI; }");
+        preprocessor.add_chunk("math", "#define PI 3.14");
+        preprocessor.add_chunk("loop", "#include \"loop\"");
+
+        let shader = preprocessor.preprocess(
+            "main",
+            "#version 300 es\n#include \"common\"\n  #include \"math\"\nvoid main() {}",
+            &[("SHADOWS", ""), ("LIGHTS", "4")]
+        ).unwrap();
+
+        assert_eq!(shader.source, "#version 300 es\n#define SHADOWS\n#define LIGHTS 4\n#define PI 3.14\nfloat common() { return PI; }\nvoid main() {}");
+        assert_eq!(shader.line_origin(3), Some(("<defines>", 2)));
+        assert_eq!(shader.line_origin(5), Some(("common", 2)));
+        assert_eq!(shader.line_origin(6), Some(("main", 4)));
+        assert_eq!(shader.line_origin(7), None);
+
+        let error_desc = |source:&str| match preprocessor.preprocess("main", source, &[]) {
+            Err(Error::Native(NativeError::ShaderPreprocess(desc))) => desc,
+            _ => None,
+        };
+        assert_eq!(error_desc("void main() {}\n#include \"missing\"").unwrap(), "main:2: no chunk named \"missing\"");
+        assert_eq!(error_desc("#include missing").unwrap(), "main:1: malformed include: #include missing");
+        assert_eq!(error_desc("#include \"loop\"").unwrap(), "loop:1: \"loop\" includes itself");
+    }
+
+    #[test]
+    fn test_program_variants() {
+        use crate::webgl::{GlCommand, MockContext, MockObjectKind, WebGlVersion};
+
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        renderer.shader_preprocessor.add_chunk("lighting", "vec3 light() { return vec3(1.0); }");
+        let vertex = "#version 300 es\nvoid main() {}";
+        let fragment = "#version 300 es\n#include \"lighting\"\nvoid main() {}";
+
+        let a = renderer.compile_program_variant(vertex, fragment, &[("SHADOWS", ""), ("LIGHTS", "4")]).unwrap();
+        assert!(renderer.gl.take_commands().contains(&GlCommand::ShaderSource(
+            "#version 300 es\n#define LIGHTS 4\n#define SHADOWS\nvec3 light() { return vec3(1.0); }\nvoid main() {}".to_string()
+        )));
+
+        //same define set in another order
+        let b = renderer.compile_program_variant(vertex, fragment, &[("LIGHTS", "4"), ("SHADOWS", "")]).unwrap();
+        assert_eq!(a, b);
+        assert!(renderer.gl.take_commands().is_empty());
+
+        let c = renderer.compile_program_variant(vertex, fragment, &[("LIGHTS", "4")]).unwrap();
+        assert_ne!(a, c);
+
+        //a repeated define keeps its last value
+        let d = renderer.compile_program_variant(vertex, fragment, &[("LIGHTS", "2"), ("LIGHTS", "4")]).unwrap();
+        assert_eq!(c, d);
+
+        //changing a chunk changes the program
+        renderer.shader_preprocessor.add_chunk("lighting", "vec3 light() { return vec3(0.5); }");
+        let e = renderer.compile_program_variant(vertex, fragment, &[("LIGHTS", "4")]).unwrap();
+        assert_ne!(c, e);
+        renderer.gl.clear_commands();
+
+        //the vertex shader doesn't outlive a failed fragment shader
+        let shaders_alive = renderer.gl.objects_alive(MockObjectKind::Shader);
+        renderer.gl.compile_error_type.set(Some(ShaderType::Fragment));
+        renderer.gl.set_compile_error(Some("ERROR: 0:1: 'x' : undeclared identifier"));
+        assert!(renderer.compile_program_variant(vertex, fragment, &[]).is_err());
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Shader), shaders_alive);
+        renderer.gl.compile_error_type.set(None);
+
+        //errors point back at the chunk
+        renderer.gl.set_compile_error(Some("ERROR: 0:3: 'vec4' : no matching overloaded function found"));
+        match renderer.compile_program_variant(fragment, fragment, &[("SHADOWS", "")]) {
+            Err(Error::Native(NativeError::ShaderCompile(err))) => {
+                assert_eq!(err.shader_type, Some(ShaderType::Vertex));
+                assert_eq!(err.entries[0].origin, Some(("lighting".to_string(), 1)));
+                assert_eq!(err.entries[0].source_lines[2], (3, "vec3 light() { return vec3(0.5); }".to_string()));
+            },
+            _ => panic!("expected a compile error"),
+        }
+        assert_eq!(renderer.gl.objects_alive(MockObjectKind::Shader), shaders_alive);
+    }
 }
@@ -0,0 +1,223 @@
+use super::{PreprocessedShader, ShaderType};
+use std::fmt;
+
+/*
+ * Driver info logs parsed into something that can be lined up with the source
+ *
+ * Recognized formats (anything else is kept as an entry without a line):
+ *   ANGLE / Chrome / Firefox:  ERROR: 0:12: 'foo' : undeclared identifier
+ *   Mesa:                      0:12(5): error: `foo' undeclared
+ *   NVIDIA:                    0(12) : error C1008: undefined variable "foo"
+ */
+
+//how many lines of source to keep on either side of the offending one
+const CONTEXT_LINES: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderCompileError {
+    //None for link errors
+    pub shader_type: Option<ShaderType>,
+    pub entries: Vec<ShaderLogEntry>,
+    //the driver's log as-is
+    pub log: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderLogEntry {
+    pub severity: ShaderLogSeverity,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub message: String,
+    //(line number, text) around the line, if there is one
+    pub source_lines: Vec<(u32, String)>,
+    //the file and line before preprocessing, if it went through the preprocessor
+    pub origin: Option<(String, u32)>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ShaderLogSeverity {
+    Error,
+    Warning,
+}
+
+impl ShaderCompileError {
+    pub fn parse(shader_type: Option<ShaderType>, source: &str, log: &str) -> Self {
+        let source: Vec<&str> = source.lines().collect();
+
+        let entries = log
+            .lines()
+            .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\0'))
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (severity, line_number, column, message) = parse_log_line(line).unwrap_or((
+                    ShaderLogSeverity::Error,
+                    None,
+                    None,
+                    line.to_string(),
+                ));
+
+                let source_lines = line_number
+                    .map(|line_number| {
+                        let first = line_number.saturating_sub(CONTEXT_LINES).max(1);
+                        let last = (line_number + CONTEXT_LINES).min(source.len() as u32);
+                        (first..=last)
+                            .map(|n| (n, source[n as usize - 1].to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                ShaderLogEntry {
+                    severity,
+                    line: line_number,
+                    column,
+                    message,
+                    source_lines,
+                    origin: None,
+                }
+            })
+            .collect();
+
+        Self {
+            shader_type,
+            entries,
+            log: log.to_string(),
+        }
+    }
+
+    //fill in where each line came from before preprocessing
+    pub fn set_origins(&mut self, preprocessed: &PreprocessedShader) {
+        for entry in self.entries.iter_mut() {
+            entry.origin = entry
+                .line
+                .and_then(|line| preprocessed.line_origin(line))
+                .map(|(file, line)| (file.to_string(), line));
+        }
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ShaderLogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.severity == ShaderLogSeverity::Error)
+    }
+}
+
+impl fmt::Display for ShaderCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.shader_type {
+            Some(ShaderType::Vertex) => writeln!(f, "vertex shader failed to compile")?,
+            Some(ShaderType::Fragment) => writeln!(f, "fragment shader failed to compile")?,
+            None => writeln!(f, "program failed to link")?,
+        }
+
+        for entry in self.entries.iter() {
+            let severity = match entry.severity {
+                ShaderLogSeverity::Error => "error",
+                ShaderLogSeverity::Warning => "warning",
+            };
+
+            match (&entry.origin, entry.line, entry.column) {
+                (Some((file, line)), _, _) => writeln!(f, "{}:{}: {}: {}", file, line, severity, entry.message)?,
+                (None, Some(line), Some(column)) => writeln!(f, "{}:{}: {}: {}", line, column, severity, entry.message)?,
+                (None, Some(line), None) => writeln!(f, "{}: {}: {}", line, severity, entry.message)?,
+                (None, None, _) => writeln!(f, "{}: {}", severity, entry.message)?,
+            }
+
+            for (n, text) in entry.source_lines.iter() {
+                let marker = if Some(*n) == entry.line { ">" } else { " " };
+                writeln!(f, "{} {:>4} | {}", marker, n, text)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_severity(s: &str) -> Option<ShaderLogSeverity> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "error" => Some(ShaderLogSeverity::Error),
+        "warning" => Some(ShaderLogSeverity::Warning),
+        _ => None,
+    }
+}
+
+fn parse_log_line(line: &str) -> Option<(ShaderLogSeverity, Option<u32>, Option<u32>, String)> {
+    parse_angle(line)
+        .or_else(|| parse_mesa(line))
+        .or_else(|| parse_nvidia(line))
+}
+
+//ERROR: 0:12: message
+fn parse_angle(line: &str) -> Option<(ShaderLogSeverity, Option<u32>, Option<u32>, String)> {
+    let (severity, rest) = line.split_once(':')?;
+    let severity = parse_severity(severity)?;
+    let (_source, rest) = rest.trim_start().split_once(':')?;
+    let (line_number, message) = rest.split_once(':')?;
+
+    Some((
+        severity,
+        Some(line_number.trim().parse().ok()?),
+        None,
+        message.trim().to_string(),
+    ))
+}
+
+//0:12(5): error: message
+fn parse_mesa(line: &str) -> Option<(ShaderLogSeverity, Option<u32>, Option<u32>, String)> {
+    let (_source, rest) = line.split_once(':')?;
+    let (line_number, rest) = rest.split_once('(')?;
+    let (column, rest) = rest.split_once(')')?;
+    let (severity, message) = rest.strip_prefix(':')?.split_once(':')?;
+
+    Some((
+        parse_severity(severity)?,
+        Some(line_number.trim().parse().ok()?),
+        Some(column.trim().parse().ok()?),
+        message.trim().to_string(),
+    ))
+}
+
+//0(12) : error C1008: message
+fn parse_nvidia(line: &str) -> Option<(ShaderLogSeverity, Option<u32>, Option<u32>, String)> {
+    let (_source, rest) = line.split_once('(')?;
+    let (line_number, rest) = rest.split_once(')')?;
+    let (kind, message) = rest.trim_start().strip_prefix(':')?.split_once(':')?;
+    let severity = parse_severity(kind.split_whitespace().next()?)?;
+
+    Some((
+        severity,
+        Some(line_number.trim().parse().ok()?),
+        None,
+        message.trim().to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_logs() {
+        let source = "#version 300 es\nprecision mediump float;\nout vec4 color;\nvoid main() {\n  color = foo;\n}";
+
+        let log = "ERROR: 0:5: 'foo' : undeclared identifier\nWARNING: 0:2: extension not supported\nERROR: 1 compilation errors.  No code generated.\n\0";
+        let err = ShaderCompileError::parse(Some(ShaderType::Fragment), source, log);
+        assert_eq!(err.entries.len(), 3);
+        assert_eq!(err.entries[0].line, Some(5));
+        assert_eq!(err.entries[0].message, "'foo' : undeclared identifier");
+        assert_eq!(err.entries[0].source_lines.first(), Some(&(3, "out vec4 color;".to_string())));
+        assert_eq!(err.entries[0].source_lines.last(), Some(&(6, "}".to_string())));
+        assert_eq!(err.entries[1].severity, ShaderLogSeverity::Warning);
+        assert_eq!(err.entries[2].line, None);
+        assert_eq!(err.errors().count(), 2);
+
+        let err = ShaderCompileError::parse(Some(ShaderType::Fragment), source, "0:5(11): error: `foo' undeclared");
+        assert_eq!(err.entries[0].line, Some(5));
+        assert_eq!(err.entries[0].column, Some(11));
+        assert_eq!(err.entries[0].message, "`foo' undeclared");
+
+        let err = ShaderCompileError::parse(Some(ShaderType::Fragment), source, "0(5) : error C1008: undefined variable \"foo\"");
+        assert_eq!(err.entries[0].line, Some(5));
+        assert_eq!(err.entries[0].message, "undefined variable \"foo\"");
+        assert_eq!(err.entries[0].source_lines.len(), 4);
+    }
+}
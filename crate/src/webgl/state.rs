@@ -0,0 +1,204 @@
+use super::funcs::FuncSettings;
+use super::misc::MiscSettings;
+use super::stencil::StencilSettings;
+use super::toggles::ToggleFlags;
+use super::viewport::ResizeStrategy;
+use super::{CullFaceMode, FrameBufferTarget, ProgramId, VaoId, FramebufferId, WebGl2Extra, WebGlCommon, WebGlRenderer};
+use crate::errors::{Error, NativeError};
+use web_sys::WebGlRenderingContext;
+
+/*
+ * Snapshots of the cached render state, for handing the renderer off to code that
+ * changes things behind our back (ui overlays, debug drawing, etc.)
+ *
+ * Restoring goes through the regular setters, so only what differs from the snapshot
+ * actually hits GL.
+ *
+ * A snapshot without a program doesn't unbind whatever is current, since nothing can
+ * rely on there being no program.
+ */
+
+#[derive(Clone, PartialEq)]
+pub struct RenderState {
+    toggle_flags: ToggleFlags,
+    func_settings: FuncSettings,
+    misc_settings: MiscSettings,
+    stencil_settings: StencilSettings,
+    viewport: Option<(u32, u32, u32, u32)>,
+    last_resize_strategy: Option<ResizeStrategy>,
+    program_id: Option<ProgramId>,
+    vao_id: Option<VaoId>,
+    framebuffer_id: Option<FramebufferId>,
+    framebuffer_target: Option<FrameBufferTarget>,
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn snapshot_state(&self) -> RenderState {
+        RenderState {
+            toggle_flags: self.toggle_flags.clone(),
+            func_settings: self.func_settings.clone(),
+            misc_settings: self.misc_settings.clone(),
+            stencil_settings: self.stencil_settings,
+            viewport: self.viewport,
+            last_resize_strategy: self.last_resize_strategy,
+            program_id: self.current_program_id,
+            vao_id: self.current_vao_id.get(),
+            framebuffer_id: self.current_framebuffer_id.get(),
+            framebuffer_target: self.current_framebuffer_target.get(),
+        }
+    }
+
+    pub fn push_state(&mut self) {
+        let state = self.snapshot_state();
+        self.state_stack.push(state);
+    }
+
+    //everything but the vertex array, which differs between webgl1 and webgl2
+    fn _restore_state_common(&mut self, state: &RenderState) -> Result<(), Error> {
+        for toggle in ToggleFlags::ALL.iter() {
+            self.toggle(*toggle, state.toggle_flags.get(*toggle));
+        }
+
+        let funcs = &state.func_settings;
+        self.set_depth_func(funcs.depth_func);
+        let (r, g, b, a) = funcs.blend_color;
+        self.set_blend_color(r, g, b, a);
+        let (src_rgb, dest_rgb, src_alpha, dest_alpha) = funcs.blend_func_separate;
+        self.set_blend_func_separate(src_rgb, dest_rgb, src_alpha, dest_alpha);
+        self.set_blend_equation_separate(funcs.blend_equation_separate.0, funcs.blend_equation_separate.1);
+        self.set_cull_face(funcs.cull_face);
+        self.set_front_face(funcs.front_face);
+        self.set_polygon_offset(funcs.polygon_offset.0, funcs.polygon_offset.1);
+
+        let misc = &state.misc_settings;
+        self.set_depth_mask(misc.depth_mask);
+        let (r, g, b, a) = misc.clear_color;
+        self.set_clear_color(r, g, b, a);
+        let (r, g, b, a) = misc.color_mask;
+        self.set_color_mask(r, g, b, a);
+        self.set_line_width(misc.line_width);
+        self.set_sample_coverage(misc.sample_coverage.0, misc.sample_coverage.1);
+
+        self.set_stencil_face(CullFaceMode::Front, &state.stencil_settings.front);
+        self.set_stencil_face(CullFaceMode::Back, &state.stencil_settings.back);
+        self.set_clear_stencil(state.stencil_settings.clear_stencil);
+
+        if let Some((x, y, width, height)) = state.viewport {
+            if self.viewport != state.viewport {
+                self.gl.awsm_viewport(x, y, width, height);
+                self.viewport = state.viewport;
+            }
+        }
+        self.last_resize_strategy = state.last_resize_strategy;
+
+        if let Some(program_id) = state.program_id {
+            self.activate_program(program_id)?;
+        }
+
+        match (state.framebuffer_id, state.framebuffer_target) {
+            (Some(framebuffer_id), Some(target)) => {
+                self.bind_framebuffer(framebuffer_id, target)?;
+            }
+            (None, Some(target)) => {
+                if self.current_framebuffer_id.get().is_some()
+                    || self.current_framebuffer_target.get() != Some(target)
+                {
+                    self.release_framebuffer(target);
+                }
+            }
+            //nothing was bound yet when the snapshot was taken, i.e. the default framebuffer
+            (None, None) => {
+                if self.current_framebuffer_id.get().is_some() {
+                    let target = self.current_framebuffer_target.get().unwrap_or(FrameBufferTarget::FrameBuffer);
+                    self.release_framebuffer(target);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_renderer {
+    ($(($($header:tt)*) { $($defs:tt)* })+) => {
+        $($($header)* {
+            pub fn restore_state(&mut self, state: &RenderState) -> Result<(), Error> {
+                self._restore_state_common(state)?;
+
+                match state.vao_id {
+                    Some(vao_id) => self.activate_vertex_array(vao_id),
+                    None if self.current_vao_id.get().is_some() => self.release_vertex_array(),
+                    None => Ok(()),
+                }
+            }
+
+            pub fn pop_state(&mut self) -> Result<(), Error> {
+                let state = self
+                    .state_stack
+                    .pop()
+                    .ok_or(Error::from(NativeError::StateStackEmpty))?;
+
+                self.restore_state(&state)
+            }
+
+            $($defs)*
+        })+
+    };
+}
+
+impl_renderer! {
+    (impl WebGlRenderer<WebGlRenderingContext>) {}
+    (impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{BlendFactor, GlCommand, GlToggle, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_push_pop_state() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        let vao = renderer.create_vertex_array().unwrap();
+        renderer.toggle(GlToggle::DepthTest, true);
+        renderer.resize(ResizeStrategy::ViewportSize(100, 100));
+        renderer.gl.clear_commands();
+
+        renderer.push_state();
+        renderer.toggle(GlToggle::Blend, true);
+        renderer.toggle(GlToggle::DepthTest, true);
+        renderer.set_blend_func(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha);
+        renderer.resize(ResizeStrategy::ViewportSize(10, 10));
+        renderer.activate_vertex_array(vao).unwrap();
+        renderer.set_stencil_mask_separate(CullFaceMode::Back, 0x00);
+        renderer.gl.clear_commands();
+
+        renderer.pop_state().unwrap();
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::Disable(GlToggle::Blend),
+            GlCommand::BlendFuncSeparate(BlendFactor::One, BlendFactor::Zero, BlendFactor::One, BlendFactor::Zero),
+            GlCommand::StencilMask(CullFaceMode::Back, 0xFFFFFFFF),
+            GlCommand::Viewport(0, 0, 100, 100),
+            GlCommand::ReleaseVertexArray,
+        ]);
+
+        //nothing left to do
+        assert!(matches!(renderer.pop_state(), Err(Error::Native(NativeError::StateStackEmpty))));
+        assert!(renderer.gl.take_commands().is_empty());
+
+        //the resize strategy comes back too
+        assert!(renderer.resize(ResizeStrategy::ViewportSize(10, 10)));
+
+        //a fresh renderer draws to the default framebuffer, which comes back too
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        let framebuffer = renderer.create_framebuffer().unwrap();
+        renderer.push_state();
+        renderer.bind_framebuffer(framebuffer, FrameBufferTarget::DrawFrameBuffer).unwrap();
+        renderer.gl.clear_commands();
+
+        renderer.pop_state().unwrap();
+        assert_eq!(renderer.gl.take_commands(), [GlCommand::ReleaseFrameBuffer(FrameBufferTarget::DrawFrameBuffer)]);
+        assert_eq!(renderer.current_framebuffer_id.get(), None);
+    }
+}
@@ -0,0 +1,483 @@
+use super::{ProgramId, UniformDataType, WebGl2Extra, WebGlCommon, WebGlRenderer};
+use crate::errors::{Error, NativeError};
+
+/*
+ * std140 layout for uniform blocks, computed on the Rust side
+ *
+ * Field names follow what the driver reports:
+ *   arrays of basic types are one field by their base name ("weights")
+ *   arrays of structs are expanded per element ("lights[1].color")
+ *
+ * UboWriter packs values into a zeroed buffer of the block's size
+ * check_uniform_buffer_layout() compares a layout with the driver's offsets
+ */
+
+const VEC4_ALIGN: u32 = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Std140Type {
+    Basic(UniformDataType),
+    Array(Box<Std140Type>, u32),
+    Struct(Vec<(String, Std140Type)>),
+}
+
+impl Std140Type {
+    pub fn array(self, len: u32) -> Self {
+        Std140Type::Array(Box::new(self), len)
+    }
+
+    pub fn new_struct(members: &[(&str, Std140Type)]) -> Self {
+        Std140Type::Struct(
+            members
+                .iter()
+                .map(|(name, member)| (name.to_string(), member.clone()))
+                .collect(),
+        )
+    }
+}
+
+impl From<UniformDataType> for Std140Type {
+    fn from(data_type: UniformDataType) -> Self {
+        Std140Type::Basic(data_type)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Std140Field {
+    pub name: String,
+    pub data_type: UniformDataType,
+    pub offset: u32,
+    //1 and 0 for anything that isn't an array, same as the driver
+    pub array_size: u32,
+    pub array_stride: u32,
+    //0 for anything that isn't a matrix
+    pub matrix_stride: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Std140Layout {
+    pub fields: Vec<Std140Field>,
+    //padded to a multiple of 16
+    pub size: u32,
+}
+
+//which kind of value goes in each component
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Std140Scalar {
+    Float,
+    Int,
+    UnsignedInt,
+    Bool,
+}
+
+//columns, rows, component kind
+fn basic_shape(data_type: UniformDataType) -> Option<(u32, u32, Std140Scalar)> {
+    use UniformDataType::*;
+
+    match data_type {
+        Float => Some((1, 1, Std140Scalar::Float)),
+        FloatVec2 => Some((1, 2, Std140Scalar::Float)),
+        FloatVec3 => Some((1, 3, Std140Scalar::Float)),
+        FloatVec4 => Some((1, 4, Std140Scalar::Float)),
+        Int => Some((1, 1, Std140Scalar::Int)),
+        IntVec2 => Some((1, 2, Std140Scalar::Int)),
+        IntVec3 => Some((1, 3, Std140Scalar::Int)),
+        IntVec4 => Some((1, 4, Std140Scalar::Int)),
+        UnsignedInt => Some((1, 1, Std140Scalar::UnsignedInt)),
+        UnsignedIntVec2 => Some((1, 2, Std140Scalar::UnsignedInt)),
+        UnsignedIntVec3 => Some((1, 3, Std140Scalar::UnsignedInt)),
+        UnsignedIntVec4 => Some((1, 4, Std140Scalar::UnsignedInt)),
+        Bool => Some((1, 1, Std140Scalar::Bool)),
+        BoolVec2 => Some((1, 2, Std140Scalar::Bool)),
+        BoolVec3 => Some((1, 3, Std140Scalar::Bool)),
+        BoolVec4 => Some((1, 4, Std140Scalar::Bool)),
+        //matCxR is C columns of R rows
+        FloatMat2 => Some((2, 2, Std140Scalar::Float)),
+        FloatMat3 => Some((3, 3, Std140Scalar::Float)),
+        FloatMat4 => Some((4, 4, Std140Scalar::Float)),
+        FloatMat2x3 => Some((2, 3, Std140Scalar::Float)),
+        FloatMat2x4 => Some((2, 4, Std140Scalar::Float)),
+        FloatMat3x2 => Some((3, 2, Std140Scalar::Float)),
+        FloatMat3x4 => Some((3, 4, Std140Scalar::Float)),
+        FloatMat4x2 => Some((4, 2, Std140Scalar::Float)),
+        FloatMat4x3 => Some((4, 3, Std140Scalar::Float)),
+        _ => None,
+    }
+}
+
+fn round_up(value: u32, align: u32) -> u32 {
+    (value + align - 1) / align * align
+}
+
+fn layout_error(desc: String) -> Error {
+    Error::from(NativeError::UniformBufferLayout(Some(desc)))
+}
+
+//size and alignment, without emitting fields
+fn size_align(name: &str, member: &Std140Type) -> Result<(u32, u32), Error> {
+    match member {
+        Std140Type::Basic(data_type) => {
+            let (columns, rows, _) = basic_shape(*data_type).ok_or_else(|| {
+                layout_error(format!("{} is a {:?}, which can't be in a uniform block", name, data_type))
+            })?;
+
+            if columns > 1 {
+                //each column is laid out like an array element
+                Ok((columns * VEC4_ALIGN, VEC4_ALIGN))
+            } else {
+                let size = rows * 4;
+                let align = if rows == 3 { 16 } else { size };
+                Ok((size, align))
+            }
+        }
+        Std140Type::Array(element, len) => {
+            let (size, align) = size_align(name, element)?;
+            let stride = round_up(size, round_up(align, VEC4_ALIGN));
+            Ok((stride * len, round_up(align, VEC4_ALIGN)))
+        }
+        Std140Type::Struct(members) => {
+            let mut offset = 0;
+            let mut struct_align = VEC4_ALIGN;
+            for (member_name, member) in members.iter() {
+                let (size, align) = size_align(member_name, member)?;
+                offset = round_up(offset, align) + size;
+                struct_align = struct_align.max(align);
+            }
+            Ok((round_up(offset, struct_align), struct_align))
+        }
+    }
+}
+
+fn push_fields(fields: &mut Vec<Std140Field>, name: String, member: &Std140Type, offset: u32) -> Result<(), Error> {
+    match member {
+        Std140Type::Basic(data_type) => {
+            let (columns, _, _) = basic_shape(*data_type).unwrap_or((1, 1, Std140Scalar::Float));
+            fields.push(Std140Field {
+                name,
+                data_type: *data_type,
+                offset,
+                array_size: 1,
+                array_stride: 0,
+                matrix_stride: if columns > 1 { VEC4_ALIGN } else { 0 },
+            });
+        }
+        Std140Type::Array(element, len) => {
+            let (size, align) = size_align(&name, element)?;
+            let stride = round_up(size, round_up(align, VEC4_ALIGN));
+
+            match element.as_ref() {
+                Std140Type::Basic(data_type) => {
+                    let (columns, _, _) = basic_shape(*data_type).unwrap_or((1, 1, Std140Scalar::Float));
+                    fields.push(Std140Field {
+                        name,
+                        data_type: *data_type,
+                        offset,
+                        array_size: *len,
+                        array_stride: stride,
+                        matrix_stride: if columns > 1 { VEC4_ALIGN } else { 0 },
+                    });
+                }
+                _ => {
+                    for index in 0..*len {
+                        push_fields(fields, format!("{}[{}]", name, index), element, offset + index * stride)?;
+                    }
+                }
+            }
+        }
+        Std140Type::Struct(members) => {
+            let mut member_offset = offset;
+            for (member_name, member) in members.iter() {
+                let (size, align) = size_align(member_name, member)?;
+                member_offset = round_up(member_offset, align);
+                push_fields(fields, format!("{}.{}", name, member_name), member, member_offset)?;
+                member_offset += size;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Std140Layout {
+    pub fn new(members: &[(&str, Std140Type)]) -> Result<Self, Error> {
+        let mut fields = Vec::new();
+        let mut offset = 0;
+
+        for (name, member) in members.iter() {
+            let (size, align) = size_align(name, member)?;
+            offset = round_up(offset, align);
+            push_fields(&mut fields, name.to_string(), member, offset)?;
+            offset += size;
+        }
+
+        Ok(Self {
+            fields,
+            size: round_up(offset, VEC4_ALIGN),
+        })
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Std140Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    //"weights[2]" -> the weights field, starting at element 2
+    fn field_element(&self, name: &str) -> Option<(&Std140Field, u32)> {
+        self.field(name).map(|field| (field, 0)).or_else(|| {
+            let (base, index) = name.strip_suffix(']')?.rsplit_once('[')?;
+            let index = index.parse().ok()?;
+            self.field(base)
+                .filter(|field| index < field.array_size)
+                .map(|field| (field, index))
+        })
+    }
+}
+
+pub struct UboWriter<'a> {
+    layout: &'a Std140Layout,
+    data: Vec<u8>,
+}
+
+impl<'a> UboWriter<'a> {
+    pub fn new(layout: &'a Std140Layout) -> Self {
+        Self {
+            layout,
+            data: vec![0; layout.size as usize],
+        }
+    }
+
+    pub fn set_f32(&mut self, name: &str, values: &[f32]) -> Result<&mut Self, Error> {
+        self.write(name, Std140Scalar::Float, values.len(), |index| values[index].to_le_bytes())
+    }
+
+    pub fn set_i32(&mut self, name: &str, values: &[i32]) -> Result<&mut Self, Error> {
+        self.write(name, Std140Scalar::Int, values.len(), |index| values[index].to_le_bytes())
+    }
+
+    pub fn set_u32(&mut self, name: &str, values: &[u32]) -> Result<&mut Self, Error> {
+        self.write(name, Std140Scalar::UnsignedInt, values.len(), |index| values[index].to_le_bytes())
+    }
+
+    pub fn set_bool(&mut self, name: &str, values: &[bool]) -> Result<&mut Self, Error> {
+        self.write(name, Std140Scalar::Bool, values.len(), |index| (values[index] as u32).to_le_bytes())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    //values are column-major for matrices, and may run over several array elements
+    fn write(&mut self, name: &str, scalar: Std140Scalar, len: usize, get: impl Fn(usize) -> [u8; 4]) -> Result<&mut Self, Error> {
+        let (field, first) = self
+            .layout
+            .field_element(name)
+            .ok_or_else(|| layout_error(format!("no field named {}", name)))?;
+
+        let (columns, rows, field_scalar) = basic_shape(field.data_type)
+            .ok_or_else(|| layout_error(format!("{} can't be written", name)))?;
+
+        if field_scalar != scalar {
+            return Err(layout_error(format!("{} is a {:?}, not {:?}", name, field.data_type, scalar)));
+        }
+
+        let element_len = (columns * rows) as usize;
+        let remaining = (field.array_size - first) as usize;
+        if len == 0 || len % element_len != 0 || len / element_len > remaining {
+            return Err(layout_error(format!("{} can't take {} values", name, len)));
+        }
+
+        for index in 0..len {
+            let element = (index / element_len) as u32;
+            let column = (index % element_len) as u32 / rows;
+            let row = (index % element_len) as u32 % rows;
+
+            let offset = field.offset
+                + (first + element) * field.array_stride
+                + column * field.matrix_stride
+                + row * 4;
+
+            let offset = offset as usize;
+            self.data[offset..offset + 4].copy_from_slice(&get(index));
+        }
+
+        Ok(self)
+    }
+}
+
+impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T> {
+    //compares the layout with what the driver reports for the block
+    pub fn check_uniform_buffer_layout(&self, program_id: ProgramId, block_name: &str, layout: &Std140Layout) -> Result<(), Error> {
+        let interface = self.reflect_program(program_id)?;
+
+        let block = interface
+            .uniform_blocks
+            .iter()
+            .find(|block| block.name == block_name)
+            .ok_or(Error::from(NativeError::UniformBufferMissing(Some(block_name.to_string()))))?;
+
+        if block.data_size < layout.size {
+            return Err(layout_error(format!(
+                "{} is {} bytes but the layout needs {}",
+                block_name, block.data_size, layout.size
+            )));
+        }
+
+        //members of blocks with an instance name are prefixed with the block name
+        let prefix = format!("{}.", block_name);
+
+        for uniform in interface.uniforms.iter().filter(|uniform| uniform.block_index == Some(block.index)) {
+            let name = uniform.name.strip_prefix(&prefix).unwrap_or(&uniform.name);
+
+            let field = layout
+                .field(name)
+                .ok_or_else(|| layout_error(format!("{} has {} but the layout doesn't", block_name, name)))?;
+
+            let reported = (
+                uniform.data_type,
+                uniform.offset.unwrap_or(0),
+                uniform.array_stride.unwrap_or(0),
+                uniform.matrix_stride.unwrap_or(0),
+            );
+            let expected = (field.data_type, field.offset, field.array_stride, field.matrix_stride);
+
+            if reported != expected {
+                return Err(layout_error(format!(
+                    "{} in {} is (type, offset, array stride, matrix stride) {:?} but the layout has {:?}",
+                    name, block_name, reported, expected
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{MockContext, MockProgramInterface, ShaderType, WebGlVersion};
+
+    fn offsets(layout: &Std140Layout) -> Vec<(&str, u32)> {
+        layout.fields.iter().map(|field| (field.name.as_str(), field.offset)).collect()
+    }
+
+    #[test]
+    fn test_std140_layout() {
+        let light = Std140Type::new_struct(&[
+            ("position", UniformDataType::FloatVec3.into()),
+            ("intensity", UniformDataType::Float.into()),
+            ("color", UniformDataType::FloatVec3.into()),
+        ]);
+
+        let layout = Std140Layout::new(&[
+            ("scale", UniformDataType::Float.into()),
+            ("offset", UniformDataType::FloatVec2.into()),
+            ("normal", UniformDataType::FloatMat3.into()),
+            ("weights", Std140Type::from(UniformDataType::Float).array(3)),
+            ("enabled", UniformDataType::Bool.into()),
+            ("lights", light.array(2)),
+            ("tint", UniformDataType::FloatVec3.into()),
+            ("count", UniformDataType::Int.into()),
+        ])
+        .unwrap();
+
+        assert_eq!(offsets(&layout), [
+            ("scale", 0),
+            ("offset", 8),
+            ("normal", 16),
+            ("weights", 64),
+            ("enabled", 112),
+            ("lights[0].position", 128),
+            ("lights[0].intensity", 140),
+            ("lights[0].color", 144),
+            ("lights[1].position", 160),
+            ("lights[1].intensity", 172),
+            ("lights[1].color", 176),
+            ("tint", 192),
+            ("count", 204),
+        ]);
+        assert_eq!(layout.size, 208);
+
+        let weights = layout.field("weights").unwrap();
+        assert_eq!((weights.array_size, weights.array_stride), (3, 16));
+        assert_eq!(layout.field("normal").unwrap().matrix_stride, 16);
+
+        assert!(Std140Layout::new(&[("texture", UniformDataType::Sampler2d.into())]).is_err());
+    }
+
+    #[test]
+    fn test_ubo_writer() {
+        let layout = Std140Layout::new(&[
+            ("offset", UniformDataType::FloatVec2.into()),
+            ("normal", UniformDataType::FloatMat3.into()),
+            ("weights", Std140Type::from(UniformDataType::Float).array(3)),
+            ("enabled", UniformDataType::Bool.into()),
+        ])
+        .unwrap();
+
+        let mut writer = UboWriter::new(&layout);
+        writer
+            .set_f32("offset", &[1.0, 2.0]).unwrap()
+            .set_f32("normal", &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]).unwrap()
+            .set_f32("weights[1]", &[0.5, 0.25]).unwrap()
+            .set_bool("enabled", &[true]).unwrap();
+
+        let floats: Vec<f32> = writer
+            .as_bytes()
+            .chunks(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+
+        assert_eq!(floats.len(), 32);
+        assert_eq!(&floats[0..2], [1.0, 2.0]);
+        //columns padded to vec4
+        assert_eq!(&floats[4..16], [1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 0.0, 7.0, 8.0, 9.0, 0.0]);
+        assert_eq!([floats[16], floats[20], floats[24]], [0.0, 0.5, 0.25]);
+        assert_eq!(&writer.as_bytes()[112..116], 1u32.to_le_bytes());
+
+        assert!(writer.set_f32("offset", &[1.0, 2.0, 3.0]).is_err());
+        assert!(writer.set_i32("offset", &[1, 2]).is_err());
+        assert!(writer.set_f32("weights[2]", &[1.0, 1.0]).is_err());
+        assert!(writer.set_f32("missing", &[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_check_uniform_buffer_layout() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        *renderer.gl.program_interface.borrow_mut() = MockProgramInterface {
+            attributes: Vec::new(),
+            uniforms: vec![
+                ("u_color".to_string(), UniformDataType::FloatVec4, 1, [-1, -1, -1, -1]),
+                ("camera.view".to_string(), UniformDataType::FloatMat4, 1, [0, 0, 0, 16]),
+                ("camera.position".to_string(), UniformDataType::FloatVec3, 1, [0, 64, 0, 0]),
+                ("camera.near_far[0]".to_string(), UniformDataType::Float, 2, [0, 80, 16, 0]),
+            ],
+            uniform_blocks: vec![("camera".to_string(), 112)],
+        };
+
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        let program_id = renderer.compile_program(&[vertex, fragment]).unwrap();
+
+        let layout = Std140Layout::new(&[
+            ("view", UniformDataType::FloatMat4.into()),
+            ("position", UniformDataType::FloatVec3.into()),
+            ("near_far", Std140Type::from(UniformDataType::Float).array(2)),
+        ])
+        .unwrap();
+        assert!(renderer.check_uniform_buffer_layout(program_id, "camera", &layout).is_ok());
+
+        //position isn't padded out to a vec4 here
+        let packed = Std140Layout::new(&[
+            ("view", UniformDataType::FloatMat4.into()),
+            ("position", UniformDataType::FloatVec3.into()),
+            ("near", UniformDataType::Float.into()),
+            ("far", UniformDataType::Float.into()),
+        ])
+        .unwrap();
+        assert!(renderer.check_uniform_buffer_layout(program_id, "camera", &packed).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use super::{CmpFunction, CullFaceMode, StencilOp, WebGlCommon, WebGlRenderer};
+use serde::{Deserialize, Serialize};
+use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
+
+pub trait PartialWebGlStencil {
+    fn awsm_stencil_func(&self, func: CmpFunction, reference: i32, mask: u32);
+    fn awsm_stencil_func_separate(&self, face: CullFaceMode, func: CmpFunction, reference: i32, mask: u32);
+    fn awsm_stencil_op(&self, fail: StencilOp, zfail: StencilOp, zpass: StencilOp);
+    fn awsm_stencil_op_separate(&self, face: CullFaceMode, fail: StencilOp, zfail: StencilOp, zpass: StencilOp);
+    fn awsm_stencil_mask(&self, mask: u32);
+    fn awsm_stencil_mask_separate(&self, face: CullFaceMode, mask: u32);
+    fn awsm_clear_stencil(&self, s: i32);
+}
+
+macro_rules! impl_context {
+    ($($type:ty { $($defs:tt)* })+) => {
+        $(impl PartialWebGlStencil for $type {
+            fn awsm_stencil_func(&self, func:CmpFunction, reference:i32, mask:u32) {
+                self.stencil_func(func as u32, reference, mask);
+            }
+            fn awsm_stencil_func_separate(&self, face:CullFaceMode, func:CmpFunction, reference:i32, mask:u32) {
+                self.stencil_func_separate(face as u32, func as u32, reference, mask);
+            }
+            fn awsm_stencil_op(&self, fail:StencilOp, zfail:StencilOp, zpass:StencilOp) {
+                self.stencil_op(fail as u32, zfail as u32, zpass as u32);
+            }
+            fn awsm_stencil_op_separate(&self, face:CullFaceMode, fail:StencilOp, zfail:StencilOp, zpass:StencilOp) {
+                self.stencil_op_separate(face as u32, fail as u32, zfail as u32, zpass as u32);
+            }
+            fn awsm_stencil_mask(&self, mask:u32) {
+                self.stencil_mask(mask);
+            }
+            fn awsm_stencil_mask_separate(&self, face:CullFaceMode, mask:u32) {
+                self.stencil_mask_separate(face as u32, mask);
+            }
+            fn awsm_clear_stencil(&self, s:i32) {
+                self.clear_stencil(s);
+            }
+            $($defs)*
+        })+
+    };
+}
+
+impl_context! {
+    WebGlRenderingContext{}
+    WebGl2RenderingContext{}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StencilFaceSettings {
+    pub func: (CmpFunction, i32, u32), //func, reference, mask
+    pub op: (StencilOp, StencilOp, StencilOp), //fail, zfail, zpass
+    pub write_mask: u32,
+}
+
+impl Default for StencilFaceSettings {
+    fn default() -> Self {
+        Self {
+            func: (CmpFunction::Always, 0, 0xFFFFFFFF),
+            op: (StencilOp::Keep, StencilOp::Keep, StencilOp::Keep),
+            write_mask: 0xFFFFFFFF,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct StencilSettings {
+    pub front: StencilFaceSettings,
+    pub back: StencilFaceSettings,
+    pub clear_stencil: i32,
+}
+
+impl StencilSettings {
+    //true if any of the faces would change
+    fn update<V: PartialEq + Copy>(
+        &mut self,
+        face: CullFaceMode,
+        value: V,
+        field: impl Fn(&mut StencilFaceSettings) -> &mut V,
+    ) -> bool {
+        let mut changed = false;
+
+        if face != CullFaceMode::Back && *field(&mut self.front) != value {
+            *field(&mut self.front) = value;
+            changed = true;
+        }
+        if face != CullFaceMode::Front && *field(&mut self.back) != value {
+            *field(&mut self.back) = value;
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+impl<T: WebGlCommon> WebGlRenderer<T> {
+    pub fn set_stencil_func(&mut self, func: CmpFunction, reference: i32, mask: u32) {
+        if self.stencil_settings.update(CullFaceMode::FrontAndBack, (func, reference, mask), |face| &mut face.func) {
+            self.gl.awsm_stencil_func(func, reference, mask);
+        }
+    }
+
+    pub fn set_stencil_func_separate(&mut self, face: CullFaceMode, func: CmpFunction, reference: i32, mask: u32) {
+        if self.stencil_settings.update(face, (func, reference, mask), |face| &mut face.func) {
+            self.gl.awsm_stencil_func_separate(face, func, reference, mask);
+        }
+    }
+
+    pub fn set_stencil_op(&mut self, fail: StencilOp, zfail: StencilOp, zpass: StencilOp) {
+        if self.stencil_settings.update(CullFaceMode::FrontAndBack, (fail, zfail, zpass), |face| &mut face.op) {
+            self.gl.awsm_stencil_op(fail, zfail, zpass);
+        }
+    }
+
+    pub fn set_stencil_op_separate(&mut self, face: CullFaceMode, fail: StencilOp, zfail: StencilOp, zpass: StencilOp) {
+        if self.stencil_settings.update(face, (fail, zfail, zpass), |face| &mut face.op) {
+            self.gl.awsm_stencil_op_separate(face, fail, zfail, zpass);
+        }
+    }
+
+    pub fn set_stencil_mask(&mut self, mask: u32) {
+        if self.stencil_settings.update(CullFaceMode::FrontAndBack, mask, |face| &mut face.write_mask) {
+            self.gl.awsm_stencil_mask(mask);
+        }
+    }
+
+    pub fn set_stencil_mask_separate(&mut self, face: CullFaceMode, mask: u32) {
+        if self.stencil_settings.update(face, mask, |face| &mut face.write_mask) {
+            self.gl.awsm_stencil_mask_separate(face, mask);
+        }
+    }
+
+    //all of a single face's settings at once
+    pub fn set_stencil_face(&mut self, face: CullFaceMode, settings: &StencilFaceSettings) {
+        let (func, reference, mask) = settings.func;
+        self.set_stencil_func_separate(face, func, reference, mask);
+        let (fail, zfail, zpass) = settings.op;
+        self.set_stencil_op_separate(face, fail, zfail, zpass);
+        self.set_stencil_mask_separate(face, settings.write_mask);
+    }
+
+    pub fn set_clear_stencil(&mut self, s: i32) {
+        if self.stencil_settings.clear_stencil != s {
+            self.gl.awsm_clear_stencil(s);
+            self.stencil_settings.clear_stencil = s;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+
+    #[test]
+    fn test_stencil_dedupe() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::One)).unwrap();
+
+        renderer.set_stencil_func(CmpFunction::Always, 1, 0xFF);
+        renderer.set_stencil_func(CmpFunction::Always, 1, 0xFF);
+        //already the case for the front
+        renderer.set_stencil_func_separate(CullFaceMode::Front, CmpFunction::Always, 1, 0xFF);
+        renderer.set_stencil_func_separate(CullFaceMode::Back, CmpFunction::NotEqual, 1, 0xFF);
+        //back differs, so it still goes through
+        renderer.set_stencil_func(CmpFunction::Always, 1, 0xFF);
+        renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Keep);
+        renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Replace);
+        renderer.set_stencil_mask_separate(CullFaceMode::FrontAndBack, 0xFFFFFFFF);
+        renderer.set_stencil_mask(0x00);
+        renderer.set_clear_stencil(0);
+
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::StencilFunc(CullFaceMode::FrontAndBack, CmpFunction::Always, 1, 0xFF),
+            GlCommand::StencilFunc(CullFaceMode::Back, CmpFunction::NotEqual, 1, 0xFF),
+            GlCommand::StencilFunc(CullFaceMode::FrontAndBack, CmpFunction::Always, 1, 0xFF),
+            GlCommand::StencilOp(CullFaceMode::FrontAndBack, StencilOp::Keep, StencilOp::Keep, StencilOp::Replace),
+            GlCommand::StencilMask(CullFaceMode::FrontAndBack, 0x00),
+        ]);
+    }
+}
@@ -1,20 +1,25 @@
 use super::{
-    DataType, Id, PixelFormat, TextureCubeFace, TextureMagFilter, TextureMinFilter,
+    DataType, TextureId, ProgramId, PixelFormat, TextureCubeFace, TextureMagFilter, TextureMinFilter,
     TextureParameterName, TextureTarget, TextureUnit, TextureWrapMode, TextureWrapTarget,
-    WebGlCommon, WebGlRenderer, WebGlSpecific,
-    ProgramQuery,
+    WebGlCommon, WebGl2Extra, WebGlRenderer, WebGlSpecific,
+    ProgramQuery, UniformDataType,
     PixelDataFormat, PixelInternalFormat,
 };
+use crate::data::TypedData;
 use crate::errors::{Error, NativeError};
+use serde::{Deserialize, Serialize};
 use web_sys::{
     HtmlCanvasElement, HtmlImageElement, HtmlVideoElement, ImageBitmap, ImageData, WebGlTexture, WebGlUniformLocation
 };
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 use std::collections::hash_map::Entry;
+use std::convert::TryFrom;
 
 pub enum WebGlTextureSource<'a> {
     ArrayBufferView(&'a js_sys::Object, u32, u32, u32), //width, height, depth
     EmptyBufferView(u32, u32, u32), //width, height, depth
+    //pre-compressed blocks in opts.internal_format (data format and type are ignored)
+    CompressedBufferView(&'a [u8], u32, u32, u32), //width, height, depth
     ImageBitmap(&'a ImageBitmap),
     ImageData(&'a ImageData),
     ImageElement(&'a HtmlImageElement),
@@ -60,6 +65,126 @@ pub struct TextureOptions {
     pub cube_face: Option<TextureCubeFace>,
 }
 
+// for updating part of an already-assigned (or allocated) texture
+// the internal format and size are whatever was assigned
+pub struct TextureSubOptions {
+    pub mip_level: u32,
+    pub data_format: PixelDataFormat,
+    pub data_type: DataType,
+    pub cube_face: Option<TextureCubeFace>,
+}
+
+impl Default for TextureSubOptions {
+    fn default() -> Self {
+        Self {
+            mip_level: 0,
+            data_format: PixelDataFormat::Rgba,
+            data_type: DataType::UnsignedByte,
+            cube_face: None,
+        }
+    }
+}
+
+// what's known about the texture's image, from assigning or allocating it
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TextureStorage {
+    pub internal_format: PixelInternalFormat,
+    //width, height, depth of the base level (depth is 1 for 2d and cube maps)
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    pub levels: u32,
+    //allocated via allocate_texture_storage() - can only be updated, not reassigned
+    pub immutable: bool,
+}
+
+impl TextureStorage {
+    pub fn size(&self) -> (u32, u32, u32) {
+        (self.width, self.height, self.depth)
+    }
+
+    /// estimated from the format, size and levels (drivers may pad or compress further)
+    pub fn byte_len(&self, bind_target: TextureTarget) -> usize {
+        let faces = if bind_target == TextureTarget::CubeMap { 6 } else { 1 };
+
+        (0..self.levels)
+            .map(|level| {
+                let width = (self.width >> level).max(1) as usize;
+                let height = (self.height >> level).max(1) as usize;
+                let depth = match bind_target {
+                    TextureTarget::Texture3d => (self.depth >> level).max(1),
+                    _ => self.depth,
+                } as usize;
+
+                let layer = match self.internal_format.compressed_block() {
+                    Some((block_width, block_height, block_bytes)) => {
+                        let block_width = block_width as usize;
+                        let block_height = block_height as usize;
+                        ((width + block_width - 1) / block_width) * ((height + block_height - 1) / block_height) * block_bytes as usize
+                    }
+                    None => width * height * self.internal_format.bytes_per_pixel().unwrap_or(4) as usize,
+                };
+
+                layer * depth
+            })
+            .sum::<usize>()
+            * faces
+    }
+}
+
+/// the number of mip levels in a full chain down to 1x1
+/// depth only shrinks with mips for 3d textures
+pub fn get_max_mip_levels(bind_target: TextureTarget, width: u32, height: u32, depth: u32) -> u32 {
+    let max = match bind_target {
+        TextureTarget::Texture3d => width.max(height).max(depth),
+        _ => width.max(height),
+    };
+
+    32 - max.max(1).leading_zeros()
+}
+
+pub trait PartialWebGl2Textures {
+    fn awsm_texture_storage(
+        &self,
+        bind_target: TextureTarget,
+        levels: u32,
+        internal_format: PixelInternalFormat,
+        width: u32,
+        height: u32,
+        depth: u32,
+    );
+}
+
+impl PartialWebGl2Textures for WebGl2RenderingContext {
+    fn awsm_texture_storage(
+        &self,
+        bind_target: TextureTarget,
+        levels: u32,
+        internal_format: PixelInternalFormat,
+        width: u32,
+        height: u32,
+        depth: u32,
+    ) {
+        match bind_target {
+            TextureTarget::Texture3d | TextureTarget::Array2d => self.tex_storage_3d(
+                bind_target as u32,
+                levels as i32,
+                internal_format as u32,
+                width as i32,
+                height as i32,
+                depth as i32,
+            ),
+            _ => self.tex_storage_2d(
+                bind_target as u32,
+                levels as i32,
+                internal_format as u32,
+                width as i32,
+                height as i32,
+            ),
+        }
+    }
+}
+
 pub trait PartialWebGlTextures {
     fn awsm_create_texture(&self) -> Result<WebGlTexture, Error>;
     fn awsm_delete_texture(&self, texture:&WebGlTexture);
@@ -129,6 +254,51 @@ pub trait PartialWebGlTextures {
         opts: &TextureOptions,
         src: &WebGlTextureSource,
     ) -> Result<(), Error>;
+
+    fn awsm_update_texture_sub(
+        &self,
+        bind_target: TextureTarget,
+        opts: &TextureSubOptions,
+        x: u32,
+        y: u32,
+        z: u32,
+        src: &WebGlTextureSource,
+        dest: &WebGlTexture,
+    ) -> Result<(), Error>;
+
+    fn _awsm_update_texture_sub(
+        &self,
+        bind_target: TextureTarget,
+        opts: &TextureSubOptions,
+        x: u32,
+        y: u32,
+        z: u32,
+        src: &WebGlTextureSource,
+    ) -> Result<(), Error>;
+
+    //src must be a CompressedBufferView in the texture's internal format
+    fn awsm_update_compressed_texture_sub(
+        &self,
+        bind_target: TextureTarget,
+        internal_format: PixelInternalFormat,
+        opts: &TextureSubOptions,
+        x: u32,
+        y: u32,
+        z: u32,
+        src: &WebGlTextureSource,
+        dest: &WebGlTexture,
+    ) -> Result<(), Error>;
+
+    fn _awsm_update_compressed_texture_sub(
+        &self,
+        bind_target: TextureTarget,
+        internal_format: PixelInternalFormat,
+        opts: &TextureSubOptions,
+        x: u32,
+        y: u32,
+        z: u32,
+        src: &WebGlTextureSource,
+    ) -> Result<(), Error>;
 }
 
 macro_rules! impl_context {
@@ -235,6 +405,16 @@ macro_rules! impl_context {
                 Ok(())
             }
 
+            fn awsm_update_texture_sub(&self, bind_target: TextureTarget, opts:&TextureSubOptions, x: u32, y: u32, z: u32, src:&WebGlTextureSource, dest:&WebGlTexture) -> Result<(), Error> {
+                self.awsm_bind_texture(bind_target, dest);
+                self._awsm_update_texture_sub(bind_target, &opts, x, y, z, &src)
+            }
+
+            fn awsm_update_compressed_texture_sub(&self, bind_target: TextureTarget, internal_format: PixelInternalFormat, opts:&TextureSubOptions, x: u32, y: u32, z: u32, src:&WebGlTextureSource, dest:&WebGlTexture) -> Result<(), Error> {
+                self.awsm_bind_texture(bind_target, dest);
+                self._awsm_update_compressed_texture_sub(bind_target, internal_format, &opts, x, y, z, &src)
+            }
+
             fn awsm_bind_texture(&self, bind_target: TextureTarget, texture:&WebGlTexture) {
                 self.bind_texture(bind_target as u32, Some(texture));
             }
@@ -497,7 +677,115 @@ impl_context! {
                         }
                     }
                 },
+                WebGlTextureSource::CompressedBufferView(buffer_view, width, height, _depth) => {
+                    match bind_target {
+                        TextureTarget::Texture2d | TextureTarget::CubeMap => {
+                            let target = if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_u32 };
+                            self.compressed_tex_image_2d_with_u8_array(
+                                target,
+                                mip_level,
+                                internal_format as u32,
+                                *width as i32,
+                                *height as i32,
+                                0,
+                                buffer_view
+                            );
+                            Ok(())
+                        },
+                        TextureTarget::Texture3d => {
+                            Err(Error::from(NativeError::WebGl1Texture3d))
+                        },
+                        TextureTarget::Array2d => {
+                            Err(Error::from(NativeError::WebGl1TextureArray2d))
+                        },
+                    }
+                },
+
+            }
+        }
+
+        fn _awsm_update_compressed_texture_sub(&self, bind_target: TextureTarget, internal_format: PixelInternalFormat, opts:&TextureSubOptions, x: u32, y: u32, _z: u32, src:&WebGlTextureSource) -> Result<(), Error> {
+
+            let target = match bind_target {
+                TextureTarget::Texture2d => bind_target as u32,
+                TextureTarget::CubeMap => get_cube_face_u32(bind_target, opts.cube_face)?,
+                TextureTarget::Texture3d => return Err(Error::from(NativeError::WebGl1Texture3d)),
+                TextureTarget::Array2d => return Err(Error::from(NativeError::WebGl1TextureArray2d)),
+            };
+
+            match src {
+                WebGlTextureSource::CompressedBufferView(buffer_view, width, height, _depth) => {
+                    let data:js_sys::Object = TypedData::new(*buffer_view).into();
+                    self.compressed_tex_sub_image_2d_with_array_buffer_view(
+                        target,
+                        opts.mip_level as i32,
+                        x as i32,
+                        y as i32,
+                        *width as i32,
+                        *height as i32,
+                        internal_format as u32,
+                        &data
+                    );
+                    Ok(())
+                },
+                _ => Err(Error::from("can't update a compressed texture from an uncompressed source")),
+            }
+        }
+
+        fn _awsm_update_texture_sub(&self, bind_target: TextureTarget, opts:&TextureSubOptions, x: u32, y: u32, _z: u32, src:&WebGlTextureSource) -> Result<(), Error> {
+
+            let data_format = opts.data_format as u32;
+            let data_type = opts.data_type as u32;
+            let mip_level = opts.mip_level as i32;
+            let (x, y) = (x as i32, y as i32);
+
+            let target = match bind_target {
+                TextureTarget::Texture2d => bind_target as u32,
+                TextureTarget::CubeMap => get_cube_face_u32(bind_target, opts.cube_face)?,
+                TextureTarget::Texture3d => return Err(Error::from(NativeError::WebGl1Texture3d)),
+                TextureTarget::Array2d => return Err(Error::from(NativeError::WebGl1TextureArray2d)),
+            };
 
+            match src {
+                WebGlTextureSource::ArrayBufferView(buffer_view, width, height, _depth) => {
+                    self.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                        target,
+                        mip_level,
+                        x,
+                        y,
+                        *width as i32,
+                        *height as i32,
+                        data_format,
+                        data_type,
+                        Some(buffer_view)
+                    ).map_err(|err| err.into())
+                },
+                WebGlTextureSource::EmptyBufferView(_, _, _) => {
+                    Err(Error::from("can't update a texture from an empty buffer view"))
+                },
+                WebGlTextureSource::CompressedBufferView(_, _, _, _) => {
+                    Err(Error::from("can't update a compressed texture from a sub image"))
+                },
+                WebGlTextureSource::ImageBitmap(bmp) => {
+                    self.tex_sub_image_2d_with_u32_and_u32_and_image_bitmap(target, mip_level, x, y, data_format, data_type, bmp)
+                        .map_err(|err| err.into())
+                },
+                WebGlTextureSource::ImageData(data) => {
+                    self.tex_sub_image_2d_with_u32_and_u32_and_image_data(target, mip_level, x, y, data_format, data_type, data)
+                        .map_err(|err| err.into())
+                },
+                WebGlTextureSource::ImageElement(img) => {
+                    self.tex_sub_image_2d_with_u32_and_u32_and_image(target, mip_level, x, y, data_format, data_type, img)
+                        .map_err(|err| err.into())
+                },
+                WebGlTextureSource::CanvasElement(canvas) => {
+                    self.tex_sub_image_2d_with_u32_and_u32_and_canvas(target, mip_level, x, y, data_format, data_type, canvas)
+                        .map_err(|err| err.into())
+                },
+                WebGlTextureSource::VideoElement(video) => {
+                    self.tex_sub_image_2d_with_u32_and_u32_and_video(target, mip_level, x, y, data_format, data_type, video)
+                        .map_err(|err| err.into())
+                },
             }
         }
     }
@@ -759,10 +1047,183 @@ impl_context! {
                         }
                     }
                 },
+                WebGlTextureSource::CompressedBufferView(buffer_view, width, height, depth) => {
+                    match bind_target {
+                        TextureTarget::Texture2d | TextureTarget::CubeMap => {
+                            let target = if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_u32 };
+                            self.compressed_tex_image_2d_with_u8_array(
+                                target,
+                                mip_level,
+                                internal_format as u32,
+                                *width as i32,
+                                *height as i32,
+                                0,
+                                buffer_view
+                            );
+                        },
+                        TextureTarget::Texture3d | TextureTarget::Array2d => {
+                            self.compressed_tex_image_3d_with_u8_array(
+                                bind_u32,
+                                mip_level,
+                                internal_format as u32,
+                                *width as i32,
+                                *height as i32,
+                                *depth as i32,
+                                0,
+                                buffer_view
+                            );
+                        },
+                    }
+                    Ok(())
+                },
 
             }
         }
 
+        fn _awsm_update_compressed_texture_sub(&self, bind_target: TextureTarget, internal_format: PixelInternalFormat, opts:&TextureSubOptions, x: u32, y: u32, z: u32, src:&WebGlTextureSource) -> Result<(), Error> {
+
+            let mip_level = opts.mip_level as i32;
+            let bind_u32 = bind_target as u32;
+            let cube_face_u32 = get_cube_face_u32(bind_target, opts.cube_face)?;
+
+            match src {
+                WebGlTextureSource::CompressedBufferView(buffer_view, width, height, depth) => {
+                    let data:js_sys::Object = TypedData::new(*buffer_view).into();
+                    match bind_target {
+                        TextureTarget::Texture2d | TextureTarget::CubeMap => {
+                            let target = if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_u32 };
+                            self.compressed_tex_sub_image_2d_with_array_buffer_view(
+                                target,
+                                mip_level,
+                                x as i32,
+                                y as i32,
+                                *width as i32,
+                                *height as i32,
+                                internal_format as u32,
+                                &data
+                            );
+                        },
+                        TextureTarget::Texture3d | TextureTarget::Array2d => {
+                            self.compressed_tex_sub_image_3d_with_array_buffer_view(
+                                bind_u32,
+                                mip_level,
+                                x as i32,
+                                y as i32,
+                                z as i32,
+                                *width as i32,
+                                *height as i32,
+                                (*depth).max(1) as i32,
+                                internal_format as u32,
+                                &data
+                            );
+                        },
+                    }
+                    Ok(())
+                },
+                _ => Err(Error::from("can't update a compressed texture from an uncompressed source")),
+            }
+        }
+
+        fn _awsm_update_texture_sub(&self, bind_target: TextureTarget, opts:&TextureSubOptions, x: u32, y: u32, z: u32, src:&WebGlTextureSource) -> Result<(), Error> {
+
+            let data_format = opts.data_format as u32;
+            let data_type = opts.data_type as u32;
+            let mip_level = opts.mip_level as i32;
+            let (x, y, z) = (x as i32, y as i32, z as i32);
+
+            let bind_u32 = bind_target as u32;
+            let cube_face_u32 = get_cube_face_u32(bind_target, opts.cube_face)?;
+
+            match src {
+                WebGlTextureSource::EmptyBufferView(_, _, _) => {
+                    return Err(Error::from("can't update a texture from an empty buffer view"));
+                },
+                WebGlTextureSource::CompressedBufferView(_, _, _, _) => {
+                    return Err(Error::from("can't update a compressed texture from a sub image"));
+                },
+                _ => {}
+            }
+
+            match bind_target {
+                TextureTarget::Texture2d | TextureTarget::CubeMap => {
+                    let target = if bind_target == TextureTarget::CubeMap { cube_face_u32 } else { bind_u32 };
+
+                    match src {
+                        WebGlTextureSource::ArrayBufferView(buffer_view, width, height, _depth) => {
+                            self.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                                target,
+                                mip_level,
+                                x,
+                                y,
+                                *width as i32,
+                                *height as i32,
+                                data_format,
+                                data_type,
+                                Some(buffer_view)
+                            )
+                        },
+                        WebGlTextureSource::ImageBitmap(bmp) => {
+                            self.tex_sub_image_2d_with_u32_and_u32_and_image_bitmap(target, mip_level, x, y, data_format, data_type, bmp)
+                        },
+                        WebGlTextureSource::ImageData(data) => {
+                            self.tex_sub_image_2d_with_u32_and_u32_and_image_data(target, mip_level, x, y, data_format, data_type, data)
+                        },
+                        WebGlTextureSource::ImageElement(img) => {
+                            self.tex_sub_image_2d_with_u32_and_u32_and_html_image_element(target, mip_level, x, y, data_format, data_type, img)
+                        },
+                        WebGlTextureSource::CanvasElement(canvas) => {
+                            self.tex_sub_image_2d_with_u32_and_u32_and_html_canvas_element(target, mip_level, x, y, data_format, data_type, canvas)
+                        },
+                        WebGlTextureSource::VideoElement(video) => {
+                            self.tex_sub_image_2d_with_u32_and_u32_and_html_video_element(target, mip_level, x, y, data_format, data_type, video)
+                        },
+                        WebGlTextureSource::EmptyBufferView(_, _, _)
+                            | WebGlTextureSource::CompressedBufferView(_, _, _, _) => unreachable!(),
+                    }
+                },
+                TextureTarget::Texture3d | TextureTarget::Array2d => {
+                    //DOM sources are a single layer
+                    let (width, height, depth) = get_texture_size(&src);
+                    let (width, height, depth) = (width as i32, height as i32, depth.max(1) as i32);
+
+                    match src {
+                        WebGlTextureSource::ArrayBufferView(buffer_view, _, _, _) => {
+                            self.tex_sub_image_3d_with_opt_array_buffer_view(
+                                bind_u32,
+                                mip_level,
+                                x,
+                                y,
+                                z,
+                                width,
+                                height,
+                                depth,
+                                data_format,
+                                data_type,
+                                Some(buffer_view)
+                            )
+                        },
+                        WebGlTextureSource::ImageBitmap(bmp) => {
+                            self.tex_sub_image_3d_with_image_bitmap(bind_u32, mip_level, x, y, z, width, height, depth, data_format, data_type, bmp)
+                        },
+                        WebGlTextureSource::ImageData(data) => {
+                            self.tex_sub_image_3d_with_image_data(bind_u32, mip_level, x, y, z, width, height, depth, data_format, data_type, data)
+                        },
+                        WebGlTextureSource::ImageElement(img) => {
+                            self.tex_sub_image_3d_with_html_image_element(bind_u32, mip_level, x, y, z, width, height, depth, data_format, data_type, img)
+                        },
+                        WebGlTextureSource::CanvasElement(canvas) => {
+                            self.tex_sub_image_3d_with_html_canvas_element(bind_u32, mip_level, x, y, z, width, height, depth, data_format, data_type, canvas)
+                        },
+                        WebGlTextureSource::VideoElement(video) => {
+                            self.tex_sub_image_3d_with_html_video_element(bind_u32, mip_level, x, y, z, width, height, depth, data_format, data_type, video)
+                        },
+                        WebGlTextureSource::EmptyBufferView(_, _, _)
+                            | WebGlTextureSource::CompressedBufferView(_, _, _, _) => unreachable!(),
+                    }
+                }
+            }.map_err(|err| err.into())
+        }
+
         fn awsm_texture_sources_can_mipmap(&self, _:&[&WebGlTextureSource]) -> Result<(), Error> {
             Ok(())
         }
@@ -778,11 +1239,15 @@ pub fn get_texture_size(src: &WebGlTextureSource) -> (u32, u32, u32) {
         WebGlTextureSource::EmptyBufferView(width, height, depth) => {
             (*width, *height, *depth)
         }
+        WebGlTextureSource::CompressedBufferView(_buffer, width, height, depth) => {
+            (*width, *height, *depth)
+        }
         WebGlTextureSource::ImageBitmap(bmp) => (bmp.width(), bmp.height(), 0),
         WebGlTextureSource::ImageData(data) => (data.width(), data.height(), 0),
-        WebGlTextureSource::ImageElement(img) => (img.width(), img.height(), 0),
+        //the intrinsic size, not the layout attributes (which may be 0 or the css size)
+        WebGlTextureSource::ImageElement(img) => (img.natural_width(), img.natural_height(), 0),
         WebGlTextureSource::CanvasElement(canvas) => (canvas.width(), canvas.height(), 0),
-        WebGlTextureSource::VideoElement(video) => (video.width(), video.height(), 0),
+        WebGlTextureSource::VideoElement(video) => (video.video_width(), video.video_height(), 0),
     }
 }
 
@@ -792,7 +1257,7 @@ pub fn is_power_of_2(src: &WebGlTextureSource) -> bool {
     is_power_of_2_val(width) && is_power_of_2_val(height) && is_power_of_2_val(depth)
 }
 
-fn get_texture_options_from_simple(opts: &SimpleTextureOptions) -> TextureOptions {
+pub(super) fn get_texture_options_from_simple(opts: &SimpleTextureOptions) -> TextureOptions {
     TextureOptions {
         internal_format: opts.pixel_format.into(),
         data_format: opts.pixel_format.into(),
@@ -807,29 +1272,63 @@ fn is_power_of_2_val(val: u32) -> bool {
 
 //WebGlRenderer Impl
 pub(super) struct TextureInfo {
-    texture: WebGlTexture,
-    bind_target: Option<TextureTarget>,
+    pub(super) texture: WebGlTexture,
+    pub(super) bind_target: Option<TextureTarget>,
+    pub(super) storage: Option<TextureStorage>,
+}
+
+impl TextureInfo {
+    //a mutable assignment replaces whatever was there before
+    pub(super) fn set_assigned(
+        &mut self,
+        bind_target: TextureTarget,
+        internal_format: PixelInternalFormat,
+        size: Option<(u32, u32, u32)>,
+        levels: u32,
+    ) -> Result<(), Error> {
+        if self.storage.map(|storage| storage.immutable).unwrap_or(false) {
+            return Err(Error::from(NativeError::TextureImmutable(None)));
+        }
+
+        self.bind_target = Some(bind_target);
+        self.storage = size.map(|(width, height, depth)| TextureStorage {
+            internal_format,
+            width,
+            height,
+            depth: depth.max(1),
+            levels,
+            immutable: false,
+        });
+
+        Ok(())
+    }
 }
 
 impl<G: WebGlCommon> WebGlRenderer<G> {
-    pub fn create_texture(&mut self) -> Result<Id, Error> {
+    pub fn create_texture(&mut self) -> Result<TextureId, Error> {
         let texture = self.gl.awsm_create_texture()?;
 
         let id = self.texture_lookup.insert(TextureInfo {
             texture,
             bind_target: None,
+            storage: None,
         });
 
         Ok(id)
     }
 
-    pub fn delete_texture(&mut self, id:Id) -> Result<(), Error> {
+    pub fn create_texture_labeled(&mut self, label: &str) -> Result<TextureId, Error> {
+        let id = self.create_texture()?;
+        self.set_label(id, label)?;
+        Ok(id)
+    }
+
+    pub fn delete_texture(&mut self, id: TextureId) -> Result<(), Error> {
 
 
         let info = self
             .texture_lookup
-            .get(id)
-            .ok_or(Error::from(NativeError::MissingTexture))?;
+            .try_get(id, NativeError::MissingTexture)?;
 
 
         let mut release_targets:Vec<u32> = Vec::new();
@@ -848,12 +1347,11 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         self.gl.awsm_release_texture_target(bind_target);
     }
 
-    pub fn get_texture_sampler_names(&self, program_id: Id) -> Result<Vec<String>, Error> {
+    pub fn get_texture_sampler_names(&self, program_id: ProgramId) -> Result<Vec<String>, Error> {
 
         let program_info = self
             .program_lookup
-            .get(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
 
         let mut texture_samplers: Vec<String> = Vec::new();
         let max: u32 = 
@@ -862,7 +1360,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
                 .awsm_get_program_parameter_u32(&program_info.program, ProgramQuery::ActiveUniforms)
                 .unwrap_or(0);
 
-        if max <= 0 {
+        if max == 0 {
             return Ok(texture_samplers);
         }
 
@@ -876,28 +1374,22 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
             
 
 
-            for name in super::shader::parse_uniform_names(&name, size as usize) {
-                match type_ {
-                    //Just the sampler types from UniformDataType
-                    //matching on enums with casting seems to be a pain point
-                    //(or I missed something in Rust)
-                    0x8B5E | 0x8B60 | 0x8B5F | 0x8B62 | 0x8DC5 | 0x8DC1 | 0x8DC4 | 0x8DCA
-                    | 0x8DCB | 0x8DCC | 0x8DCF | 0x8DD2 | 0x8DD3 | 0x8DD4 | 0x8DD7 => {
-                        texture_samplers.push(name)
-                    }
-                    _ => {}
-                };
+            let is_sampler = UniformDataType::try_from(type_)
+                .map(|data_type| data_type.is_sampler())
+                .unwrap_or(false);
+
+            if is_sampler {
+                texture_samplers.extend(super::shader::parse_uniform_names(&name, size as usize));
             }
         }
 
         Ok(texture_samplers)
     }
 
-    pub fn cache_sampler_index_name(&mut self, program_id: Id, name:&str) -> Result<u32, Error> {
+    pub fn cache_sampler_index_name(&mut self, program_id: ProgramId, name:&str) -> Result<u32, Error> {
         let program_info = self
             .program_lookup
-            .get_mut(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get_mut(program_id, NativeError::MissingShaderProgram)?;
 
         let index = {
             program_info.texture_sampler_slot_lookup.len() as u32
@@ -923,32 +1415,37 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
     pub fn get_sampler_index_name(&mut self, name: &str) -> Result<u32, Error> {
         let program_id = self
             .current_program_id
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .ok_or(Error::from(NativeError::MissingShaderProgram(None)))?;
 
         self.cache_sampler_index_name(program_id, name)
     }
 
-    pub fn get_texture(&self, texture_id:Id) -> Result<&WebGlTexture, Error> {
+    pub fn get_texture_storage(&self, texture_id: TextureId) -> Result<TextureStorage, Error> {
+        self
+            .texture_lookup
+            .try_get(texture_id, NativeError::MissingTexture)
+            .and_then(|info| info.storage.ok_or_else(|| self.texture_lookup.labeled(texture_id, NativeError::NoTextureTarget(None))))
+    }
+
+    pub fn get_texture(&self, texture_id: TextureId) -> Result<&WebGlTexture, Error> {
         self
             .texture_lookup
-            .get(texture_id)
-            .ok_or(Error::from(NativeError::MissingTexture))
+            .try_get(texture_id, NativeError::MissingTexture)
             .map(|info| &info.texture)
     }
 
     pub fn assign_simple_texture(
         &mut self,
-        texture_id: Id,
+        texture_id: TextureId,
         bind_target: TextureTarget,
         opts: &SimpleTextureOptions,
         src: &WebGlTextureSource,
     ) -> Result<(), Error> {
-        let texture_info = self
+        let (texture_info, label) = self
             .texture_lookup
-            .get_mut(texture_id)
-            .ok_or(Error::from(NativeError::MissingTexture))?;
+            .try_get_mut_labeled(texture_id, NativeError::MissingTexture)?;
 
-        texture_info.bind_target = Some(bind_target);
+        texture_info.set_assigned(bind_target, opts.pixel_format.into(), Some(get_texture_size(src)), 1).map_err(|err| err.with_label(label))?;
 
         self.gl
             .awsm_assign_simple_texture(bind_target, &opts, &src, &texture_info.texture)
@@ -956,17 +1453,28 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
 
     pub fn assign_simple_texture_mips(
         &mut self,
-        texture_id: Id,
+        texture_id: TextureId,
         bind_target: TextureTarget,
         opts: &SimpleTextureOptions,
         srcs: &[&WebGlTextureSource],
     ) -> Result<(), Error> {
-        let texture_info = self
+        let (texture_info, label) = self
             .texture_lookup
-            .get_mut(texture_id)
-            .ok_or(Error::from(NativeError::MissingTexture))?;
+            .try_get_mut_labeled(texture_id, NativeError::MissingTexture)?;
 
-        texture_info.bind_target = Some(bind_target);
+        //the simple parameters generate the rest of the chain
+        let size = srcs.first().map(|src| get_texture_size(src));
+        let levels = size
+            .map(|(width, height, depth)| get_max_mip_levels(bind_target, width, height, depth))
+            .unwrap_or(0)
+            .max(srcs.len() as u32);
+
+        texture_info.set_assigned(
+            bind_target,
+            opts.pixel_format.into(),
+            size,
+            levels,
+        ).map_err(|err| err.with_label(label))?;
 
         self.gl
             .awsm_assign_simple_texture_mips(bind_target, &opts, &srcs, &texture_info.texture)
@@ -974,18 +1482,17 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
 
     pub fn assign_texture(
         &mut self,
-        texture_id: Id,
+        texture_id: TextureId,
         bind_target: TextureTarget,
         opts: &TextureOptions,
         set_parameters: Option<impl Fn(&G) -> ()>,
         src: &WebGlTextureSource,
     ) -> Result<(), Error> {
-        let texture_info = self
+        let (texture_info, label) = self
             .texture_lookup
-            .get_mut(texture_id)
-            .ok_or(Error::from(NativeError::MissingTexture))?;
+            .try_get_mut_labeled(texture_id, NativeError::MissingTexture)?;
 
-        texture_info.bind_target = Some(bind_target);
+        texture_info.set_assigned(bind_target, opts.internal_format, Some(get_texture_size(src)), 1).map_err(|err| err.with_label(label))?;
 
         self.gl.awsm_assign_texture(
             bind_target,
@@ -998,18 +1505,22 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
 
     pub fn assign_texture_mips(
         &mut self,
-        texture_id: Id,
+        texture_id: TextureId,
         bind_target: TextureTarget,
         opts: &TextureOptions,
         set_parameters: Option<impl Fn(&G) -> ()>,
         srcs: &[&WebGlTextureSource],
     ) -> Result<(), Error> {
-        let texture_info = self
+        let (texture_info, label) = self
             .texture_lookup
-            .get_mut(texture_id)
-            .ok_or(Error::from(NativeError::MissingTexture))?;
+            .try_get_mut_labeled(texture_id, NativeError::MissingTexture)?;
 
-        texture_info.bind_target = Some(bind_target);
+        texture_info.set_assigned(
+            bind_target,
+            opts.internal_format,
+            srcs.first().map(|src| get_texture_size(src)),
+            srcs.len() as u32,
+        ).map_err(|err| err.with_label(label))?;
 
         self.gl.awsm_assign_texture_mips(
             bind_target,
@@ -1020,9 +1531,83 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         )
     }
 
+    // x, y offset into a 2d texture or cube face
+    pub fn update_texture_sub(
+        &self,
+        texture_id: TextureId,
+        opts: &TextureSubOptions,
+        x: u32,
+        y: u32,
+        src: &WebGlTextureSource,
+    ) -> Result<(), Error> {
+        self.update_texture_sub_3d(texture_id, opts, x, y, 0, src)
+    }
+
+    // x, y, z offset - where z is the depth or array layer (webgl2 only)
+    pub fn update_texture_sub_3d(
+        &self,
+        texture_id: TextureId,
+        opts: &TextureSubOptions,
+        x: u32,
+        y: u32,
+        z: u32,
+        src: &WebGlTextureSource,
+    ) -> Result<(), Error> {
+        let texture_info = self
+            .texture_lookup
+            .try_get(texture_id, NativeError::MissingTexture)?;
+
+        let bind_target = texture_info.bind_target.ok_or_else(|| self.texture_lookup.labeled(texture_id, NativeError::NoTextureTarget(None)))?;
+        let storage = texture_info.storage.ok_or_else(|| self.texture_lookup.labeled(texture_id, NativeError::NoTextureTarget(None)))?;
+
+        //mutable textures may have had other levels uploaded outside of the renderer
+        if storage.immutable && opts.mip_level >= storage.levels {
+            return Err(self.texture_lookup.labeled(texture_id, NativeError::TextureSubImageBounds(None)));
+        }
+
+        //compressed sources are in the storage's own format, data_format and data_type are ignored
+        if let WebGlTextureSource::CompressedBufferView(_, _, _, _) = src {
+            if !storage.internal_format.is_compressed() {
+                return Err(self.texture_lookup.labeled(texture_id, NativeError::TextureFormatMismatch(None)));
+            }
+
+            check_sub_bounds(bind_target, storage.size(), opts.mip_level, (x, y, z), get_texture_size(src))
+                .and_then(|_| check_sub_blocks(storage, opts.mip_level, (x, y), get_texture_size(src)))
+                .map_err(|err| err.with_label(self.texture_lookup.label(texture_id)))?;
+
+            return self.gl.awsm_update_compressed_texture_sub(
+                bind_target,
+                storage.internal_format,
+                &opts,
+                x,
+                y,
+                z,
+                &src,
+                &texture_info.texture,
+            );
+        }
+
+        if storage.internal_format.is_compressed() || opts.data_format != storage.internal_format.data_format() {
+            return Err(self.texture_lookup.labeled(texture_id, NativeError::TextureFormatMismatch(None)));
+        }
+
+        check_sub_bounds(bind_target, storage.size(), opts.mip_level, (x, y, z), get_texture_size(src))
+            .map_err(|err| err.with_label(self.texture_lookup.label(texture_id)))?;
+
+        self.gl.awsm_update_texture_sub(
+            bind_target,
+            &opts,
+            x,
+            y,
+            z,
+            &src,
+            &texture_info.texture,
+        )
+    }
+
     pub fn activate_texture_sampler_name(
         &mut self,
-        texture_id: Id,
+        texture_id: TextureId,
         sampler_name: &str,
     ) -> Result<(), Error> {
 
@@ -1036,17 +1621,16 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
 
     pub fn activate_texture_sampler_index(
         &mut self,
-        texture_id: Id,
+        texture_id: TextureId,
         sampler_loc: WebGlUniformLocation,
         sampler_index: u32,
     ) -> Result<(), Error> {
 
         let texture_info = self
             .texture_lookup
-            .get(texture_id)
-            .ok_or(Error::from(NativeError::MissingTexture))?;
+            .try_get(texture_id, NativeError::MissingTexture)?;
 
-        let bind_target = texture_info.bind_target.ok_or(Error::from(NativeError::NoTextureTarget))?;
+        let bind_target = texture_info.bind_target.ok_or_else(|| self.texture_lookup.labeled(texture_id, NativeError::NoTextureTarget(None)))?;
 
         self.gl.awsm_uniform1i(&sampler_loc, sampler_index as i32);
         self.gl.awsm_activate_texture_sampler_index(sampler_index);
@@ -1056,7 +1640,141 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
     }
 }
 
-fn get_cube_face_u32(
+impl<G: WebGlCommon + WebGl2Extra> WebGlRenderer<G> {
+    // immutable storage for every mip level at once (texStorage2D / texStorage3D)
+    // the contents are then filled in via update_texture_sub() (or assign_compressed_texture())
+    // depth is ignored for 2d and cube map targets
+    pub fn allocate_texture_storage(
+        &mut self,
+        texture_id: TextureId,
+        bind_target: TextureTarget,
+        levels: u32,
+        internal_format: PixelInternalFormat,
+        width: u32,
+        height: u32,
+        depth: u32,
+    ) -> Result<(), Error> {
+        let (texture_info, label) = self
+            .texture_lookup
+            .try_get_mut_labeled(texture_id, NativeError::MissingTexture)?;
+
+        if texture_info.storage.map(|storage| storage.immutable).unwrap_or(false) {
+            return Err(Error::from(NativeError::TextureImmutable(None)).with_label(label));
+        }
+
+        let depth = match bind_target {
+            TextureTarget::Texture3d | TextureTarget::Array2d => depth,
+            _ => 1,
+        };
+
+        check_storage(bind_target, levels, internal_format, width, height, depth)?;
+
+        self.gl.awsm_bind_texture(bind_target, &texture_info.texture);
+        self.gl.awsm_texture_storage(bind_target, levels, internal_format, width, height, depth);
+
+        texture_info.bind_target = Some(bind_target);
+        texture_info.storage = Some(TextureStorage {
+            internal_format,
+            width,
+            height,
+            depth,
+            levels,
+            immutable: true,
+        });
+
+        Ok(())
+    }
+}
+
+fn check_storage(
+    bind_target: TextureTarget,
+    levels: u32,
+    internal_format: PixelInternalFormat,
+    width: u32,
+    height: u32,
+    depth: u32,
+) -> Result<(), Error> {
+    let err = |desc: String| Err(Error::from(NativeError::TextureStorage(Some(desc))));
+
+    if !internal_format.is_sized() {
+        return err(format!("{:?} is not a sized internal format", internal_format));
+    }
+    if width == 0 || height == 0 || depth == 0 {
+        return err(format!("{}x{}x{} is empty", width, height, depth));
+    }
+    if bind_target == TextureTarget::CubeMap && width != height {
+        return err(format!("cube map faces must be square, not {}x{}", width, height));
+    }
+
+    let max_levels = get_max_mip_levels(bind_target, width, height, depth);
+    if levels == 0 || levels > max_levels {
+        return err(format!("{} levels (must be between 1 and {})", levels, max_levels));
+    }
+
+    Ok(())
+}
+
+//the region must fit inside the mip level
+//array layers don't shrink with mips, but 3d depth does
+fn check_sub_bounds(
+    bind_target: TextureTarget,
+    size: (u32, u32, u32),
+    mip_level: u32,
+    offset: (u32, u32, u32),
+    src_size: (u32, u32, u32),
+) -> Result<(), Error> {
+    let mip_size = |value: u32| (value.checked_shr(mip_level).unwrap_or(0)).max(1);
+
+    let (width, height, depth) = size;
+    let (width, height) = (mip_size(width), mip_size(height));
+    let depth = match bind_target {
+        TextureTarget::Texture3d => mip_size(depth),
+        TextureTarget::Array2d => depth.max(1),
+        _ => 1,
+    };
+
+    let (x, y, z) = offset;
+    let (src_width, src_height, src_depth) = src_size;
+
+    let fits = |offset: u32, len: u32, max: u32| {
+        offset.checked_add(len).map(|end| end <= max).unwrap_or(false)
+    };
+
+    if fits(x, src_width, width) && fits(y, src_height, height) && fits(z, src_depth.max(1), depth) {
+        Ok(())
+    } else {
+        Err(Error::from(NativeError::TextureSubImageBounds(None)))
+    }
+}
+
+//compressed updates are whole blocks, except where they reach the edge of the level
+fn check_sub_blocks(
+    storage: TextureStorage,
+    mip_level: u32,
+    offset: (u32, u32),
+    src_size: (u32, u32, u32),
+) -> Result<(), Error> {
+    let (block_width, block_height, _) = match storage.internal_format.compressed_block() {
+        Some(block) => block,
+        None => return Ok(()),
+    };
+    let mip_size = |value: u32| (value.checked_shr(mip_level).unwrap_or(0)).max(1);
+
+    let aligned = |offset: u32, len: u32, block: u32, max: u32| {
+        offset % block == 0 && (len % block == 0 || offset + len == max)
+    };
+
+    let (x, y) = offset;
+    let (src_width, src_height, _) = src_size;
+
+    if aligned(x, src_width, block_width, mip_size(storage.width)) && aligned(y, src_height, block_height, mip_size(storage.height)) {
+        Ok(())
+    } else {
+        Err(Error::from(NativeError::TextureSubImageBounds(None)))
+    }
+}
+
+pub(super) fn get_cube_face_u32(
     bind_target: TextureTarget,
     cube_face: Option<TextureCubeFace>,
 ) -> Result<u32, Error> {
@@ -1077,3 +1795,136 @@ fn get_cube_face_u32(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, WebGlVersion};
+    use wasm_bindgen::{JsCast, JsValue};
+
+    #[test]
+    fn test_sub_bounds() {
+        let size = (256, 128, 4);
+        assert!(check_sub_bounds(TextureTarget::Texture2d, size, 0, (128, 64, 0), (128, 64, 1)).is_ok());
+        assert!(check_sub_bounds(TextureTarget::Texture2d, size, 0, (129, 0, 0), (128, 64, 1)).is_err());
+        assert!(check_sub_bounds(TextureTarget::Texture2d, size, 1, (0, 0, 0), (128, 64, 1)).is_ok());
+        assert!(check_sub_bounds(TextureTarget::Texture2d, size, 1, (0, 0, 0), (256, 64, 1)).is_err());
+        assert!(check_sub_bounds(TextureTarget::Texture2d, size, 0, (0, 0, 1), (1, 1, 1)).is_err());
+        assert!(check_sub_bounds(TextureTarget::Array2d, size, 2, (0, 0, 3), (64, 32, 1)).is_ok());
+        assert!(check_sub_bounds(TextureTarget::Texture3d, size, 2, (0, 0, 1), (64, 32, 1)).is_err());
+        assert!(check_sub_bounds(TextureTarget::Texture2d, size, 0, (u32::MAX, 0, 0), (1, 1, 1)).is_err());
+    }
+
+    #[test]
+    fn test_storage_checks() {
+        assert_eq!(get_max_mip_levels(TextureTarget::Texture2d, 256, 64, 1), 9);
+        assert_eq!(get_max_mip_levels(TextureTarget::Array2d, 4, 4, 64), 3);
+        assert_eq!(get_max_mip_levels(TextureTarget::Texture3d, 4, 4, 64), 7);
+
+        assert!(check_storage(TextureTarget::Texture2d, 9, PixelInternalFormat::Rgba8, 256, 64, 1).is_ok());
+        assert!(check_storage(TextureTarget::Texture2d, 10, PixelInternalFormat::Rgba8, 256, 64, 1).is_err());
+        assert!(check_storage(TextureTarget::Texture2d, 0, PixelInternalFormat::Rgba8, 256, 64, 1).is_err());
+        assert!(check_storage(TextureTarget::Texture2d, 1, PixelInternalFormat::Rgba, 256, 64, 1).is_err());
+        assert!(check_storage(TextureTarget::CubeMap, 1, PixelInternalFormat::Rgba8, 256, 64, 1).is_err());
+        assert!(check_storage(TextureTarget::Array2d, 1, PixelInternalFormat::R32ui, 4, 4, 0).is_err());
+    }
+
+    #[test]
+    fn test_allocate_texture_storage() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        let texture = renderer.create_texture().unwrap();
+        let data: js_sys::Object = JsValue::UNDEFINED.unchecked_into();
+        let tile = WebGlTextureSource::ArrayBufferView(&data, 16, 16, 1);
+
+        renderer.allocate_texture_storage(texture, TextureTarget::Texture2d, 3, PixelInternalFormat::Rgba8, 64, 64, 8).unwrap();
+        assert_eq!(renderer.get_texture_storage(texture).unwrap(), TextureStorage {
+            internal_format: PixelInternalFormat::Rgba8,
+            width: 64,
+            height: 64,
+            depth: 1,
+            levels: 3,
+            immutable: true,
+        });
+
+        renderer.update_texture_sub(texture, &TextureSubOptions { mip_level: 2, ..TextureSubOptions::default() }, 0, 0, &tile).unwrap();
+        assert!(matches!(
+            renderer.update_texture_sub(texture, &TextureSubOptions { mip_level: 3, ..TextureSubOptions::default() }, 0, 0, &WebGlTextureSource::ArrayBufferView(&data, 1, 1, 1)),
+            Err(Error::Native(NativeError::TextureSubImageBounds(None)))
+        ));
+        assert!(matches!(
+            renderer.update_texture_sub(texture, &TextureSubOptions { data_format: PixelDataFormat::Rgb, ..TextureSubOptions::default() }, 0, 0, &tile),
+            Err(Error::Native(NativeError::TextureFormatMismatch(None)))
+        ));
+        assert!(matches!(
+            renderer.assign_simple_texture(texture, TextureTarget::Texture2d, &SimpleTextureOptions::default(), &tile),
+            Err(Error::Native(NativeError::TextureImmutable(None)))
+        ));
+        assert!(matches!(
+            renderer.allocate_texture_storage(texture, TextureTarget::Texture2d, 1, PixelInternalFormat::Rgba8, 64, 64, 1),
+            Err(Error::Native(NativeError::TextureImmutable(None)))
+        ));
+    }
+
+    #[test]
+    fn test_update_texture_sub() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::One)).unwrap();
+        let texture = renderer.create_texture().unwrap();
+        let data: js_sys::Object = JsValue::UNDEFINED.unchecked_into();
+        let tile = WebGlTextureSource::ArrayBufferView(&data, 16, 16, 1);
+
+        assert!(matches!(
+            renderer.update_texture_sub(texture, &TextureSubOptions::default(), 0, 0, &tile),
+            Err(Error::Native(NativeError::NoTextureTarget(None)))
+        ));
+
+        let opts = SimpleTextureOptions {
+            pixel_format: PixelFormat::Rgba,
+            ..SimpleTextureOptions::default()
+        };
+        renderer.assign_simple_texture(texture, TextureTarget::Texture2d, &opts, &WebGlTextureSource::EmptyBufferView(64, 64, 1)).unwrap();
+        renderer.gl.clear_commands();
+
+        renderer.update_texture_sub(texture, &TextureSubOptions::default(), 48, 16, &tile).unwrap();
+        assert!(matches!(
+            renderer.update_texture_sub(texture, &TextureSubOptions::default(), 49, 16, &tile),
+            Err(Error::Native(NativeError::TextureSubImageBounds(None)))
+        ));
+        assert!(matches!(
+            renderer.update_texture_sub_3d(texture, &TextureSubOptions::default(), 0, 0, 1, &tile),
+            Err(Error::Native(NativeError::TextureSubImageBounds(None)))
+        ));
+
+        assert_eq!(renderer.gl.take_commands(), [
+            GlCommand::BindTexture(TextureTarget::Texture2d),
+            GlCommand::TexSubImage {
+                target: TextureTarget::Texture2d as u32,
+                mip_level: 0,
+                offset: (48, 16, 0),
+                data_format: PixelDataFormat::Rgba,
+                data_type: DataType::UnsignedByte,
+                size: Some((16, 16, 1)),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_generated_mips() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        let texture = renderer.create_texture().unwrap();
+        let opts = SimpleTextureOptions {
+            pixel_format: PixelFormat::Rgba,
+            ..SimpleTextureOptions::default()
+        };
+
+        renderer.assign_simple_texture_mips(texture, TextureTarget::Texture2d, &opts, &[&WebGlTextureSource::EmptyBufferView(64, 32, 1)]).unwrap();
+        assert!(renderer.gl.take_commands().contains(&GlCommand::GenerateMipmap(TextureTarget::Texture2d)));
+
+        //64x32 down to 1x1
+        let storage = renderer.get_texture_storage(texture).unwrap();
+        assert_eq!(storage.levels, 7);
+        assert_eq!(storage.byte_len(TextureTarget::Texture2d), (8192 + 2048 + 512 + 128 + 32 + 8 + 4) as usize);
+
+        renderer.assign_simple_texture(texture, TextureTarget::Texture2d, &opts, &WebGlTextureSource::EmptyBufferView(64, 32, 1)).unwrap();
+        assert_eq!(renderer.get_texture_storage(texture).unwrap().levels, 1);
+    }
+}
@@ -24,7 +24,7 @@ impl_context! {
     WebGlRenderingContext{}
     WebGl2RenderingContext{}
 }
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq)]
 pub(super) struct ToggleFlags {
     blend: bool,
     cull_face: bool,
@@ -38,20 +38,54 @@ pub(super) struct ToggleFlags {
     rasterizer_discard: bool,
 }
 
+impl ToggleFlags {
+    pub(super) const ALL: [GlToggle; 10] = [
+        GlToggle::Blend,
+        GlToggle::CullFace,
+        GlToggle::DepthTest,
+        GlToggle::Dither,
+        GlToggle::PolygonOffsetFill,
+        GlToggle::SampleAlphaToCoverage,
+        GlToggle::SampleCoverage,
+        GlToggle::ScissorTest,
+        GlToggle::StencilTest,
+        GlToggle::RasterizerDiscard,
+    ];
+
+    pub(super) fn get(&self, toggle: GlToggle) -> bool {
+        match toggle {
+            GlToggle::Blend => self.blend,
+            GlToggle::CullFace => self.cull_face,
+            GlToggle::DepthTest => self.depth_test,
+            GlToggle::Dither => self.dither,
+            GlToggle::PolygonOffsetFill => self.polygon_offset_fill,
+            GlToggle::SampleAlphaToCoverage => self.sample_alpha_to_coverage,
+            GlToggle::SampleCoverage => self.sample_coverage,
+            GlToggle::ScissorTest => self.scissor_test,
+            GlToggle::StencilTest => self.stencil_test,
+            GlToggle::RasterizerDiscard => self.rasterizer_discard,
+        }
+    }
+
+    fn get_mut(&mut self, toggle: GlToggle) -> &mut bool {
+        match toggle {
+            GlToggle::Blend => &mut self.blend,
+            GlToggle::CullFace => &mut self.cull_face,
+            GlToggle::DepthTest => &mut self.depth_test,
+            GlToggle::Dither => &mut self.dither,
+            GlToggle::PolygonOffsetFill => &mut self.polygon_offset_fill,
+            GlToggle::SampleAlphaToCoverage => &mut self.sample_alpha_to_coverage,
+            GlToggle::SampleCoverage => &mut self.sample_coverage,
+            GlToggle::ScissorTest => &mut self.scissor_test,
+            GlToggle::StencilTest => &mut self.stencil_test,
+            GlToggle::RasterizerDiscard => &mut self.rasterizer_discard,
+        }
+    }
+}
+
 impl<T: WebGlCommon> WebGlRenderer<T> {
     pub fn toggle(&mut self, toggle: GlToggle, flag: bool) {
-        let curr = match toggle {
-            GlToggle::Blend => &mut self.toggle_flags.blend,
-            GlToggle::CullFace => &mut self.toggle_flags.cull_face,
-            GlToggle::DepthTest => &mut self.toggle_flags.depth_test,
-            GlToggle::Dither => &mut self.toggle_flags.dither,
-            GlToggle::PolygonOffsetFill => &mut self.toggle_flags.polygon_offset_fill,
-            GlToggle::SampleAlphaToCoverage => &mut self.toggle_flags.sample_alpha_to_coverage,
-            GlToggle::SampleCoverage => &mut self.toggle_flags.sample_coverage,
-            GlToggle::ScissorTest => &mut self.toggle_flags.scissor_test,
-            GlToggle::StencilTest => &mut self.toggle_flags.stencil_test,
-            GlToggle::RasterizerDiscard => &mut self.toggle_flags.rasterizer_discard,
-        };
+        let curr = self.toggle_flags.get_mut(toggle);
 
         if *curr != flag {
             *curr = flag;
@@ -1,6 +1,6 @@
 use super::{
     BufferData, BufferDataImpl, BufferSubData, 
-    BufferSubDataImpl, BufferTarget, BufferUsage, Id, WebGlRenderer,
+    BufferSubDataImpl, BufferTarget, BufferUsage, BufferId, ProgramId, WebGlRenderer,
     UniformBlockActiveQuery, ProgramQuery, UniformBlockQuery,
     shader::PartialWebGlShaders,
     WebGlSpecific
@@ -21,11 +21,10 @@ pub type BufferLocation = u32;
 impl WebGlRenderer<WebGl2RenderingContext> {
 
     //Just used for debugging
-    pub fn get_uniform_buffer_offsets(&self, program_id:Id) -> Result<Vec<(String, UniformIndex, BlockOffset)>, Error> {
+    pub fn get_uniform_buffer_offsets(&self, program_id: ProgramId) -> Result<Vec<(String, UniformIndex, BlockOffset)>, Error> {
         let program_info = self
             .program_lookup
-            .get(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
 
         let max: u32 = self
             .gl
@@ -83,11 +82,10 @@ impl WebGlRenderer<WebGl2RenderingContext> {
 
     }
 
-    pub fn get_uniform_buffer_offset(&self, program_id:Id, uniform_index: u32) -> Result<u32, Error> {
+    pub fn get_uniform_buffer_offset(&self, program_id: ProgramId, uniform_index: u32) -> Result<u32, Error> {
         let program_info = self
             .program_lookup
-            .get(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
 
         let active_uniforms:Vec<u32> = vec![uniform_index];
 
@@ -109,12 +107,11 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         }
 
     }
-    pub fn cache_uniform_buffer_location(&mut self, program_id: Id, name:&str) -> Result<(BufferLocation, bool), Error> {
+    pub fn cache_uniform_buffer_location(&mut self, program_id: ProgramId, name:&str) -> Result<(BufferLocation, bool), Error> {
         let location = {
             let program_info = self
                 .program_lookup
-                .get_mut(program_id)
-                .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+                .try_get_mut(program_id, NativeError::MissingShaderProgram)?;
 
 
             program_info.uniform_buffer_lookup_location.get(name)
@@ -128,8 +125,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
                         None => {
                             let program_info = self
                                 .program_lookup
-                                .get_mut(program_id)
-                                .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+                                .try_get_mut(program_id, NativeError::MissingShaderProgram)?;
 
                             let hardcoded_max = 
                                 self.hardcoded_ubo_locations
@@ -152,8 +148,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
 
                 self
                     .program_lookup
-                    .get_mut(program_id)
-                    .ok_or(Error::from(NativeError::MissingShaderProgram))?
+                    .try_get_mut(program_id, NativeError::MissingShaderProgram)?
                     .uniform_buffer_lookup_location.insert(name.to_string(), location);
 
 
@@ -171,14 +166,13 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         }
 
     }
-    pub fn cache_uniform_buffer_block_index(&mut self, program_id: Id, name:&str) -> Result<(BlockIndex, bool), Error> {
+    pub fn cache_uniform_buffer_block_index(&mut self, program_id: ProgramId, name:&str) -> Result<(BlockIndex, bool), Error> {
         let (location, _) = self.cache_uniform_buffer_location(program_id, name)?;
 
         let block_index = {
             let program_info = self
                 .program_lookup
-                .get_mut(program_id)
-                .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+                .try_get_mut(program_id, NativeError::MissingShaderProgram)?;
 
 
             program_info.uniform_buffer_lookup_activation
@@ -194,8 +188,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
                 let block_index = {
                     let program_info = self
                         .program_lookup
-                        .get(program_id)
-                        .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+                        .try_get(program_id, NativeError::MissingShaderProgram)?;
 
                     let block_index = self.gl.get_uniform_block_index(&program_info.program, &name);
 
@@ -209,8 +202,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
 
                 self
                     .program_lookup
-                    .get_mut(program_id)
-                    .ok_or(Error::from(NativeError::MissingShaderProgram))?
+                    .try_get_mut(program_id, NativeError::MissingShaderProgram)?
                     .uniform_buffer_lookup_activation.insert(location, UniformBufferActivation{
                         block_index,
                         offsets: FxHashMap::default()
@@ -229,7 +221,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
 
     }
 
-    pub fn cache_uniform_buffer_block_field_offset_name(&mut self, program_id: Id, block_name:&str, field_name:&str, field_offset: FieldOffset) -> Result<(BlockOffset, bool), Error> {
+    pub fn cache_uniform_buffer_block_field_offset_name(&mut self, program_id: ProgramId, block_name:&str, field_name:&str, field_offset: FieldOffset) -> Result<(BlockOffset, bool), Error> {
 
 
         let (location, _) = self.cache_uniform_buffer_location(program_id, block_name)?;
@@ -238,8 +230,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         let offset:Option<u32> = {
             self
                 .program_lookup
-                .get(program_id)
-                .ok_or(Error::from(NativeError::MissingShaderProgram))?
+                .try_get(program_id, NativeError::MissingShaderProgram)?
                 .uniform_buffer_lookup_activation
                 .get(&location)
                 .ok_or(Error::from(NativeError::UniformBufferMissing(Some(block_name.to_string()))))?
@@ -272,8 +263,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
 
             let offsets = &mut self
                 .program_lookup
-                .get_mut(program_id)
-                .ok_or(Error::from(NativeError::MissingShaderProgram))?
+                .try_get_mut(program_id, NativeError::MissingShaderProgram)?
                 .uniform_buffer_lookup_activation
                 .get_mut(&location)
                 .ok_or(Error::from(NativeError::UniformBufferMissing(Some(format!("block: {}, field: {}",block_name, field_name)))))?
@@ -297,18 +287,18 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         }
     }
 
-    pub fn get_uniform_buffer_location_name(&mut self, program_id: Id, name: &str) -> Result<BufferLocation, Error> {
+    pub fn get_uniform_buffer_location_name(&mut self, program_id: ProgramId, name: &str) -> Result<BufferLocation, Error> {
         self.cache_uniform_buffer_location(program_id, name)
             .map(|(location, _cached)| location)
     }
-    pub fn get_uniform_buffer_block_index_name(&mut self, program_id: Id, name: &str) -> Result<BlockIndex, Error> {
+    pub fn get_uniform_buffer_block_index_name(&mut self, program_id: ProgramId, name: &str) -> Result<BlockIndex, Error> {
         self.cache_uniform_buffer_block_index(program_id, name)
             .map(|(index, _cached)| index)
     }
 
     pub fn get_uniform_buffer_block_field_offset_name(
         &mut self,
-        program_id: Id,
+        program_id: ProgramId,
         block_name: &str,
         field_name: &str,
     ) -> Result<BlockOffset, Error> {
@@ -318,8 +308,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         let offset:Option<u32> = {
             self
                 .program_lookup
-                .get(program_id)
-                .ok_or(Error::from(NativeError::MissingShaderProgram))?
+                .try_get(program_id, NativeError::MissingShaderProgram)?
                 .uniform_buffer_lookup_activation
                 .get(&location)
                 .ok_or(Error::from(NativeError::UniformBufferMissing(Some(block_name.to_string()))))?
@@ -340,7 +329,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
 
     pub fn get_uniform_buffer_block_field_offset(
         &mut self,
-        program_id: Id,
+        program_id: ProgramId,
         block_name: &str,
         field_offset: FieldOffset,
     ) -> Result<BlockOffset, Error> {
@@ -356,16 +345,15 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     }
 
     // at shader compilation time
-    pub fn init_uniform_buffer_name(&mut self, program_id: Id, name:&str) -> Result<(), Error> {
+    pub fn init_uniform_buffer_name(&mut self, program_id: ProgramId, name:&str) -> Result<(), Error> {
         let location = self.get_uniform_buffer_location_name(program_id, name)?;
         let block_index = self.get_uniform_buffer_block_index_name(program_id, name)?;
         self.init_uniform_buffer_loc(program_id, block_index, location)
     }
-    pub fn init_uniform_buffer_loc(&mut self, program_id: Id, block_index: BlockIndex, location: BufferLocation) -> Result<(), Error> {
+    pub fn init_uniform_buffer_loc(&mut self, program_id: ProgramId, block_index: BlockIndex, location: BufferLocation) -> Result<(), Error> {
         let program_info = self
             .program_lookup
-            .get(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
 
         self.gl.uniform_block_binding(&program_info.program, block_index, location);
 
@@ -373,14 +361,14 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     }
    
     // At render time
-    pub fn activate_uniform_buffer_loc(&mut self, id: Id, location:BufferLocation) {
+    pub fn activate_uniform_buffer_loc(&mut self, id: BufferId, location:BufferLocation) {
         self.bind_buffer_base(id, location, BufferTarget::UniformBuffer);
     }
 
-    pub fn activate_uniform_buffer_name(&mut self, id: Id, name:&str) -> Result<(), Error> {
+    pub fn activate_uniform_buffer_name(&mut self, id: BufferId, name:&str) -> Result<(), Error> {
         let program_id = self
             .current_program_id
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .ok_or(Error::from(NativeError::MissingShaderProgram(None)))?;
 
         let location = self.get_uniform_buffer_location_name(program_id, name)?;
         self.bind_buffer_base(id, location, BufferTarget::UniformBuffer);
@@ -390,7 +378,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     ///upload buffer data
     pub fn upload_uniform_buffer<B: BufferDataImpl>(
         &mut self,
-        id: Id,
+        id: BufferId,
         buffer_data: B,
     ) -> Result<(), Error> {
         match buffer_data.get_target() {
@@ -405,7 +393,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     pub fn upload_sub_uniform_buffer<B: BufferSubDataImpl>(
         &mut self,
         block_offset: BlockOffset,
-        id: Id,
+        id: BufferId,
         buffer_data: B,
     ) -> Result<(), Error> {
         match buffer_data.get_target() {
@@ -419,7 +407,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     ///convenience function
     pub fn upload_uniform_buffer_f32(
         &mut self,
-        id: Id,
+        id: BufferId,
         values: &[f32],
         buffer_usage: BufferUsage,
     ) -> Result<(), Error> {
@@ -431,7 +419,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     ///convenience function
     pub fn upload_uniform_buffer_u8_loc(
         &mut self,
-        id: Id,
+        id: BufferId,
         values: &[u8],
         buffer_usage: BufferUsage,
     ) -> Result<(), Error> {
@@ -444,7 +432,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     pub fn upload_sub_uniform_buffer_f32(
         &mut self,
         block_offset: BlockOffset,
-        id: Id,
+        id: BufferId,
         values: &[f32],
     ) -> Result<(), Error> {
         self.upload_sub_uniform_buffer(
@@ -457,7 +445,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
     pub fn upload_sub_uniform_buffer_u8(
         &mut self,
         block_offset: BlockOffset,
-        id: Id,
+        id: BufferId,
         values: &[u8],
     ) -> Result<(), Error> {
         self.upload_sub_uniform_buffer(
@@ -1,4 +1,4 @@
-use super::{WebGlCommon, Id, WebGlRenderer};
+use super::{WebGlCommon, ProgramId, UniformDataType, WebGlRenderer};
 use crate::errors::{Error, NativeError};
 use std::marker::PhantomData;
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
@@ -7,6 +7,7 @@ use std::collections::hash_map::Entry;
 use std::convert::TryInto;
 use wasm_bindgen::prelude::*;
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum UniformType {
     Scalar1,
     Scalar2,
@@ -24,6 +25,14 @@ pub enum UniformType {
     MatrixTransposed4,
 }
 
+//the kind of values in an upload
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UniformScalar {
+    Float,
+    Int,
+    UnsignedInt,
+}
+
 pub trait PartialWebGlUniforms {
     fn awsm_get_uniform_location(
         &self,
@@ -429,14 +438,159 @@ impl<T: AsRef<[u32]>> UniformUploadImpl2 for UniformSlice<T, u32> {
     }
 }
 
+/*
+ * Upload validation (opt-in via renderer.validate_uniforms)
+ *
+ * The declared types come from reflection, once per program
+ * Bools accept any kind of value, same as GL
+ * Names that weren't reflected (e.g. optimized out) are left for GL to deal with
+ */
+
+//values per element, and whether it's a matrix upload
+fn upload_shape(_type: UniformType) -> (usize, bool) {
+    match _type {
+        UniformType::Scalar1 | UniformType::Vector1 => (1, false),
+        UniformType::Scalar2 | UniformType::Vector2 => (2, false),
+        UniformType::Scalar3 | UniformType::Vector3 => (3, false),
+        UniformType::Scalar4 | UniformType::Vector4 => (4, false),
+        UniformType::Matrix2 | UniformType::MatrixTransposed2 => (4, true),
+        UniformType::Matrix3 | UniformType::MatrixTransposed3 => (9, true),
+        UniformType::Matrix4 | UniformType::MatrixTransposed4 => (16, true),
+    }
+}
+
+//which kinds of values are accepted, values per element, and whether it's a matrix
+fn declared_shape(data_type: UniformDataType) -> Option<(&'static [UniformScalar], usize, bool)> {
+    const FLOAT: &[UniformScalar] = &[UniformScalar::Float];
+    const INT: &[UniformScalar] = &[UniformScalar::Int];
+    const UINT: &[UniformScalar] = &[UniformScalar::UnsignedInt];
+    const ANY: &[UniformScalar] = &[UniformScalar::Float, UniformScalar::Int, UniformScalar::UnsignedInt];
+
+    if data_type.is_sampler() {
+        return Some((INT, 1, false));
+    }
+
+    match data_type {
+        UniformDataType::Float => Some((FLOAT, 1, false)),
+        UniformDataType::FloatVec2 => Some((FLOAT, 2, false)),
+        UniformDataType::FloatVec3 => Some((FLOAT, 3, false)),
+        UniformDataType::FloatVec4 => Some((FLOAT, 4, false)),
+        UniformDataType::Int => Some((INT, 1, false)),
+        UniformDataType::IntVec2 => Some((INT, 2, false)),
+        UniformDataType::IntVec3 => Some((INT, 3, false)),
+        UniformDataType::IntVec4 => Some((INT, 4, false)),
+        UniformDataType::UnsignedInt => Some((UINT, 1, false)),
+        UniformDataType::UnsignedIntVec2 => Some((UINT, 2, false)),
+        UniformDataType::UnsignedIntVec3 => Some((UINT, 3, false)),
+        UniformDataType::UnsignedIntVec4 => Some((UINT, 4, false)),
+        UniformDataType::Bool => Some((ANY, 1, false)),
+        UniformDataType::BoolVec2 => Some((ANY, 2, false)),
+        UniformDataType::BoolVec3 => Some((ANY, 3, false)),
+        UniformDataType::BoolVec4 => Some((ANY, 4, false)),
+        UniformDataType::FloatMat2 => Some((FLOAT, 4, true)),
+        UniformDataType::FloatMat3 => Some((FLOAT, 9, true)),
+        UniformDataType::FloatMat4 => Some((FLOAT, 16, true)),
+        UniformDataType::FloatMat2x3 | UniformDataType::FloatMat3x2 => Some((FLOAT, 6, true)),
+        UniformDataType::FloatMat2x4 | UniformDataType::FloatMat4x2 => Some((FLOAT, 8, true)),
+        UniformDataType::FloatMat3x4 | UniformDataType::FloatMat4x3 => Some((FLOAT, 12, true)),
+        _ => None,
+    }
+}
+
+//u_lights[2] -> (u_lights, 2)
+fn split_array_index(name: &str) -> Option<(&str, u32)> {
+    let (base, index) = name.strip_suffix(']')?.rsplit_once('[')?;
+    Some((base, index.parse().ok()?))
+}
+
+fn check_uniform_upload(
+    name: &str,
+    declared: (UniformDataType, u32),
+    index: u32,
+    scalar: UniformScalar,
+    _type: UniformType,
+    len: usize,
+) -> Result<(), Error> {
+    let (data_type, array_size) = declared;
+
+    let (scalars, size, matrix) = match declared_shape(data_type) {
+        Some(shape) => shape,
+        None => return Ok(()),
+    };
+
+    let mismatch = |desc: String| {
+        Err(Error::from(NativeError::UniformType(Some(format!(
+            "{} is declared as {:?}{} but {}",
+            name,
+            data_type,
+            if array_size > 1 { format!("[{}]", array_size) } else { String::new() },
+            desc
+        )))))
+    };
+
+    if !scalars.contains(&scalar) || upload_shape(_type) != (size, matrix) {
+        return mismatch(format!("the upload is {:?} of {:?}", _type, scalar));
+    }
+
+    let remaining = array_size.saturating_sub(index) as usize;
+    if len == 0 || len % size != 0 || len / size > remaining {
+        return mismatch(format!("the upload has {} values", len));
+    }
+
+    Ok(())
+}
+
 //Renderer wrapper
 impl<G: WebGlCommon> WebGlRenderer<G> {
 
-    pub fn cache_uniform_name(&mut self, program_id: Id, name:&str) -> Result<(WebGlUniformLocation, bool), Error> {
+    pub(super) fn validate_uniform_upload(&mut self, name: &str, scalar: UniformScalar, _type: UniformType, len: usize) -> Result<(), Error> {
+        if !self.validate_uniforms {
+            return Ok(());
+        }
+
+        let program_id = self
+            .current_program_id
+            .ok_or(Error::from(NativeError::MissingShaderProgram(None)))?;
+
         let program_info = self
             .program_lookup
-            .get_mut(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
+
+        if program_info.uniform_types.is_none() {
+            let uniform_types = self
+                ._reflect_program_common(&program_info.program)?
+                .uniforms
+                .into_iter()
+                .map(|uniform| (uniform.name, (uniform.data_type, uniform.array_size)))
+                .collect();
+
+            self.program_lookup.try_get_mut(program_id, NativeError::MissingShaderProgram)?
+                .uniform_types = Some(uniform_types);
+        }
+
+        let uniform_types = self
+            .program_lookup
+            .get(program_id)
+            .and_then(|program_info| program_info.uniform_types.as_ref())
+            .ok_or(Error::from(NativeError::MissingShaderProgram(None)))?;
+
+        let declared = match uniform_types.get(name) {
+            Some(declared) => Some((*declared, 0)),
+            None => split_array_index(name).and_then(|(base, index)| {
+                uniform_types.get(base).map(|declared| (*declared, index))
+            }),
+        };
+
+        match declared {
+            Some((declared, index)) => check_uniform_upload(name, declared, index, scalar, _type, len),
+            None => Ok(()),
+        }
+    }
+
+    pub fn cache_uniform_name(&mut self, program_id: ProgramId, name:&str) -> Result<(WebGlUniformLocation, bool), Error> {
+        let program_info = self
+            .program_lookup
+            .try_get_mut(program_id, NativeError::MissingShaderProgram)?;
 
 
         let entry = program_info.uniform_lookup.entry(name.to_string());
@@ -463,7 +617,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
     pub fn get_uniform_location_name(&mut self, name: &str) -> Result<WebGlUniformLocation, Error> {
         let program_id = self
             .current_program_id
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .ok_or(Error::from(NativeError::MissingShaderProgram(None)))?;
 
         self.cache_uniform_name(program_id, name)
             .map(|(loc, _cached)| loc)
@@ -487,6 +641,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         _type: UniformType,
         data: &T,
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Float, _type, data.as_ref().len())?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.upload_uniform_fvec_loc(&loc, _type, data)
     }
@@ -506,6 +661,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         _type: UniformType,
         data: &T,
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Int, _type, data.as_ref().len())?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.upload_uniform_ivec_loc(&loc, _type, data)
     }
@@ -736,6 +892,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         target_name: &str,
         data: (f32, f32, f32, f32),
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Float, UniformType::Scalar4, 4)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform4f(&loc, data.0, data.1, data.2, data.3);
         Ok(())
@@ -752,6 +909,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         target_name: &str,
         data: (f32, f32, f32),
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Float, UniformType::Scalar3, 3)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform3f(&loc, data.0, data.1, data.2);
         Ok(())
@@ -764,6 +922,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         self.gl.awsm_uniform2f(&loc, data.0, data.1);
     }
     pub fn upload_uniform_fvals_2_name(&mut self, target_name: &str, data: (f32, f32)) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Float, UniformType::Scalar2, 2)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform2f(&loc, data.0, data.1);
         Ok(())
@@ -776,6 +935,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         self.gl.awsm_uniform1f(&loc, data);
     }
     pub fn upload_uniform_fval_name(&mut self, target_name: &str, data: f32) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Float, UniformType::Scalar1, 1)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform1f(&loc, data);
         Ok(())
@@ -794,6 +954,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         target_name: &str,
         data: (i32, i32, i32, i32),
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Int, UniformType::Scalar4, 4)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform4i(&loc, data.0, data.1, data.2, data.3);
         Ok(())
@@ -810,6 +971,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         target_name: &str,
         data: (i32, i32, i32),
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Int, UniformType::Scalar3, 3)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform3i(&loc, data.0, data.1, data.2);
         Ok(())
@@ -822,6 +984,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         self.gl.awsm_uniform2i(&loc, data.0, data.1);
     }
     pub fn upload_uniform_ivals_2_name(&mut self, target_name: &str, data: (i32, i32)) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Int, UniformType::Scalar2, 2)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform2i(&loc, data.0, data.1);
         Ok(())
@@ -834,6 +997,7 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
         self.gl.awsm_uniform1i(&loc, data);
     }
     pub fn upload_uniform_ival_name(&mut self, target_name: &str, data: i32) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::Int, UniformType::Scalar1, 1)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform1i(&loc, data);
         Ok(())
@@ -842,11 +1006,10 @@ impl<G: WebGlCommon> WebGlRenderer<G> {
 
 impl WebGlRenderer<WebGl2RenderingContext> {
 
-    pub fn get_uniform_index_name(&self, program_id:Id, name:&str) -> Result<u32, Error> {
+    pub fn get_uniform_index_name(&self, program_id: ProgramId, name:&str) -> Result<u32, Error> {
         let program_info = self
             .program_lookup
-            .get(program_id)
-            .ok_or(Error::from(NativeError::MissingShaderProgram))?;
+            .try_get(program_id, NativeError::MissingShaderProgram)?;
 
         self.gl.awsm_get_uniform_indices(&program_info.program, &vec![name])
             .ok_or(Error::from(NativeError::UniformIndex(Some(name.to_owned()))))
@@ -870,6 +1033,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         _type: UniformType,
         data: &T,
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::UnsignedInt, _type, data.as_ref().len())?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.upload_uniform_uvec_loc(&loc, _type, data)
     }
@@ -945,6 +1109,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         target_name: &str,
         data: (u32, u32, u32, u32),
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::UnsignedInt, UniformType::Scalar4, 4)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform4ui(&loc, data.0, data.1, data.2, data.3);
         Ok(())
@@ -961,6 +1126,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         target_name: &str,
         data: (u32, u32, u32),
     ) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::UnsignedInt, UniformType::Scalar3, 3)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform3ui(&loc, data.0, data.1, data.2);
         Ok(())
@@ -973,6 +1139,7 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         self.gl.awsm_uniform2ui(&loc, data.0, data.1);
     }
     pub fn upload_uniform_uvals_2_name(&mut self, target_name: &str, data: (u32, u32)) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::UnsignedInt, UniformType::Scalar2, 2)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform2ui(&loc, data.0, data.1);
         Ok(())
@@ -985,8 +1152,74 @@ impl WebGlRenderer<WebGl2RenderingContext> {
         self.gl.awsm_uniform1ui(&loc, data);
     }
     pub fn upload_uniform_uval_name(&mut self, target_name: &str, data: u32) -> Result<(), Error> {
+        self.validate_uniform_upload(target_name, UniformScalar::UnsignedInt, UniformType::Scalar1, 1)?;
         let loc = self.get_uniform_location_name(&target_name)?;
         self.gl.awsm_uniform1ui(&loc, data);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{MockContext, MockProgramInterface, ShaderType, WebGlVersion};
+
+    fn type_error(result: Result<(), Error>) -> Option<String> {
+        match result {
+            Err(Error::Native(NativeError::UniformType(desc))) => desc,
+            _ => None,
+        }
+    }
+
+    //the mock has no uniform locations, so an upload that gets past validation fails there
+    fn passed(result: Result<(), Error>) -> bool {
+        matches!(result, Err(Error::Native(NativeError::UniformLocation(_))))
+    }
+
+    #[test]
+    fn test_validate_uniforms() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        renderer.gl.program_interface.borrow_mut().uniforms = vec![
+            ("u_color".to_string(), UniformDataType::FloatVec4, 1, [-1; 4]),
+            ("u_model".to_string(), UniformDataType::FloatMat4, 1, [-1; 4]),
+            ("u_lights[0]".to_string(), UniformDataType::FloatVec3, 4, [-1; 4]),
+            ("u_texture".to_string(), UniformDataType::Sampler2d, 1, [-1; 4]),
+            ("u_enabled".to_string(), UniformDataType::Bool, 1, [-1; 4]),
+        ];
+
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        renderer.compile_program(&[vertex, fragment]).unwrap();
+
+        //off by default
+        assert!(passed(renderer.upload_uniform_mat_4_name("u_color", &[0.0; 16])));
+
+        renderer.validate_uniforms = true;
+        assert!(passed(renderer.upload_uniform_fvec_4_name("u_color", &[0.0; 4])));
+        assert!(passed(renderer.upload_uniform_fvals_4_name("u_color", (0.0, 0.0, 0.0, 1.0))));
+        assert!(passed(renderer.upload_uniform_mat_transposed_4_name("u_model", &[0.0; 16])));
+        assert!(passed(renderer.upload_uniform_ival_name("u_texture", 0)));
+        assert!(passed(renderer.upload_uniform_ival_name("u_enabled", 1)));
+        assert!(passed(renderer.upload_uniform_fval_name("u_enabled", 1.0)));
+        assert!(passed(renderer.upload_uniform_fvec_3_name("u_lights", &[0.0; 12])));
+        assert!(passed(renderer.upload_uniform_fvec_3_name("u_lights[3]", &[0.0; 3])));
+        //not reflected, left to GL
+        assert!(passed(renderer.upload_uniform_fval_name("u_unknown", 1.0)));
+
+        assert_eq!(
+            type_error(renderer.upload_uniform_fvec_3_name("u_model", &[0.0; 3])).as_deref(),
+            Some("u_model is declared as FloatMat4 but the upload is Vector3 of Float")
+        );
+        assert!(type_error(renderer.upload_uniform_fval_name("u_texture", 0.0)).is_some());
+        assert!(type_error(renderer.upload_uniform_ival_name("u_color", 1)).is_some());
+        assert_eq!(
+            type_error(renderer.upload_uniform_fvec_4_name("u_color", &[0.0; 8])).as_deref(),
+            Some("u_color is declared as FloatVec4 but the upload has 8 values")
+        );
+        assert_eq!(
+            type_error(renderer.upload_uniform_fvec_3_name("u_lights[2]", &[0.0; 9])).as_deref(),
+            Some("u_lights[2] is declared as FloatVec3[4] but the upload has 9 values")
+        );
+        assert!(type_error(renderer.upload_uniform_fvec_3_name("u_lights", &[0.0; 4])).is_some());
+    }
+}
@@ -1,12 +1,36 @@
-use super::{AttributeOptions, BufferTarget, Id, WebGlRenderer, AttributeLocation};
+use super::{AttributeOptions, BufferTarget, BufferId, VaoId, VertexLayout, WebGlCommon, WebGl2Extra, WebGlRenderer, AttributeLocation};
 use crate::errors::{Error, NativeError};
 use web_sys::WebGlVertexArrayObject;
 use web_sys::{WebGl2RenderingContext, WebGlRenderingContext};
 
+//WebGl1 goes through the OES_vertex_array_object extension on the renderer
+//so this is only needed for the native WebGl2 calls
+pub trait PartialWebGl2VertexArray {
+    fn awsm_create_vertex_array(&self) -> Result<WebGlVertexArrayObject, Error>;
+    fn awsm_bind_vertex_array(&self, vao: Option<&WebGlVertexArrayObject>);
+    fn awsm_delete_vertex_array(&self, vao: &WebGlVertexArrayObject);
+    fn awsm_vertex_attrib_divisor(&self, loc: u32, divisor: u32);
+}
+
+impl PartialWebGl2VertexArray for WebGl2RenderingContext {
+    fn awsm_create_vertex_array(&self) -> Result<WebGlVertexArrayObject, Error> {
+        self.create_vertex_array().ok_or(Error::from(NativeError::VertexArrayCreate))
+    }
+    fn awsm_bind_vertex_array(&self, vao: Option<&WebGlVertexArrayObject>) {
+        self.bind_vertex_array(vao);
+    }
+    fn awsm_delete_vertex_array(&self, vao: &WebGlVertexArrayObject) {
+        self.delete_vertex_array(Some(vao));
+    }
+    fn awsm_vertex_attrib_divisor(&self, loc: u32, divisor: u32) {
+        self.vertex_attrib_divisor(loc, divisor);
+    }
+}
+
 #[derive(Debug)]
 pub struct VertexArray<'a> {
     pub attribute: NameOrLoc<'a>,
-    pub buffer_id: Id,
+    pub buffer_id: BufferId,
     pub opts: AttributeOptions,
 }
 
@@ -20,7 +44,7 @@ pub enum NameOrLoc<'a> {
 }
 
 impl <'a> VertexArray<'a> {
-    pub fn new (attribute: NameOrLoc<'a>, buffer_id: Id, opts: AttributeOptions) -> Self {
+    pub fn new (attribute: NameOrLoc<'a>, buffer_id: BufferId, opts: AttributeOptions) -> Self {
         Self {
             attribute,
             buffer_id,
@@ -30,27 +54,27 @@ impl <'a> VertexArray<'a> {
 }
 
 macro_rules! impl_renderer {
-    ($($type:ty { $($defs:tt)* })+) => {
-        $(impl WebGlRenderer<$type> {
+    ($(($($header:tt)*) { $($defs:tt)* })+) => {
+        $($($header)* {
 
 
             pub fn release_vertex_array(&self) -> Result<(), Error> {
                 self._bind_vertex_array(None, None)
             }
 
-            pub fn activate_vertex_array(&self, vao_id:Id) -> Result<(), Error> {
+            pub fn activate_vertex_array(&self, vao_id: VaoId) -> Result<(), Error> {
                 if Some(vao_id) != self.current_vao_id.get() {
                     if let Some(vao) = self.vao_lookup.get(vao_id) {
                         self._bind_vertex_array(Some(vao_id), Some(&vao))
                     } else {
-                        Err(Error::from(NativeError::VertexArrayMissing))
+                        Err(self.vao_lookup.labeled(vao_id, NativeError::VertexArrayMissing(None)))
                     }
                 } else {
                     Ok(())
                 }
             }
 
-            pub fn assign_vertex_array(&mut self, vao_id:Id, element_buffer_id:Option<Id>, configs:&[VertexArray]) -> Result<(), Error> {
+            pub fn assign_vertex_array(&mut self, vao_id: VaoId, element_buffer_id: Option<BufferId>, configs:&[VertexArray]) -> Result<(), Error> {
                 let result = if let Some(vao) = self.vao_lookup.get(vao_id) {
                     self._bind_vertex_array(Some(vao_id), Some(&vao))?;
 
@@ -72,7 +96,7 @@ macro_rules! impl_renderer {
                     }
                     Ok(())
                 } else {
-                    Err(Error::from(NativeError::VertexArrayMissing))
+                    Err(self.vao_lookup.labeled(vao_id, NativeError::VertexArrayMissing(None)))
                 };
 
                 //release it for the next call that might use elements
@@ -81,54 +105,123 @@ macro_rules! impl_renderer {
                 result
             }
 
+            //all the attributes are interleaved in buffer_id
+            pub fn assign_vertex_layout(&mut self, vao_id: VaoId, element_buffer_id: Option<BufferId>, buffer_id: BufferId, layout:&VertexLayout) -> Result<(), Error> {
+                let options = layout.attribute_options()?;
+
+                let configs:Vec<VertexArray> = options
+                    .into_iter()
+                    .map(|(name, opts)| VertexArray::new(NameOrLoc::Name(name), buffer_id, opts))
+                    .collect();
+
+                self.assign_vertex_array(vao_id, element_buffer_id, &configs)?;
+
+                //divisors are vao state too, and a reassigned vao may still hold instanced ones
+                let locations = layout.attributes
+                    .iter()
+                    .map(|attribute| Ok((self.get_attribute_location_name(&attribute.name)?, attribute.divisor)))
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                self.activate_vertex_array(vao_id)?;
+                let result = locations
+                    .into_iter()
+                    .try_for_each(|(loc, divisor)| self._vertex_attrib_divisor(loc, divisor));
+                self.release_vertex_array()?;
+
+                result
+            }
+
+            pub fn delete_vertex_array(&mut self, vao_id: VaoId) -> Result<(), Error> {
+                if Some(vao_id) == self.current_vao_id.get() {
+                    self.release_vertex_array()?;
+                }
+
+                let vao = self.vao_lookup.try_remove(vao_id, NativeError::VertexArrayMissing)?;
+
+                self._delete_vertex_array_handle(&vao)
+            }
+
+            //used by delete_all(), which can't fail halfway through
+            pub(super) fn _delete_all_vertex_arrays(&mut self) {
+                if self.current_vao_id.get().is_some() {
+                    let _ = self.release_vertex_array();
+                }
+
+                let vaos:Vec<WebGlVertexArrayObject> = self.vao_lookup.drain().collect();
+                for vao in vaos.iter() {
+                    let _ = self._delete_vertex_array_handle(vao);
+                }
+            }
+
+            pub fn create_vertex_array(&mut self) -> Result<VaoId, Error> {
+                let vao = self._create_vertex_array_handle()?;
+                let id = self.vao_lookup.insert(vao);
+                Ok(id)
+            }
+
+            pub fn create_vertex_array_labeled(&mut self, label: &str) -> Result<VaoId, Error> {
+                let id = self.create_vertex_array()?;
+                self.set_label(id, label)?;
+                Ok(id)
+            }
+
             $($defs)*
         })+
     };
 }
 
 impl_renderer! {
-    WebGlRenderingContext{
+    (impl WebGlRenderer<WebGlRenderingContext>) {
 
-        fn _bind_vertex_array(&self, id:Option<Id>, vao:Option<&WebGlVertexArrayObject>) -> Result<(), Error> {
+        fn _bind_vertex_array(&self, id: Option<VaoId>, vao:Option<&WebGlVertexArrayObject>) -> Result<(), Error> {
             let ext = self.get_extension_vertex_array()?;
             ext.bind_vertex_array_oes(vao);
             self.current_vao_id.set(id);
             Ok(())
         }
 
-        pub fn create_vertex_array(&mut self) -> Result<Id, Error> {
+        pub(super) fn _create_vertex_array_handle(&self) -> Result<WebGlVertexArrayObject, Error> {
             let ext = self.get_extension_vertex_array()?;
-            let vao = ext.create_vertex_array_oes().ok_or(Error::from(NativeError::VertexArrayCreate))?;
-            let id = self.vao_lookup.insert(vao);
-            Ok(id)
+            ext.create_vertex_array_oes().ok_or(Error::from(NativeError::VertexArrayCreate))
+        }
+
+        fn _vertex_attrib_divisor(&self, loc:u32, divisor:u32) -> Result<(), Error> {
+            match self.get_extension_instanced_arrays() {
+                Ok(ext) => {
+                    ext.vertex_attrib_divisor_angle(loc, divisor);
+                    Ok(())
+                },
+                //without the extension every divisor is already 0
+                Err(_) if divisor == 0 => Ok(()),
+                Err(err) => Err(err)
+            }
+        }
+
+        fn _delete_vertex_array_handle(&self, vao:&WebGlVertexArrayObject) -> Result<(), Error> {
+            let ext = self.get_extension_vertex_array()?;
+            ext.delete_vertex_array_oes(Some(vao));
+            Ok(())
         }
     }
-    WebGl2RenderingContext{
-        fn _bind_vertex_array(&self, id:Option<Id>, vao:Option<&WebGlVertexArrayObject>) -> Result<(), Error> {
-            self.gl.bind_vertex_array(vao);
+    (impl<T: WebGlCommon + WebGl2Extra> WebGlRenderer<T>) {
+        fn _bind_vertex_array(&self, id: Option<VaoId>, vao:Option<&WebGlVertexArrayObject>) -> Result<(), Error> {
+            self.gl.awsm_bind_vertex_array(vao);
             self.current_vao_id.set(id);
             Ok(())
         }
 
-        pub fn create_vertex_array(&mut self) -> Result<Id, Error> {
-            let vao = self.gl.create_vertex_array().ok_or(Error::from(NativeError::VertexArrayCreate))?;
-            let id = self.vao_lookup.insert(vao);
-            Ok(id)
+        pub(super) fn _create_vertex_array_handle(&self) -> Result<WebGlVertexArrayObject, Error> {
+            self.gl.awsm_create_vertex_array()
         }
 
-        pub fn delete_vertex_array(&self, vao_id: Id) -> Result<(), Error> {
-
-            if Some(vao_id) == self.current_vao_id.get() {
-                self.release_vertex_array()?;
-            }
-
-            if let Some(vao) = self.vao_lookup.get(vao_id) {
-                self.gl.delete_vertex_array(Some(vao));
-                Ok(())
-            } else {
-                Err(Error::from(NativeError::VertexArrayMissing))
-            }
+        fn _vertex_attrib_divisor(&self, loc:u32, divisor:u32) -> Result<(), Error> {
+            self.gl.awsm_vertex_attrib_divisor(loc, divisor);
+            Ok(())
+        }
 
+        fn _delete_vertex_array_handle(&self, vao:&WebGlVertexArrayObject) -> Result<(), Error> {
+            self.gl.awsm_delete_vertex_array(vao);
+            Ok(())
         }
     }
 }
@@ -0,0 +1,292 @@
+use super::{AttributeOptions, DataType};
+use crate::errors::{Error, NativeError};
+
+/*
+ * Interleaved attributes in a single buffer
+ *
+ * Offsets are packed in order, each aligned to its component size,
+ * and the stride is padded so every attribute stays aligned (WebGL requires both)
+ *
+ * assign_vertex_layout() in vertex_arrays.rs applies it to a vao
+ */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VertexAttribute {
+    pub name: String,
+    pub size: u8, //according to spec, must be 1,2,3,4
+    pub data_type: DataType,
+    pub normalized: bool,
+    //only for webgl2
+    pub is_int_array: bool,
+    //0 is per-vertex
+    pub divisor: u32,
+}
+
+impl VertexAttribute {
+    pub fn new(name: &str, size: u8, data_type: DataType) -> Self {
+        Self {
+            name: name.to_string(),
+            size,
+            data_type,
+            normalized: false,
+            is_int_array: false,
+            divisor: 0,
+        }
+    }
+    pub fn new_int(name: &str, size: u8, data_type: DataType) -> Self {
+        Self {
+            is_int_array: true,
+            ..Self::new(name, size, data_type)
+        }
+    }
+    pub fn normalized(mut self) -> Self {
+        self.normalized = true;
+        self
+    }
+    pub fn divisor(mut self, divisor: u32) -> Self {
+        self.divisor = divisor;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VertexLayout {
+    pub attributes: Vec<VertexAttribute>,
+}
+
+pub(super) fn data_type_size(data_type: DataType) -> u32 {
+    match data_type {
+        DataType::Byte | DataType::UnsignedByte => 1,
+        DataType::Short | DataType::UnsignedShort | DataType::HalfFloat => 2,
+        DataType::Int | DataType::UnsignedInt | DataType::Float => 4,
+    }
+}
+
+fn round_up(value: u32, align: u32) -> u32 {
+    (value + align - 1) / align * align
+}
+
+fn layout_error(desc: String) -> Error {
+    Error::from(NativeError::VertexLayout(Some(desc)))
+}
+
+impl VertexLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_attribute(mut self, attribute: VertexAttribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    pub fn offsets(&self) -> Vec<u32> {
+        let mut end = 0;
+        self.attributes
+            .iter()
+            .map(|attribute| {
+                let offset = round_up(end, data_type_size(attribute.data_type));
+                end = offset + attribute.size as u32 * data_type_size(attribute.data_type);
+                offset
+            })
+            .collect()
+    }
+
+    pub fn stride(&self) -> u32 {
+        let end = self
+            .attributes
+            .iter()
+            .zip(self.offsets())
+            .map(|(attribute, offset)| offset + attribute.size as u32 * data_type_size(attribute.data_type))
+            .max()
+            .unwrap_or(0);
+
+        let align = self
+            .attributes
+            .iter()
+            .map(|attribute| data_type_size(attribute.data_type))
+            .max()
+            .unwrap_or(1);
+
+        round_up(end, align)
+    }
+
+    //one entry per attribute, ready for assign_vertex_array()
+    pub fn attribute_options(&self) -> Result<Vec<(&str, AttributeOptions)>, Error> {
+        let stride = self.stride();
+        if stride > 255 {
+            return Err(layout_error(format!("stride is {} bytes (max is 255)", stride)));
+        }
+
+        let mut names: Vec<&str> = Vec::with_capacity(self.attributes.len());
+
+        self.attributes
+            .iter()
+            .zip(self.offsets())
+            .map(|(attribute, offset)| {
+                if attribute.size < 1 || attribute.size > 4 {
+                    return Err(layout_error(format!("{} has {} components (must be 1-4)", attribute.name, attribute.size)));
+                }
+                if attribute.is_int_array && (attribute.data_type == DataType::Float || attribute.data_type == DataType::HalfFloat) {
+                    return Err(layout_error(format!("{} is an int attribute with {:?} data", attribute.name, attribute.data_type)));
+                }
+                if names.contains(&attribute.name.as_str()) {
+                    return Err(layout_error(format!("{} is in the layout twice", attribute.name)));
+                }
+                names.push(&attribute.name);
+
+                Ok((
+                    attribute.name.as_str(),
+                    AttributeOptions {
+                        size: attribute.size,
+                        data_type: attribute.data_type,
+                        normalized: attribute.normalized,
+                        stride: stride as u8,
+                        offset: offset as u64,
+                        is_int_array: attribute.is_int_array,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+//count is how many elements are read from the buffer (vertices, or instances / divisor)
+pub fn validate_vertex_layout(layout: &VertexLayout, buffer_len: usize, count: u32) -> Result<(), Error> {
+    if layout.attributes.is_empty() {
+        return Err(layout_error("the layout has no attributes".to_string()));
+    }
+
+    let options = layout.attribute_options()?;
+
+    if count == 0 {
+        return Ok(());
+    }
+
+    //the last element doesn't need the padding at the end of the stride
+    let last_end = options
+        .iter()
+        .map(|(_, opts)| opts.offset + opts.size as u64 * data_type_size(opts.data_type) as u64)
+        .max()
+        .unwrap_or(0);
+
+    let required = (count as u64 - 1) * layout.stride() as u64 + last_end;
+
+    if (buffer_len as u64) < required {
+        return Err(layout_error(format!(
+            "{} elements need {} bytes but the buffer has {}",
+            count, required, buffer_len
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl::{GlCommand, MockContext, MockProgramInterface, ShaderType, UniformDataType, WebGlRenderer, WebGlVersion};
+
+    fn layout() -> VertexLayout {
+        VertexLayout::new()
+            .with_attribute(VertexAttribute::new("a_position", 3, DataType::Float))
+            .with_attribute(VertexAttribute::new("a_color", 3, DataType::UnsignedByte).normalized())
+            .with_attribute(VertexAttribute::new("a_uv", 2, DataType::UnsignedShort).normalized())
+            .with_attribute(VertexAttribute::new_int("a_joint", 1, DataType::UnsignedByte))
+    }
+
+    #[test]
+    fn test_vertex_layout() {
+        let layout = layout();
+        assert_eq!(layout.offsets(), [0, 12, 16, 20]);
+        assert_eq!(layout.stride(), 24);
+
+        let options = layout.attribute_options().unwrap();
+        assert_eq!(options[2].0, "a_uv");
+        assert_eq!((options[2].1.stride, options[2].1.offset), (24, 16));
+        assert!(options[1].1.normalized);
+        assert!(options[3].1.is_int_array);
+
+        assert!(validate_vertex_layout(&layout, 24 * 3, 3).is_ok());
+        //the last vertex can skip the trailing padding
+        assert!(validate_vertex_layout(&layout, 24 * 2 + 21, 3).is_ok());
+        assert!(validate_vertex_layout(&layout, 24 * 2 + 20, 3).is_err());
+        assert!(validate_vertex_layout(&layout, 0, 0).is_ok());
+        assert!(validate_vertex_layout(&VertexLayout::new(), 64, 1).is_err());
+
+        let bad_size = VertexLayout::new().with_attribute(VertexAttribute::new("a_position", 5, DataType::Float));
+        assert!(bad_size.attribute_options().is_err());
+
+        let bad_int = VertexLayout::new().with_attribute(VertexAttribute::new_int("a_index", 1, DataType::Float));
+        assert!(bad_int.attribute_options().is_err());
+
+        let duplicate = layout.clone().with_attribute(VertexAttribute::new("a_uv", 2, DataType::Float));
+        assert!(duplicate.attribute_options().is_err());
+
+        let too_wide = (0..17).fold(VertexLayout::new(), |layout, index| {
+            layout.with_attribute(VertexAttribute::new(&format!("a_{}", index), 4, DataType::Float))
+        });
+        assert!(too_wide.attribute_options().is_err());
+    }
+
+    #[test]
+    fn test_assign_vertex_layout() {
+        let mut renderer = WebGlRenderer::new(MockContext::new(WebGlVersion::Two)).unwrap();
+        *renderer.gl.program_interface.borrow_mut() = MockProgramInterface {
+            attributes: vec![
+                ("a_position".to_string(), UniformDataType::FloatVec3, 1),
+                ("a_offset".to_string(), UniformDataType::FloatVec2, 1),
+            ],
+            ..MockProgramInterface::default()
+        };
+        let vertex = renderer.compile_shader("void main() {}", ShaderType::Vertex).unwrap();
+        let fragment = renderer.compile_shader("void main() {}", ShaderType::Fragment).unwrap();
+        renderer.compile_program(&[vertex, fragment]).unwrap();
+
+        let vao = renderer.create_vertex_array().unwrap();
+        let buffer = renderer.create_buffer().unwrap();
+
+        let layout = VertexLayout::new()
+            .with_attribute(VertexAttribute::new("a_position", 3, DataType::Float))
+            .with_attribute(VertexAttribute::new("a_offset", 2, DataType::Float).divisor(1));
+
+        renderer.gl.clear_commands();
+        renderer.assign_vertex_layout(vao, None, buffer, &layout).unwrap();
+
+        let commands = renderer.gl.take_commands();
+        assert!(commands.contains(&GlCommand::ActivateAttribute {
+            loc: 1,
+            size: 2,
+            data_type: DataType::Float,
+            normalized: false,
+            stride: 20,
+            offset: 12,
+            is_int_array: false,
+        }));
+        assert_eq!(&commands[commands.len() - 4..], [
+            GlCommand::BindVertexArray,
+            GlCommand::VertexAttribDivisor(0, 0),
+            GlCommand::VertexAttribDivisor(1, 1),
+            GlCommand::ReleaseVertexArray,
+        ]);
+
+        //reassigning without instancing has to reset the divisor left behind
+        let per_vertex = VertexLayout::new()
+            .with_attribute(VertexAttribute::new("a_position", 3, DataType::Float))
+            .with_attribute(VertexAttribute::new("a_offset", 2, DataType::Float));
+
+        renderer.gl.clear_commands();
+        renderer.assign_vertex_layout(vao, None, buffer, &per_vertex).unwrap();
+
+        let commands = renderer.gl.take_commands();
+        assert_eq!(&commands[commands.len() - 4..], [
+            GlCommand::BindVertexArray,
+            GlCommand::VertexAttribDivisor(0, 0),
+            GlCommand::VertexAttribDivisor(1, 0),
+            GlCommand::ReleaseVertexArray,
+        ]);
+
+        let missing = layout.with_attribute(VertexAttribute::new("a_missing", 1, DataType::Float));
+        assert!(renderer.assign_vertex_layout(vao, None, buffer, &missing).is_err());
+    }
+}
@@ -3,7 +3,7 @@ use crate::scenes::webgl::common::*;
 use crate::start_webgl;
 use awsm_web::loaders::image;
 use awsm_web::webgl::{
-    BeginMode, BlendFactor, BufferMask, GlToggle, Id, PixelFormat, SimpleTextureOptions,
+    BeginMode, BlendFactor, BufferMask, GlToggle, TextureId, ProgramId, PixelFormat, SimpleTextureOptions,
     TextureTarget, WebGlTextureSource,
     WebGlVersion,
     ShaderType
@@ -22,9 +22,9 @@ struct State {
     pub area: Area,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub bottom_texture_id: Option<Id>,
-    pub top_texture_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub bottom_texture_id: Option<TextureId>,
+    pub top_texture_id: Option<TextureId>,
 }
 
 impl State {
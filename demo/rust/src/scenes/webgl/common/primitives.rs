@@ -1,6 +1,6 @@
 use awsm_web::errors::Error;
 use awsm_web::webgl::{
-    AttributeOptions, BufferData, BufferTarget, BufferUsage, DataType, Id, WebGlCommon,
+    AttributeOptions, BufferData, BufferTarget, BufferUsage, DataType, BufferId, WebGlCommon,
     WebGlRenderer,
 };
 
@@ -42,7 +42,7 @@ pub static N_BOX_ELEMENTS: u32 = 36;
 
 pub fn create_and_assign_unit_quad_buffer<T: WebGlCommon>(
     webgl_renderer: &mut WebGlRenderer<T>,
-) -> Result<Id, Error> {
+) -> Result<BufferId, Error> {
     let buffer_id = webgl_renderer.create_buffer()?;
 
     webgl_renderer.upload_buffer_to_attribute_name(
@@ -61,7 +61,7 @@ pub fn create_and_assign_unit_quad_buffer<T: WebGlCommon>(
 
 pub fn create_unit_box_buffers<T: WebGlCommon>(
     webgl_renderer: &mut WebGlRenderer<T>,
-) -> Result<(Id, Id, Id), Error> {
+) -> Result<(BufferId, BufferId, BufferId), Error> {
     let geom_id = webgl_renderer.create_buffer()?;
     let colors_id = webgl_renderer.create_buffer()?;
     let elements_id = webgl_renderer.create_buffer()?;
@@ -1,6 +1,6 @@
 use crate::scenes::webgl::common::*;
 use crate::start_webgl;
-use awsm_web::webgl::{BeginMode, BufferMask, Id, WebGlCommon, WebGlRenderer,
+use awsm_web::webgl::{BeginMode, BufferMask, TextureId, ProgramId, FramebufferId, RenderbufferId, WebGlCommon, WebGlRenderer,
     PixelFormat, SimpleTextureOptions, TextureTarget,
     WebGlTextureSource,
     RenderBufferFormat,
@@ -28,8 +28,8 @@ struct State {
     pub area: Area,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub texture_program_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub texture_program_id: Option<ProgramId>,
 }
 
 impl State {
@@ -277,7 +277,7 @@ pub fn draw_positions<T: WebGlCommon> (renderer:&mut WebGlRenderer<T>, camera_ma
         renderer.draw_arrays(BeginMode::TriangleStrip, 0, 4);
     }
 }
-pub fn blit<T: WebGlCommon> (renderer:&mut WebGlRenderer<T>, camera_mat: &Matrix4<f32>, scaling_mat: &Matrix4<f32>, texture_id: Id) {
+pub fn blit<T: WebGlCommon> (renderer:&mut WebGlRenderer<T>, camera_mat: &Matrix4<f32>, scaling_mat: &Matrix4<f32>, texture_id: TextureId) {
 
         //Upload matrices to the GPU
         let mvp_mat = camera_mat;
@@ -325,11 +325,11 @@ impl State {
 }
 
 struct FrameBufferPicker {
-    _hidden_texture_id: Id,
-    visible_texture_id: Id,
-    _renderbuffer_id: Id,
-    write_framebuffer_id: Id,
-    read_framebuffer_id: Id,
+    _hidden_texture_id: TextureId,
+    visible_texture_id: TextureId,
+    _renderbuffer_id: RenderbufferId,
+    write_framebuffer_id: FramebufferId,
+    read_framebuffer_id: FramebufferId,
 }
 
 //see: https://stackoverflow.com/questions/21841483/webgl-using-framebuffers-for-picking-multiple-objects
@@ -402,7 +402,7 @@ impl FrameBufferPicker {
         })
     }
 
-    pub fn bind<T: WebGlCommon> (&self, renderer:&mut WebGlRenderer<T>, id: Id) -> Result<(), awsm_web::errors::Error> {
+    pub fn bind<T: WebGlCommon> (&self, renderer:&mut WebGlRenderer<T>, id: FramebufferId) -> Result<(), awsm_web::errors::Error> {
         renderer.bind_framebuffer(id, FrameBufferTarget::FrameBuffer)
         //note - if the framebuffer *didn't* equal window size, set viewport to framebuffer size here
     }
@@ -1,7 +1,7 @@
 use crate::scenes::webgl::common::*;
 use crate::start_webgl;
 use awsm_web::webgl::{
-    AttributeOptions, BeginMode, BufferTarget, BufferMask, DataType, GlToggle, Id,WebGlVersion, ShaderType
+    AttributeOptions, BeginMode, BufferTarget, BufferMask, DataType, GlToggle, BufferId, ProgramId,WebGlVersion, ShaderType
 };
 use nalgebra::{Isometry3, Matrix4, Perspective3, Point3, Vector3};
 use std::cell::RefCell;
@@ -9,14 +9,14 @@ use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 use web_sys::{Document, HtmlElement, Window};
 
-type BufferIds = (Id, Id, Id);
+type BufferIds = (BufferId, BufferId, BufferId);
 struct State {
     //mutable for each tick
     pub pos: Point3<f64>,
     pub volume: Volume,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
     pub buffer_ids: Option<BufferIds>,
 }
 
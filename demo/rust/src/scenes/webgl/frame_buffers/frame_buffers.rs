@@ -1,6 +1,6 @@
 use crate::scenes::webgl::common::*;
 use crate::start_webgl;
-use awsm_web::webgl::{BeginMode, BufferMask, Id, WebGlCommon, WebGlRenderer,
+use awsm_web::webgl::{BeginMode, BufferMask, TextureId, ProgramId, FramebufferId, RenderbufferId, WebGlCommon, WebGlRenderer,
     PixelFormat, SimpleTextureOptions, TextureTarget,
     WebGlTextureSource,
     RenderBufferFormat,
@@ -27,7 +27,7 @@ struct State {
     pub area: Area,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
 }
 
 impl State {
@@ -261,9 +261,9 @@ impl State {
 }
 
 struct FrameBufferPicker {
-    _texture_id: Id,
-    _renderbuffer_id: Id,
-    framebuffer_id: Id,
+    _texture_id: TextureId,
+    _renderbuffer_id: RenderbufferId,
+    framebuffer_id: FramebufferId,
 }
 
 //see: https://stackoverflow.com/questions/21841483/webgl-using-framebuffers-for-picking-multiple-objects
@@ -4,7 +4,7 @@ use crate::start_webgl;
 use awsm_web::loaders::image;
 use awsm_web::webgl::{
     AttributeOptions, BeginMode, BufferData, BufferTarget, BufferUsage, BufferMask, DataType,
-    Id, PixelFormat, SimpleTextureOptions, TextureTarget, WebGlTextureSource,
+    BufferId, TextureId, ProgramId, PixelFormat, SimpleTextureOptions, TextureTarget, WebGlTextureSource,
     WebGlVersion,
     ShaderType
 
@@ -23,9 +23,9 @@ struct State {
     pub area: Area,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub texture_id: Option<Id>,
-    pub instance_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub texture_id: Option<TextureId>,
+    pub instance_id: Option<BufferId>,
 }
 
 impl State {
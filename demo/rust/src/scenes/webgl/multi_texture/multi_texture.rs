@@ -3,7 +3,7 @@ use crate::scenes::webgl::common::*;
 use crate::start_webgl;
 use awsm_web::loaders::image;
 use awsm_web::webgl::{
-    BeginMode, BufferMask, Id, PixelFormat, SimpleTextureOptions, TextureTarget,
+    BeginMode, BufferMask, TextureId, ProgramId, PixelFormat, SimpleTextureOptions, TextureTarget,
     WebGlTextureSource,
     WebGlVersion,
     ShaderType
@@ -23,9 +23,9 @@ struct State {
     pub area: Area,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub texture1_id: Option<Id>,
-    pub texture2_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub texture1_id: Option<TextureId>,
+    pub texture2_id: Option<TextureId>,
 }
 
 impl State {
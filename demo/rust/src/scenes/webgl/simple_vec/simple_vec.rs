@@ -1,6 +1,6 @@
 use crate::scenes::webgl::common::*;
 use crate::start_webgl;
-use awsm_web::webgl::{BeginMode, BufferMask, Id, GlToggle, BlendFactor, WebGlVersion, ShaderType};
+use awsm_web::webgl::{BeginMode, BufferMask, ProgramId, GlToggle, BlendFactor, WebGlVersion, ShaderType};
 use nalgebra::{Matrix4, Point2, Vector3};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -15,7 +15,7 @@ struct State {
     pub camera_width: f64,
     pub camera_height: f64,
     pub direction: f64,
-    pub program_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
 }
 
 impl State {
@@ -3,7 +3,7 @@ use crate::scenes::webgl::common::*;
 use crate::start_webgl;
 use awsm_web::loaders::image;
 use awsm_web::webgl::{
-    BeginMode, BufferMask, Id, PixelFormat, SimpleTextureOptions, TextureTarget,
+    BeginMode, BufferMask, TextureId, ProgramId, PixelFormat, SimpleTextureOptions, TextureTarget,
     WebGlTextureSource,
     WebGlVersion,
     ShaderType
@@ -23,8 +23,8 @@ struct State {
     pub area: Area,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub texture_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub texture_id: Option<TextureId>,
 }
 
 impl State {
@@ -4,7 +4,7 @@ use awsm_web::data::TypedData;
 use awsm_web::loaders::{fetch::fetch_url, image};
 use awsm_web::webgl::PartialWebGlTextures;
 use awsm_web::webgl::{
-    BeginMode, BufferMask, DataType, Id, PixelFormat, SimpleTextureOptions, TextureMagFilter,
+    BeginMode, BufferMask, DataType, TextureId, ProgramId, PixelFormat, SimpleTextureOptions, TextureMagFilter,
     TextureMinFilter, TextureOptions, TextureTarget, TextureWrapMode, TextureWrapTarget,
     WebGlTextureSource,
     ShaderType,
@@ -29,9 +29,9 @@ struct State {
     pub area: Area,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub diffuse_texture_id: Option<Id>,
-    pub lut_texture_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub diffuse_texture_id: Option<TextureId>,
+    pub lut_texture_id: Option<TextureId>,
 }
 
 impl State {
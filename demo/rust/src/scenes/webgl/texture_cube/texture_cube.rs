@@ -4,7 +4,7 @@ use crate::start_webgl;
 use awsm_web::errors::Error;
 use awsm_web::loaders::image;
 use awsm_web::webgl::{
-    AttributeOptions, BeginMode, BufferMask, DataType, Id, SimpleTextureOptions,
+    AttributeOptions, BeginMode, BufferMask, DataType, TextureId, ProgramId, VaoId, SimpleTextureOptions,
     TextureCubeFace, TextureTarget, VertexArray, WebGlTextureSource,
     WebGlVersion,
     ShaderType,
@@ -24,9 +24,9 @@ struct State {
     //mutable for each tick
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub texture_id: Option<Id>,
-    pub vao_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub texture_id: Option<TextureId>,
+    pub vao_id: Option<VaoId>,
 }
 
 impl State {
@@ -2,7 +2,7 @@ use crate::scenes::webgl::common::*;
 use awsm_web::errors::Error;
 use awsm_web::webgl::{
     AttributeOptions, BeginMode, BufferData, BufferTarget, BufferUsage, BufferMask, DataType,
-    GlToggle, Id, VertexArray, WebGl2Renderer,
+    GlToggle, BufferId, ProgramId, VaoId, VertexArray, WebGl2Renderer,
     ShaderType,
     NameOrLoc
 
@@ -27,11 +27,11 @@ struct State {
     pub volume: Volume,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub vao_id: Option<Id>,
-    pub model_buffer_id: Option<Id>,
-    pub camera_buffer_id: Option<Id>,
-    pub scale_buffer_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub vao_id: Option<VaoId>,
+    pub model_buffer_id: Option<BufferId>,
+    pub camera_buffer_id: Option<BufferId>,
+    pub scale_buffer_id: Option<BufferId>,
     pub ticks: usize,
 }
 
@@ -188,7 +188,7 @@ pub fn start(window: Window, document: Document, body: HtmlElement) -> Result<()
 }
 
 fn set_initial_scale_buffer(
-    scale_buffer_id: Id,
+    scale_buffer_id: BufferId,
     webgl_renderer: &WebGl2Renderer,
 ) -> Result<(), Error> {
     //Upload them to the GPU as a UBO
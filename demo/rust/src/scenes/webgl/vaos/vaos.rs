@@ -1,7 +1,7 @@
 use crate::scenes::webgl::common::*;
 use crate::start_webgl;
 use awsm_web::webgl::{
-    AttributeOptions, BeginMode, BufferMask, DataType, GlToggle, Id, VertexArray,
+    AttributeOptions, BeginMode, BufferMask, DataType, GlToggle, ProgramId, VaoId, VertexArray,
     WebGlVersion,
     ShaderType,
     NameOrLoc
@@ -19,8 +19,8 @@ struct State {
     pub volume: Volume,
     pub camera_width: f64,
     pub camera_height: f64,
-    pub program_id: Option<Id>,
-    pub vao_id: Option<Id>,
+    pub program_id: Option<ProgramId>,
+    pub vao_id: Option<VaoId>,
 }
 
 impl State {